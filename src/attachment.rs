@@ -0,0 +1,171 @@
+//! Documents attached to applications
+//! Candidates attach their résumé, cover letter or portfolio to an application.
+//! The content is addressed by its SHA-256, so documents uploaded more than once are stored once.
+//! It's kept in a directory on the local filesystem when one is configured, or in the store otherwise.
+use crate::{Id, StoreError, StoreResult, Timestamp};
+use std::path::{Path, PathBuf};
+
+/// Largest document accepted unless configured otherwise, 5 MiB
+pub const DEFAULT_MAX_ATTACHMENT_SIZE: u64 = 5 * 1024 * 1024;
+/// MIME types accepted unless configured otherwise
+pub const DEFAULT_ATTACHMENT_TYPES: &[&str] = &[
+    "application/pdf",
+    "application/msword",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "text/plain",
+    "text/markdown",
+];
+/// Longest file name an attachment can have, in characters
+pub const MAX_FILE_NAME_LEN: usize = 255;
+
+/// What the document is
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DocumentKind {
+    Resume = 0,
+    CoverLetter = 1,
+    Portfolio = 2,
+    Other = 3,
+}
+
+impl std::convert::TryFrom<u8> for DocumentKind {
+    type Error = u8;
+
+    fn try_from(i: u8) -> Result<Self, Self::Error> {
+        match i {
+            0 => Ok(Self::Resume),
+            1 => Ok(Self::CoverLetter),
+            2 => Ok(Self::Portfolio),
+            3 => Ok(Self::Other),
+            i => Err(i),
+        }
+    }
+}
+
+impl std::fmt::Display for DocumentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Resume => write!(f, "Résumé"),
+            Self::CoverLetter => write!(f, "Cover letter"),
+            Self::Portfolio => write!(f, "Portfolio"),
+            Self::Other => write!(f, "Other"),
+        }
+    }
+}
+
+/// A document attached to the application of `candidate_id` to `job_id`, without its content
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Attachment {
+    pub id: Id,
+    pub job_id: Id,
+    pub candidate_id: Id,
+    pub kind: DocumentKind,
+    /// Name of the uploaded file, without directories
+    pub file_name: String,
+    pub mime_type: String,
+    /// Size of the content in bytes
+    pub size: u64,
+    /// Hex encoded SHA-256 of the content, the address it's stored at
+    pub sha256: String,
+    pub uploaded_at: Timestamp,
+}
+
+impl std::fmt::Display for Attachment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} {}({}, {} bytes) uploaded at {}",
+            self.id, self.kind, self.file_name, self.mime_type, self.size, self.uploaded_at
+        )
+    }
+}
+
+/// Hex encoded SHA-256 of `content`
+pub fn content_hash(content: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, content)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Checks the file name is usable and the content looks like `mime_type`.
+/// Formats with a known signature must start with it, text has to be UTF-8, other types aren't checked
+pub fn check_document(file_name: &str, mime_type: &str, content: &[u8]) -> Result<(), String> {
+    if file_name.trim().is_empty() {
+        return Err("The file needs a name".into());
+    }
+    if file_name.chars().count() > MAX_FILE_NAME_LEN {
+        return Err(format!(
+            "The file name can't be longer than {} characters",
+            MAX_FILE_NAME_LEN
+        ));
+    }
+    if file_name.contains(['/', '\\']) || file_name == "." || file_name == ".." {
+        return Err(format!("{} isn't a file name", file_name));
+    }
+    if content.is_empty() {
+        return Err("The file is empty".into());
+    }
+    let signature: &[u8] = match mime_type {
+        "application/pdf" => b"%PDF-",
+        // Compound File Binary, the format of the old Office documents
+        "application/msword" => b"\xd0\xcf\x11\xe0",
+        // Office Open XML documents are zip files
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => b"PK\x03\x04",
+        "image/png" => b"\x89PNG",
+        "image/jpeg" => b"\xff\xd8\xff",
+        "application/zip" => b"PK\x03\x04",
+        _ => b"",
+    };
+    if !content.starts_with(signature) {
+        return Err(format!("The file isn't a valid {} document", mime_type));
+    }
+    if mime_type.starts_with("text/") && std::str::from_utf8(content).is_err() {
+        return Err(format!("The file isn't UTF-8 {}", mime_type));
+    }
+    Ok(())
+}
+
+/// Content stored in a directory, each document at `<first 2 hex digits>/<hash>` so directories stay small
+pub struct ContentDir<'a> {
+    pub root: &'a Path,
+}
+
+impl ContentDir<'_> {
+    pub fn path(&self, sha256: &str) -> PathBuf {
+        self.root.join(&sha256[..2]).join(sha256)
+    }
+
+    /// Stores the content unless it's already there, it's written to a temporary file first so it's never partially visible
+    pub fn put(&self, sha256: &str, content: &[u8]) -> StoreResult<()> {
+        let path = self.path(sha256);
+        if path.exists() {
+            return Ok(());
+        }
+        let dir = self.root.join(&sha256[..2]);
+        std::fs::create_dir_all(&dir).map_err(io_error)?;
+        let temp = dir.join(format!(".{}.tmp", sha256));
+        std::fs::write(&temp, content).map_err(io_error)?;
+        std::fs::rename(&temp, &path).map_err(io_error)
+    }
+
+    pub fn get(&self, sha256: &str) -> StoreResult<Vec<u8>> {
+        std::fs::read(self.path(sha256)).map_err(io_error)
+    }
+
+    /// Returns the number of deleted files(0 if it wasn't there)
+    pub fn delete(&self, sha256: &str) -> StoreResult<usize> {
+        match std::fs::remove_file(self.path(sha256)) {
+            Ok(()) => Ok(1),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(io_error(e)),
+        }
+    }
+}
+
+fn io_error(e: std::io::Error) -> StoreError {
+    match e.kind() {
+        std::io::ErrorKind::NotFound => StoreError::NotFound,
+        _ => StoreError::Backend(Box::new(e)),
+    }
+}
@@ -0,0 +1,120 @@
+//! User roles and what each of them is allowed to do
+use std::convert::TryFrom;
+
+/// Role of a user, stored in the DB next to the user
+/// Note: New users are always candidates, only admins can change roles
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Role {
+    #[default]
+    Candidate = 0,
+    Recruiter = 1,
+    HiringManager = 2,
+    Admin = 3,
+}
+
+// Unknown roles aren't taken as any role, not even the least privileged one
+impl TryFrom<u8> for Role {
+    type Error = u8;
+
+    fn try_from(i: u8) -> Result<Self, u8> {
+        match i {
+            0 => Ok(Self::Candidate),
+            1 => Ok(Self::Recruiter),
+            2 => Ok(Self::HiringManager),
+            3 => Ok(Self::Admin),
+            i => Err(i),
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Candidate => write!(f, "Candidate"),
+            Self::Recruiter => write!(f, "Recruiter"),
+            Self::HiringManager => write!(f, "Hiring Manager"),
+            Self::Admin => write!(f, "Admin"),
+        }
+    }
+}
+
+/// Every operation of the system that requires a permission
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Action {
+    ListJobs,
+    CreateJob,
+    Apply,
+    Interview,
+    Approve,
+    Reject,
+    Withdraw,
+    ManageOffers,
+    AnswerOffers,
+    ManageUsers,
+    ManagePipelines,
+    EditProfile,
+    ViewProfiles,
+    AttachDocuments,
+    ManageDocuments,
+    RunMaintenance,
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ListJobs => write!(f, "list jobs"),
+            Self::CreateJob => write!(f, "create jobs"),
+            Self::Apply => write!(f, "apply to jobs"),
+            Self::Interview => write!(f, "interview candidates"),
+            Self::Approve => write!(f, "approve candidates"),
+            Self::Reject => write!(f, "reject candidates"),
+            Self::Withdraw => write!(f, "withdraw applications"),
+            Self::ManageOffers => write!(f, "manage offers"),
+            Self::AnswerOffers => write!(f, "answer offers"),
+            Self::ManageUsers => write!(f, "manage users"),
+            Self::ManagePipelines => write!(f, "manage pipelines"),
+            Self::EditProfile => write!(f, "edit profiles"),
+            Self::ViewProfiles => write!(f, "view candidate profiles"),
+            Self::AttachDocuments => write!(f, "attach documents"),
+            Self::ManageDocuments => write!(f, "manage documents"),
+            Self::RunMaintenance => write!(f, "run the maintenance"),
+        }
+    }
+}
+
+impl Role {
+    /// The permission matrix
+    /// | Action          | Candidate | Recruiter | Hiring Manager | Admin |
+    /// |-----------------|-----------|-----------|----------------|-------|
+    /// | ListJobs        | x         | x         | x              | x     |
+    /// | Apply           | x         |           |                |       |
+    /// | Withdraw        | x         |           |                |       |
+    /// | AnswerOffers    | x         |           |                |       |
+    /// | EditProfile     | x         |           |                |       |
+    /// | AttachDocuments | x         |           |                |       |
+    /// | CreateJob       |           | x         | x              | x     |
+    /// | Interview       |           | x         | x              | x     |
+    /// | ViewProfiles    |           | x         | x              | x     |
+    /// | Approve         |           |           | x              | x     |
+    /// | Reject          |           |           | x              | x     |
+    /// | ManageOffers    |           |           | x              | x     |
+    /// | ManagePipelines |           |           | x              | x     |
+    /// | ManageUsers     |           |           |                | x     |
+    /// | ManageDocuments |           |           |                | x     |
+    /// | RunMaintenance  |           |           |                | x     |
+    pub fn can(self, action: Action) -> bool {
+        use Action::*;
+        use Role::*;
+        match action {
+            ListJobs => true,
+            Apply | Withdraw | AnswerOffers | EditProfile | AttachDocuments => self == Candidate,
+            CreateJob | Interview | ViewProfiles => {
+                matches!(self, Recruiter | HiringManager | Admin)
+            }
+            Approve | Reject | ManageOffers | ManagePipelines => {
+                matches!(self, HiringManager | Admin)
+            }
+            ManageUsers | ManageDocuments | RunMaintenance => self == Admin,
+        }
+    }
+}
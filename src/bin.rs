@@ -1,6 +1,11 @@
-use hrsystem::{LoggedUser, System};
+use hrsystem::{
+    config::{Config, CONFIG_ENV},
+    Action, Answer, CandidateSearch, DocumentKind, EmploymentType, InterviewStatus, JobDetails,
+    JobSearch, JobSkill, JobState, Knockout, LoggedUser, Proficiency, Profile, Question,
+    QuestionKind, Recommendation, Role, Scorecard, Seniority, System, Workplace, YearMonth,
+};
 use lazy_static::lazy_static;
-use promptly::prompt;
+use promptly::{prompt, prompt_default, prompt_opt};
 use std::sync::Mutex;
 
 // This is of course not secure. But I'll not focus on this now.
@@ -9,38 +14,168 @@ lazy_static! {
 }
 
 fn main() {
-    main_menu();
+    // The settings are read by the System the first time it's used, so this must happen before
+    let mut args = std::env::args().skip(1);
+    let mut maintenance = false;
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--config", Some(path)) => std::env::set_var(CONFIG_ENV, path),
+            ("maintenance", None) => maintenance = true,
+            _ => {
+                println!("Usage: cli [--config <path to config file>] [maintenance]");
+                println!("  maintenance: closes the jobs whose application window ended, expires offers and exits.");
+                println!("    Logs in as the admin in the maintenance_user and maintenance_password settings");
+                return;
+            }
+        }
+    }
+    if maintenance {
+        // Meant to run unattended, so the credentials come from the settings
+        let (user, pass) = match Config::load() {
+            Ok(Config {
+                maintenance_user: Some(user),
+                maintenance_password: Some(pass),
+                ..
+            }) => (user, pass),
+            Ok(_) => {
+                println!("Set maintenance_user and maintenance_password to run the maintenance");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                println!("There was an error reading the settings: {}", e);
+                std::process::exit(1);
+            }
+        };
+        let result = System::login(&user, &pass).and_then(|admin| {
+            let report = System::run_maintenance(&admin);
+            let _ = System::logout(&admin);
+            report
+        });
+        match result {
+            Ok(report) => println!("{}", report),
+            Err(e) => {
+                println!("There was an error running the maintenance: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        main_menu();
+    }
 }
 
 fn main_menu() {
+    let mut page = 0;
     loop {
-        println!("Available Jobs:");
-        print_jobs();
-        println!("");
-        if TOKEN
-            .lock()
-            .expect("Should be used in single-thread for now")
-            .is_none()
-        {
-            match print_options(&["Add Job", "Register", "Login", "Advance Process"]) {
-                0 => job_menu(),
-                1 => register(),
-                2 => login_menu(),
-                3 => advance_process_menu(),
-                _ => panic!("Print options should never return an option beyond the option length"),
+        let role = {
+            let mut token = TOKEN
+                .lock()
+                .expect("Should be used in single-thread for now");
+            // Expired or revoked sessions go back to the login menu
+            if let Some(logged_user) = token.as_ref() {
+                if System::check_token(logged_user).is_err() {
+                    println!("The session ended, login again");
+                    *token = None;
+                }
+            }
+            token.as_ref().map(|logged_user| logged_user.role)
+        };
+        if let Some(role) = role {
+            println!("Available Jobs(page {}):", page + 1);
+            print_jobs(page);
+            println!();
+
+            // Options depend on what the role can do
+            let mut options = vec!["Next Jobs page", "Previous Jobs page", "Search jobs"];
+            if role.can(Action::Apply) {
+                options.push("Apply");
+                options.push("Suggested jobs");
+            }
+            if role.can(Action::Withdraw) {
+                options.push("Withdraw application");
+            }
+            if role.can(Action::AnswerOffers) {
+                options.push("My offers");
+            }
+            if role.can(Action::EditProfile) {
+                options.push("My profile");
+            }
+            if role.can(Action::CreateJob) {
+                options.push("Add Job");
+                options.push("Edit Job");
+                options.push("Job lifecycle");
+                options.push("Screening questions");
+                options.push("Job skills");
+            }
+            if role.can(Action::Interview) {
+                options.push("Advance Process");
+                options.push("Interviews");
+                options.push("Job report");
+            }
+            if role.can(Action::ViewProfiles) {
+                options.push("Applicant profiles");
+                options.push("Applicant answers");
+                options.push("Rank applicants");
+                options.push("Search candidates");
+            }
+            if role.can(Action::AttachDocuments) || role.can(Action::ViewProfiles) {
+                options.push("Documents");
+            }
+            if role.can(Action::ManageOffers) {
+                options.push("Offers");
+            }
+            if role.can(Action::ManageUsers) {
+                options.push("Change Role");
+            }
+            options.push("Logout");
+            match options[print_options(&options)] {
+                "Next Jobs page" => page += 1,
+                "Previous Jobs page" => page = page.saturating_sub(1),
+                "Search jobs" => search_jobs(),
+                "Apply" => job_apply(),
+                "Suggested jobs" => suggested_jobs(),
+                "Withdraw application" => withdraw(),
+                "Add Job" => job_menu(),
+                "Edit Job" => edit_job_menu(),
+                "Job lifecycle" => job_lifecycle_menu(),
+                "Screening questions" => questionnaire_menu(),
+                "Job skills" => job_skills_menu(),
+                "Advance Process" => advance_process_menu(),
+                "Interviews" => interviews_menu(),
+                "Job report" => job_report(),
+                "Offers" => offers_menu(),
+                "My offers" => my_offers_menu(),
+                "My profile" => profile_menu(),
+                "Applicant profiles" => applicant_profiles(),
+                "Applicant answers" => applicant_answers(),
+                "Rank applicants" => rank_applicants(),
+                "Search candidates" => search_candidates(),
+                "Documents" => documents_menu(),
+                "Change Role" => role_menu(),
+                "Logout" => logout(),
+                _ => panic!("Every option should be handled"),
             }
         } else {
-            match print_options(&["Apply", "Logout"]) {
-                0 => job_apply(),
-                1 => *TOKEN.lock().expect("This is single threaded") = None,
-                _ => panic!(
-                    "Print options should never return a number beyond the number of options"
-                ),
+            println!("Login to see the available Jobs");
+            println!();
+            match print_options(&["Register", "Login", "Register first Admin"]) {
+                0 => register(),
+                1 => login_menu(),
+                2 => register_admin(),
+                _ => panic!("Print options should never return an option beyond the option length"),
             }
         }
     }
 }
 
+fn logout() {
+    let mut token = TOKEN.lock().expect("This is single threaded");
+    if let Some(logged_user) = token.take() {
+        if let Err(e) = System::logout(&logged_user) {
+            println!("There was an error ending the session: {}", e);
+        }
+    }
+}
+
 fn job_apply() {
     let job_id = prompt("Chose what job to apply").expect("Error reading line");
 
@@ -48,15 +183,532 @@ fn job_apply() {
     let logged_user = temp_token
         .as_ref()
         .expect("Should have logged in at this point");
-    if System::apply(
-        &logged_user.user,
-        logged_user.token,
-        logged_user.user_id,
-        job_id,
-    )
-    .is_err()
+    let questions = match System::questionnaire(logged_user, job_id) {
+        Ok(questions) => questions,
+        Err(e) => {
+            println!("{}\n", e);
+            return;
+        }
+    };
+    let answers: Vec<_> = questions
+        .iter()
+        .enumerate()
+        .filter_map(|(position, question)| prompt_answer(question).map(|answer| (position, answer)))
+        .collect();
+    if let Err(e) = System::apply_with_answers(logged_user, job_id, &answers) {
+        println!("{}\n", e);
+    }
+}
+
+/// Asks until the answer fits the question, an empty answer skips optional questions.
+/// Choices are picked by their number, several separated by commas
+fn prompt_answer(question: &Question) -> Option<Answer> {
+    question
+        .choices
+        .iter()
+        .enumerate()
+        .for_each(|(i, choice)| println!("{}. {}", i, choice));
+    let pick = |i: &str| {
+        question
+            .choices
+            .get(i.trim().parse::<usize>().ok()?)
+            .cloned()
+    };
+    loop {
+        let line: String = match prompt_opt(question.to_string()).expect("Error reading line") {
+            Some(line) => line,
+            None if question.required => continue,
+            None => return None,
+        };
+        let answer = match question.kind {
+            QuestionKind::FreeText => Some(Answer::Text(line)),
+            QuestionKind::YesNo => match line.trim().to_lowercase().as_str() {
+                "y" | "yes" => Some(Answer::YesNo(true)),
+                "n" | "no" => Some(Answer::YesNo(false)),
+                _ => None,
+            },
+            QuestionKind::SingleChoice => pick(&line).map(Answer::Choice),
+            QuestionKind::MultipleChoice => line
+                .split(',')
+                .map(pick)
+                .collect::<Option<Vec<_>>>()
+                .map(Answer::Choices),
+            QuestionKind::Numeric => line.trim().parse().ok().map(Answer::Number),
+        };
+        match answer {
+            Some(answer) => return Some(answer),
+            None => println!("That isn't a valid answer"),
+        }
+    }
+}
+
+fn withdraw() {
+    let job_id = prompt("Job id of the application to withdraw").expect("Error reading line");
+
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    if let Err(e) = System::withdraw(logged_user, job_id) {
+        println!("There was an error withdrawing the application: {}", e);
+    }
+}
+
+fn job_report() {
+    let job_id = prompt("Job id").expect("Error reading line");
+
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    match System::job_report(logged_user, job_id) {
+        Ok(report) => println!("{}", report),
+        Err(e) => println!("There was an error building the report: {}", e),
+    }
+}
+
+fn profile_menu() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    match System::my_profile(logged_user) {
+        Ok(profile) => println!("{}", profile),
+        Err(e) => {
+            println!("There was an error getting the profile: {}", e);
+            return;
+        }
+    }
+    let draft = match print_options(&[
+        "Back",
+        "Edit it",
+        "Fill it from a résumé file",
+        "Fill it from an attached résumé",
+    ]) {
+        0 => return,
+        1 => None,
+        2 => {
+            let path: std::path::PathBuf = prompt("File").expect("Error reading line");
+            match std::fs::read_to_string(&path) {
+                Ok(resume) => Some(System::draft_profile(logged_user, &resume)),
+                Err(e) => {
+                    println!("Couldn't read {}: {}", path.display(), e);
+                    return;
+                }
+            }
+        }
+        3 => Some(System::draft_profile_from_attachment(
+            logged_user,
+            prompt("Document id").expect("Error reading line"),
+        )),
+        _ => panic!("Print options should never return a number beyond the number of options"),
+    };
+    if let Some(draft) = draft {
+        match draft {
+            Ok(draft) => {
+                println!("{}\n\nDraft:\n{}", draft.report, draft.profile);
+                if prompt_default("Save it", false).expect("Error reading line") {
+                    if let Err(e) = System::update_profile(logged_user, &draft.profile) {
+                        println!("There was an error saving the profile: {}", e);
+                    }
+                }
+            }
+            Err(e) => println!("There was an error reading the résumé: {}", e),
+        }
+        return;
+    }
+    let full_name: String = prompt("Full name").expect("Error reading line");
+    let mut profile = Profile::new(&full_name);
+    if let Some(email) = prompt_opt::<String, _>("Email").expect("Error reading line") {
+        profile = profile.email(&email);
+    }
+    if let Some(phone) = prompt_opt::<String, _>("Phone").expect("Error reading line") {
+        profile = profile.phone(&phone);
+    }
+    if let Some(location) = prompt_opt::<String, _>("Location").expect("Error reading line") {
+        profile = profile.location(&location);
+    }
+    let proficiencies = [
+        Proficiency::Beginner,
+        Proficiency::Intermediate,
+        Proficiency::Advanced,
+        Proficiency::Expert,
+    ];
+    while let Some(skill) =
+        prompt_opt::<String, _>("Skill(empty to stop)").expect("Error reading line")
+    {
+        profile = profile.skill(&skill, proficiencies[print_options(&proficiencies)]);
+    }
+    while let Some(company) =
+        prompt_opt::<String, _>("Company you worked at(empty to stop)").expect("Error reading line")
+    {
+        let title: String = prompt("Title").expect("Error reading line");
+        let start = prompt_month("Started(YYYY-MM)");
+        let end = prompt_opt_month("Ended(YYYY-MM, empty if you still work there)");
+        profile = profile.experience(&company, &title, start, end);
+    }
+    while let Some(institution) =
+        prompt_opt::<String, _>("Institution you studied at(empty to stop)")
+            .expect("Error reading line")
+    {
+        let degree: String = prompt("Degree").expect("Error reading line");
+        let start = prompt_month("Started(YYYY-MM)");
+        let end = prompt_opt_month("Ended(YYYY-MM, empty if you still study there)");
+        profile = profile.education(&institution, &degree, start, end);
+    }
+    if let Err(e) = System::update_profile(logged_user, &profile) {
+        println!("There was an error saving the profile: {}", e);
+    }
+}
+
+fn prompt_month(message: &str) -> YearMonth {
+    loop {
+        if let Some(month) = prompt_opt_month(message) {
+            return month;
+        }
+    }
+}
+
+fn prompt_opt_month(message: &str) -> Option<YearMonth> {
+    loop {
+        let month: String = prompt_opt(message).expect("Error reading line")?;
+        match month
+            .split_once('-')
+            .map(|(year, month)| (year.trim().parse(), month.trim().parse()))
+        {
+            Some((Ok(year), Ok(month))) => return Some(YearMonth::new(year, month)),
+            _ => println!("Invalid month {}, it should be like 2020-03", month),
+        }
+    }
+}
+
+fn search_jobs() {
+    let query: Option<String> =
+        prompt_opt("Search for(empty for any job)").expect("Error reading line");
+    let mut search = JobSearch::new(&query.unwrap_or_default());
+    println!("State:");
+    let states = [
+        None,
+        Some(JobState::Open),
+        Some(JobState::Draft),
+        Some(JobState::OnHold),
+        Some(JobState::Filled),
+        Some(JobState::Cancelled),
+        Some(JobState::Closed),
+    ];
+    let names: Vec<_> = states
+        .iter()
+        .map(|state| state.map_or("Any".to_string(), |s| s.to_string()))
+        .collect();
+    search.state = states[print_options(&names)];
+    search.department = prompt_opt("Department(empty for any)").expect("Error reading line");
+    search.location = prompt_opt("Location(empty for any)").expect("Error reading line");
+    search.posted_after =
+        prompt_opt("Posted after(timestamp, empty for any)").expect("Error reading line");
+    search.posted_before =
+        prompt_opt("Posted before(timestamp, empty for any)").expect("Error reading line");
+
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    match System::search_jobs(logged_user, &search) {
+        Ok(jobs) if jobs.is_empty() => println!("No jobs found"),
+        Ok(jobs) => jobs.iter().for_each(|job| println!("{}: {}", job.id, job)),
+        Err(e) => println!("There was an error searching the jobs: {}", e),
+    }
+}
+
+fn search_candidates() {
+    let query: Option<String> =
+        prompt_opt("Search for(empty for any candidate)").expect("Error reading line");
+    let mut search = CandidateSearch::new(&query.unwrap_or_default());
+    search.location = prompt_opt("Location(empty for any)").expect("Error reading line");
+
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    match System::search_candidates(logged_user, &search) {
+        Ok(found) if found.is_empty() => println!("No candidates found"),
+        Ok(found) => found
+            .iter()
+            .for_each(|(user, profile)| println!("{}: {}", user, profile)),
+        Err(e) => println!("There was an error searching the candidates: {}", e),
+    }
+}
+
+fn applicant_profiles() {
+    let job_id = prompt("Job id").expect("Error reading line");
+
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    match System::applicant_profiles(logged_user, job_id) {
+        Ok(profiles) if profiles.is_empty() => println!("Nobody applied yet"),
+        Ok(profiles) => profiles
+            .iter()
+            .for_each(|(user, profile)| println!("{}: {}", user, profile)),
+        Err(e) => println!("There was an error getting the profiles: {}", e),
+    }
+}
+
+fn documents_menu() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    let mut options = vec!["List documents", "Download document", "Delete document"];
+    if logged_user.role.can(Action::AttachDocuments) {
+        options.push("Attach document");
+    }
+    match options[print_options(&options)] {
+        "List documents" => {
+            let user = if logged_user.role.can(Action::ViewProfiles) {
+                prompt("Candidate").expect("Read line error")
+            } else {
+                logged_user.user.clone()
+            };
+            let job_id = prompt("Job id of the application").expect("Read line error");
+            match System::list_attachments(logged_user, &user, job_id) {
+                Ok(attachments) if attachments.is_empty() => println!("There are no documents"),
+                Ok(attachments) => attachments
+                    .iter()
+                    .for_each(|attachment| println!("{}", attachment)),
+                Err(e) => println!("There was an error listing the documents: {}", e),
+            }
+        }
+        "Download document" => {
+            let attachment_id = prompt("Document id").expect("Read line error");
+            match System::download_attachment(logged_user, attachment_id) {
+                Ok((attachment, content)) => {
+                    let path: String =
+                        prompt_default("Save as", attachment.file_name).expect("Read line error");
+                    match std::fs::write(&path, content) {
+                        Ok(()) => println!("Saved to {}", path),
+                        Err(e) => println!("Couldn't write {}: {}", path, e),
+                    }
+                }
+                Err(e) => println!("There was an error downloading the document: {}", e),
+            }
+        }
+        "Delete document" => {
+            let attachment_id = prompt("Document id").expect("Read line error");
+            if let Err(e) = System::delete_attachment(logged_user, attachment_id) {
+                println!("There was an error deleting the document: {}", e);
+            }
+        }
+        "Attach document" => {
+            let job_id = prompt("Job id of the application").expect("Read line error");
+            let path: std::path::PathBuf = prompt("File").expect("Read line error");
+            let kinds = [
+                DocumentKind::Resume,
+                DocumentKind::CoverLetter,
+                DocumentKind::Portfolio,
+                DocumentKind::Other,
+            ];
+            let kind = kinds[print_options(&kinds)];
+            // Only the extensions of the accepted types by default are guessed
+            let guessed = match path.extension().and_then(|extension| extension.to_str()) {
+                Some("pdf") => "application/pdf",
+                Some("doc") => "application/msword",
+                Some("docx") => {
+                    "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+                }
+                Some("md") => "text/markdown",
+                _ => "text/plain",
+            };
+            let mime_type: String =
+                prompt_default("MIME type", guessed.to_string()).expect("Read line error");
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let attached = std::fs::read(&path)
+                .map_err(|e| format!("Couldn't read {}: {}", path.display(), e))
+                .and_then(|content| {
+                    System::attach_document(
+                        logged_user,
+                        job_id,
+                        kind,
+                        &file_name,
+                        &mime_type,
+                        &content,
+                    )
+                    .map_err(|e| e.to_string())
+                });
+            match attached {
+                Ok(id) => println!("Attached document {}", id),
+                Err(e) => println!("There was an error attaching the document: {}", e),
+            }
+        }
+        _ => panic!("Every option should be handled"),
+    }
+}
+
+fn questionnaire_menu() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    let job_id = prompt("Job id").expect("Error reading line");
+    // The questions are asked in the order they're added, replacing the current ones
+    let mut questions = Vec::new();
+    loop {
+        let kinds = [
+            "Done",
+            "Free text",
+            "Yes/No",
+            "Single choice",
+            "Multiple choice",
+            "Numeric",
+        ];
+        let kind = print_options(&kinds);
+        if kind == 0 {
+            break;
+        }
+        let text: String = prompt("Question").expect("Error reading line");
+        let choices: Vec<String> = if kind == 3 || kind == 4 {
+            prompt::<String, _>("Choices, separated by commas")
+                .expect("Error reading line")
+                .split(',')
+                .map(|choice| choice.trim().to_string())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let choices: Vec<_> = choices.iter().map(String::as_str).collect();
+        let mut question = match kind {
+            1 => Question::free_text(&text),
+            2 => Question::yes_no(&text),
+            3 => Question::single_choice(&text, &choices),
+            4 => Question::multiple_choice(&text, &choices),
+            _ => Question::numeric(&text),
+        };
+        if prompt_default("Required", false).expect("Error reading line") {
+            question = question.required();
+        }
+        let knockout = match kind {
+            2 => match print_options(&["No knockout rule", "Must be yes", "Must be no"]) {
+                0 => None,
+                answer => Some(Knockout::Requires(answer == 1)),
+            },
+            3 | 4 => {
+                let accepted: Option<String> =
+                    prompt_opt("Knockout: choices that pass, separated by commas(empty for none)")
+                        .expect("Error reading line");
+                accepted.map(|accepted| {
+                    let accepted = accepted
+                        .split(',')
+                        .map(|choice| choice.trim().to_string())
+                        .collect();
+                    if kind == 4 && print_options(&["Any of them", "All of them"]) == 1 {
+                        Knockout::AllOf(accepted)
+                    } else {
+                        Knockout::AnyOf(accepted)
+                    }
+                })
+            }
+            5 => {
+                let min =
+                    prompt_opt("Knockout: minimum(empty for none)").expect("Error reading line");
+                let max =
+                    prompt_opt("Knockout: maximum(empty for none)").expect("Error reading line");
+                if min.is_some() || max.is_some() {
+                    Some(Knockout::Range { min, max })
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        if let Some(knockout) = knockout {
+            question = question.knockout(knockout);
+        }
+        questions.push(question);
+    }
+    if let Err(e) = System::set_questionnaire(logged_user, job_id, &questions) {
+        println!("There was an error setting the questions: {}", e);
+    }
+}
+
+fn job_skills_menu() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    let job_id = prompt("Job id").expect("Error reading line");
+    // The skills given replace the current ones
+    let proficiencies = [
+        Proficiency::Beginner,
+        Proficiency::Intermediate,
+        Proficiency::Advanced,
+        Proficiency::Expert,
+    ];
+    let mut skills = Vec::new();
+    while let Some(name) =
+        prompt_opt::<String, _>("Skill(empty to stop)").expect("Error reading line")
     {
-        println!("Already applied or closed\n");
+        let proficiency = proficiencies[print_options(&proficiencies)];
+        if prompt_default("Required", true).expect("Error reading line") {
+            skills.push(JobSkill::required(&name, proficiency));
+        } else {
+            skills.push(JobSkill::nice_to_have(&name, proficiency));
+        }
+    }
+    if let Err(e) = System::set_job_skills(logged_user, job_id, &skills) {
+        println!("There was an error setting the skills: {}", e);
+    }
+}
+
+fn rank_applicants() {
+    let job_id = prompt("Job id").expect("Error reading line");
+
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    match System::rank_applicants(logged_user, job_id) {
+        Ok(ranking) if ranking.is_empty() => println!("There are no applicants in the process"),
+        Ok(ranking) => ranking
+            .iter()
+            .enumerate()
+            .for_each(|(i, (user, score))| println!("{}. {}: {}", i + 1, user, score)),
+        Err(e) => println!("There was an error ranking the applicants: {}", e),
+    }
+}
+
+fn suggested_jobs() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    match System::suggest_jobs(logged_user) {
+        Ok(suggestions) if suggestions.is_empty() => {
+            println!("There are no suggestions, filling your profile helps")
+        }
+        Ok(suggestions) => suggestions
+            .iter()
+            .for_each(|(job, score)| println!("{}: {}\n {}", job.id, job.name, score)),
+        Err(e) => println!("There was an error suggesting jobs: {}", e),
+    }
+}
+
+fn applicant_answers() {
+    let user: String = prompt("Candidate").expect("Error reading line");
+    let job_id = prompt("Job id").expect("Error reading line");
+
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    match System::application_answers(logged_user, &user, job_id) {
+        Ok(answers) if answers.is_empty() => println!("There are no answers"),
+        Ok(answers) => answers
+            .iter()
+            .for_each(|(question, answer)| println!("{}\n {}", question.text, answer)),
+        Err(e) => println!("There was an error getting the answers: {}", e),
     }
 }
 
@@ -65,79 +717,474 @@ fn register() {
         let user = prompt("Insert Username").expect("Error reading line");
         // This should use password prompt but promptly doesn't have that.
         let pass = prompt("Insert Password").expect("Error reading line");
-        if System::register_candidate(user, pass).is_err() {
-            println!("Username already registered")
+        if let Err(e) = System::register_candidate(user, pass) {
+            println!("{}", e)
         } else {
             break;
         }
     }
 }
 
+fn register_admin() {
+    let user = prompt("Insert Username").expect("Error reading line");
+    // This should use password prompt but promptly doesn't have that.
+    let pass = prompt("Insert Password").expect("Error reading line");
+    if let Err(e) = System::bootstrap_admin(user, pass) {
+        println!("There's already an Admin or the username is taken: {}", e)
+    }
+}
+
 fn job_menu() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    // Jobs use the default pipeline unless there are others to choose from
+    let pipelines = System::list_pipelines(logged_user).unwrap_or_default();
+    let pipeline_id = match pipelines.as_slice() {
+        [] | [_] => None,
+        pipelines => {
+            println!("Hiring pipeline:");
+            let names: Vec<_> = pipelines
+                .iter()
+                .map(|pipeline| {
+                    let stages: Vec<_> = pipeline
+                        .stages
+                        .iter()
+                        .map(|stage| stage.name.as_str())
+                        .collect();
+                    format!("{} ({})", pipeline.name, stages.join(", "))
+                })
+                .collect();
+            Some(pipelines[print_options(&names)].id)
+        }
+    };
+    // Drafts don't take applications until they're published from the lifecycle menu
+    let publish = prompt_default("Publish right away", true).expect("Error reading line");
     loop {
         let job_name = prompt("Enter job name").expect("Error reading line");
-        if System::create_job_posting(job_name).is_ok() {
-            break;
-        } else {
-            println!("Error creating job posting");
+        let created = match pipeline_id {
+            _ if !publish => System::create_draft_job_posting(logged_user, job_name, pipeline_id),
+            Some(pipeline_id) => {
+                System::create_job_posting_with_pipeline(logged_user, job_name, pipeline_id)
+            }
+            None => System::create_job_posting(logged_user, job_name),
+        };
+        match created {
+            Ok(job_id) => {
+                let headcount =
+                    prompt_default("Number of openings", 1).expect("Error reading line");
+                if headcount != 1 {
+                    if let Err(e) = System::set_headcount(logged_user, job_id, headcount) {
+                        println!("Error setting the number of openings: {}", e);
+                    }
+                }
+                break;
+            }
+            Err(e) => println!("Error creating job posting: {}", e),
         }
     }
 }
 
+fn edit_job_menu() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    let job_id = prompt("Job id").expect("Error reading line");
+    let description: Option<String> = prompt_opt("Description").expect("Error reading line");
+    let mut details = JobDetails::new(&description.unwrap_or_default());
+    if let Some(department) = prompt_opt::<String, _>("Department").expect("Error reading line") {
+        details = details.department(&department);
+    }
+    let location: Option<String> = prompt_opt("Location").expect("Error reading line");
+    println!("Workplace:");
+    let workplaces = [Workplace::OnSite, Workplace::Hybrid, Workplace::Remote];
+    details = details.location(
+        &location.unwrap_or_default(),
+        workplaces[print_options(&workplaces)],
+    );
+    println!("Employment type:");
+    let employment_types = [
+        EmploymentType::FullTime,
+        EmploymentType::PartTime,
+        EmploymentType::Contract,
+        EmploymentType::Temporary,
+        EmploymentType::Internship,
+    ];
+    details = details.employment_type(employment_types[print_options(&employment_types)]);
+    println!("Seniority:");
+    let seniorities = [
+        None,
+        Some(Seniority::Junior),
+        Some(Seniority::Mid),
+        Some(Seniority::Senior),
+        Some(Seniority::Lead),
+        Some(Seniority::Principal),
+    ];
+    let names: Vec<_> = seniorities
+        .iter()
+        .map(|seniority| seniority.map_or("Not given".to_string(), |s| s.to_string()))
+        .collect();
+    if let Some(seniority) = seniorities[print_options(&names)] {
+        details = details.seniority(seniority);
+    }
+    let salary: Option<String> = prompt_opt("Salary range(min-max currency, like 50000-70000 USD)")
+        .expect("Error reading line");
+    if let Some(salary) = salary {
+        let parsed = salary.split_once(' ').and_then(|(range, currency)| {
+            let (min, max) = range.split_once('-')?;
+            Some((
+                min.trim().parse().ok()?,
+                max.trim().parse().ok()?,
+                currency.trim(),
+            ))
+        });
+        match parsed {
+            Some((min, max, currency)) => details = details.salary(min, max, currency),
+            None => println!("Ignoring invalid salary range {}", salary),
+        }
+    }
+    if let Err(e) = System::update_job_posting(logged_user, job_id, &details) {
+        println!("Error updating the job posting: {}", e);
+    }
+}
+
+fn job_lifecycle_menu() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    let job_id = prompt("Job id").expect("Error reading line");
+    let moved = match print_options(&[
+        "Publish",
+        "Pause",
+        "Resume",
+        "Reopen",
+        "Cancel",
+        "Set application window",
+    ]) {
+        0 => System::publish_job(logged_user, job_id),
+        1 => System::pause_job(logged_user, job_id),
+        2 => System::resume_job(logged_user, job_id),
+        3 => System::reopen_job(logged_user, job_id),
+        4 => System::cancel_job(logged_user, job_id),
+        5 => {
+            let opens_at = prompt_opt("Opens at(timestamp, empty to open right away)")
+                .expect("Error reading line");
+            let closes_at = prompt_opt("Closes at(timestamp, empty to never close)")
+                .expect("Error reading line");
+            System::set_application_window(logged_user, job_id, opens_at, closes_at)
+        }
+        _ => panic!("Print options should never return an option beyond the option length"),
+    };
+    if let Err(e) = moved {
+        println!("There was an error changing the job's state: {}", e);
+    }
+}
+
+fn role_menu() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    let user: String = prompt("User to change the role").expect("Error reading line");
+    let roles = [
+        Role::Candidate,
+        Role::Recruiter,
+        Role::HiringManager,
+        Role::Admin,
+    ];
+    let role = roles[print_options(
+        &roles
+            .iter()
+            .map(|role| role.to_string())
+            .collect::<Vec<_>>(),
+    )];
+    if let Err(e) = System::set_role(logged_user, &user, role) {
+        println!("There was an error changing the role: {}", e);
+    }
+}
+
 fn login_menu() {
     loop {
-        let user = prompt("Insert Username").expect("Error reading line");
+        let user: String = prompt("Insert Username").expect("Error reading line");
         // This should use password prompt but promptly doesn't have that.
-        let pass = prompt("Insert Password").expect("Error reading line");
-        let logged_user = System::login(&user, &pass);
-        if let Some(logged_user) = logged_user {
-            *TOKEN
-                .lock()
-                .expect("This should be single-threaded for now") = Some(logged_user);
-            break;
-        } else {
-            println!("Inexistent user or incorrect password")
+        let pass: String = prompt("Insert Password").expect("Error reading line");
+        match System::login(&user, &pass) {
+            Ok(logged_user) => {
+                *TOKEN
+                    .lock()
+                    .expect("This should be single-threaded for now") = Some(logged_user);
+                break;
+            }
+            Err(e) => println!("{}", e),
         }
     }
 }
 
 fn advance_process_menu() {
-    match print_options(&["Interview", "Approve", "Reject"]) {
+    match print_options(&["Interview", "Approve", "Reject", "Move to stage"]) {
         0 => interview(),
         1 => approve(),
         2 => reject(),
+        3 => move_to_stage(),
         _ => panic!("Print options should never return a number beyond the number of options"),
     }
 }
 
 // TODO: Dedup this --
 fn interview() {
-    let candidate = prompt("Candidate to interview").expect("Read line error");
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    let candidate: String = prompt("Candidate to interview").expect("Read line error");
     let job_id = prompt("Job id of the interview").expect("Read line error");
-    if System::interview(candidate, job_id).is_err() {
-        println!("There was an error interviewing candidate");
+    if let Err(e) = System::interview(logged_user, &candidate, job_id) {
+        println!("There was an error interviewing candidate: {}", e);
     }
 }
 
 fn approve() {
-    let candidate = prompt("Candidate to interview").expect("Read line error");
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    let candidate: String = prompt("Candidate to interview").expect("Read line error");
     let job_id = prompt("Job id of the interview").expect("Read line error");
-    if System::approve(candidate, job_id).is_err() {
-        println!("There was an error approving candidate");
+    if let Err(e) = System::approve(logged_user, &candidate, job_id) {
+        println!("There was an error approving candidate: {}", e);
     }
 }
 
 fn reject() {
-    let candidate = prompt("Candidate to interview").expect("Read line error");
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    let candidate: String = prompt("Candidate to interview").expect("Read line error");
     let job_id = prompt("Job id of the interview").expect("Read line error");
-    if System::reject(candidate, job_id).is_err() {
-        println!("There was an error rejecting candidate");
+    let reasons = System::rejection_reasons();
+    let reason = &reasons[print_options(reasons)];
+    let note: Option<String> = prompt_opt("Note").expect("Read line error");
+    if let Err(e) = System::reject(
+        logged_user,
+        &candidate,
+        job_id,
+        reason,
+        &note.unwrap_or_default(),
+    ) {
+        println!("There was an error rejecting candidate: {}", e);
+    }
+}
+
+fn move_to_stage() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    let candidate: String = prompt("Candidate to move").expect("Read line error");
+    let job_id = prompt("Job id of the application").expect("Read line error");
+    let stage: String = prompt("Stage to move to").expect("Read line error");
+    if let Err(e) = System::advance(logged_user, &candidate, job_id, &stage) {
+        println!("There was an error moving the candidate: {}", e);
     }
 }
 // --
 
+fn interviews_menu() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    match print_options(&[
+        "My upcoming interviews",
+        "Upcoming interviews of a job",
+        "Schedule interview",
+        "Mark interview",
+        "Submit scorecard",
+        "Scorecards of an application",
+    ]) {
+        0 => print_interviews(System::upcoming_interviews_for(
+            logged_user,
+            &logged_user.user,
+        )),
+        1 => {
+            let job_id = prompt("Job id").expect("Read line error");
+            print_interviews(System::upcoming_interviews_for_job(logged_user, job_id))
+        }
+        2 => {
+            let candidate: String = prompt("Candidate to interview").expect("Read line error");
+            let job_id = prompt("Job id of the interview").expect("Read line error");
+            let starts_at = prompt("Start time(unix timestamp)").expect("Read line error");
+            let minutes: i64 = prompt("Duration in minutes").expect("Read line error");
+            let interviewers: String =
+                prompt("Interviewers(comma separated)").expect("Read line error");
+            let interviewers: Vec<&str> = interviewers.split(',').map(str::trim).collect();
+            match System::schedule_interview(
+                logged_user,
+                &candidate,
+                job_id,
+                starts_at,
+                minutes * 60,
+                &interviewers,
+            ) {
+                Ok(id) => println!("Scheduled interview {}", id),
+                Err(e) => println!("There was an error scheduling the interview: {}", e),
+            }
+        }
+        3 => {
+            let interview_id = prompt("Interview id").expect("Read line error");
+            let statuses = [
+                InterviewStatus::Completed,
+                InterviewStatus::NoShow,
+                InterviewStatus::Cancelled,
+            ];
+            let status = statuses[print_options(&statuses)];
+            if let Err(e) = System::mark_interview(logged_user, interview_id, status) {
+                println!("There was an error marking the interview: {}", e);
+            }
+        }
+        4 => {
+            let candidate: String = prompt("Candidate").expect("Read line error");
+            let job_id = prompt("Job id of the application").expect("Read line error");
+            let recommendations = [
+                Recommendation::StrongNo,
+                Recommendation::No,
+                Recommendation::Yes,
+                Recommendation::StrongYes,
+            ];
+            let mut scorecard = Scorecard::new(recommendations[print_options(&recommendations)]);
+            let ratings: Option<String> =
+                prompt_opt("Ratings(competency=score, comma separated)").expect("Read line error");
+            for rating in ratings.iter().flat_map(|ratings| ratings.split(',')) {
+                match rating
+                    .split_once('=')
+                    .map(|(c, s)| (c.trim(), s.trim().parse()))
+                {
+                    Some((competency, Ok(score))) => {
+                        scorecard = scorecard.rating(competency, score)
+                    }
+                    _ => println!("Ignoring invalid rating {}", rating),
+                }
+            }
+            if let Some(notes) = prompt_opt::<String, _>("Notes").expect("Read line error") {
+                scorecard = scorecard.notes(&notes);
+            }
+            if let Some(interview_id) = prompt_opt("Interview id").expect("Read line error") {
+                scorecard = scorecard.interview(interview_id);
+            }
+            match System::submit_scorecard(logged_user, &candidate, job_id, scorecard) {
+                Ok(id) => println!("Submitted scorecard {}", id),
+                Err(e) => println!("There was an error submitting the scorecard: {}", e),
+            }
+        }
+        5 => {
+            let candidate: String = prompt("Candidate").expect("Read line error");
+            let job_id = prompt("Job id of the application").expect("Read line error");
+            match System::scorecards(logged_user, &candidate, job_id) {
+                Ok(scorecards) => {
+                    for scorecard in scorecards.iter() {
+                        println!(
+                            "{}: by {}, {}, {:?} {}",
+                            scorecard.id,
+                            scorecard.interviewer_id,
+                            scorecard.recommendation,
+                            scorecard
+                                .ratings
+                                .iter()
+                                .map(|rating| format!("{}={}", rating.competency, rating.score))
+                                .collect::<Vec<_>>(),
+                            scorecard.notes
+                        );
+                    }
+                    println!("{}", hrsystem::ScorecardSummary::new(&scorecards));
+                }
+                Err(e) => println!("There was an error listing the scorecards: {}", e),
+            }
+        }
+        _ => panic!("Print options should never return a number beyond the number of options"),
+    }
+}
+
+fn offers_menu() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    match print_options(&["Offers of a job", "Create offer", "Send offer"]) {
+        0 => {
+            let job_id = prompt("Job id").expect("Read line error");
+            print_offers(System::offers_for_job(logged_user, job_id))
+        }
+        1 => {
+            let candidate: String = prompt("Candidate").expect("Read line error");
+            let job_id = prompt("Job id of the application").expect("Read line error");
+            let salary = prompt("Yearly salary").expect("Read line error");
+            let start_date = prompt("Start date(unix timestamp)").expect("Read line error");
+            let expires_at = prompt("Expires at(unix timestamp)").expect("Read line error");
+            match System::create_offer(
+                logged_user,
+                &candidate,
+                job_id,
+                salary,
+                start_date,
+                expires_at,
+            ) {
+                Ok(id) => println!("Drafted offer {}", id),
+                Err(e) => println!("There was an error creating the offer: {}", e),
+            }
+        }
+        2 => {
+            let offer_id = prompt("Offer id").expect("Read line error");
+            if let Err(e) = System::send_offer(logged_user, offer_id) {
+                println!("There was an error sending the offer: {}", e);
+            }
+        }
+        _ => panic!("Print options should never return a number beyond the number of options"),
+    }
+}
+
+fn my_offers_menu() {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    print_offers(System::my_offers(logged_user));
+    let answered = match print_options(&["Back", "Accept offer", "Decline offer"]) {
+        0 => return,
+        1 => System::accept_offer(logged_user, prompt("Offer id").expect("Read line error")),
+        2 => System::decline_offer(logged_user, prompt("Offer id").expect("Read line error")),
+        _ => panic!("Print options should never return a number beyond the number of options"),
+    };
+    if let Err(e) = answered {
+        println!("There was an error answering the offer: {}", e);
+    }
+}
+
+fn print_offers(offers: Result<Vec<hrsystem::Offer>, hrsystem::ErrorVariant>) {
+    match offers {
+        Ok(offers) if offers.is_empty() => println!("There are no offers"),
+        Ok(offers) => offers
+            .iter()
+            .for_each(|offer| println!("{}: {}", offer.id, offer)),
+        Err(e) => println!("There was an error listing the offers: {}", e),
+    }
+}
+
+fn print_interviews(interviews: Result<Vec<hrsystem::Interview>, hrsystem::ErrorVariant>) {
+    match interviews {
+        Ok(interviews) if interviews.is_empty() => println!("There are no upcoming interviews"),
+        Ok(interviews) => interviews
+            .iter()
+            .for_each(|interview| println!("{}: {}", interview.id, interview)),
+        Err(e) => println!("There was an error listing the interviews: {}", e),
+    }
+}
+
 // TODO: This could dispatch the corresponding menu
-fn print_options(options: &[&str]) -> usize {
+fn print_options<S: std::fmt::Display>(options: &[S]) -> usize {
     options
         .iter()
         .enumerate()
@@ -152,11 +1199,23 @@ fn print_options(options: &[&str]) -> usize {
         }
     }
 }
-// TODO: Make this generic on storage
-fn print_jobs() {
-    let jobs = System::list_jobs().expect("DB Connection problems");
-    if jobs.is_empty() {
+
+fn print_jobs(page: usize) {
+    let temp_token = TOKEN.lock().expect("Single threaded");
+    let logged_user = temp_token
+        .as_ref()
+        .expect("Should have logged in at this point");
+    let jobs = match System::list_jobs_page(logged_user, page) {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            println!("There was an error listing the Jobs: {}", e);
+            return;
+        }
+    };
+    if jobs.is_empty() && page == 0 {
         println!("There are no Jobs posted yet");
+    } else if jobs.is_empty() {
+        println!("There are no more Jobs");
     } else {
         jobs.iter().for_each(|job| println!("{}: {}", job.id, job));
     }
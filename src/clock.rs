@@ -0,0 +1,32 @@
+//! Source of the current time for the system
+//! Can be swapped through `HRSystemBuilder::clock`, mostly useful for tests.
+
+/// Seconds since the Unix epoch
+pub type Timestamp = i64;
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Timestamp;
+}
+
+/// The real time as reported by the OS
+#[derive(Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as Timestamp)
+            .unwrap_or_default()
+    }
+}
+
+/// Any closure returning a timestamp can be used as a clock
+impl<F> Clock for F
+where
+    F: Fn() -> Timestamp + Send + Sync,
+{
+    fn now(&self) -> Timestamp {
+        self()
+    }
+}
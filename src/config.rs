@@ -0,0 +1,388 @@
+//! Runtime settings of the system read from a TOML file and environment variables
+//!
+//! Settings are resolved in this order, each step overriding the previous one:
+//! 1. Defaults(`Config::default()`)
+//! 2. The config file: the path in `HR_CONFIG` or `hr_system.toml` in the current directory if it exists
+//! 3. Environment variables
+//! 4. Anything set on `HRSystemBuilder` after calling `HRSystemBuilder::config`
+//!
+//! | File key      | Environment variable | Default                                 |
+//! |---------------|----------------------|-----------------------------------------|
+//! | `backend`     | `HR_BACKEND`         | `sqlite`(`memory` without that feature) |
+//! | `db_path`     | `HR_DB_PATH`         | `hr_store.db`                           |
+//! | `secret_file` | `HR_SECRET_FILE`     | None, a random key is kept in the store |
+//! |               | `HR_SECRET`          | None, takes precedence over the file    |
+//! | `page_size`   | `HR_PAGE_SIZE`       | 20                                      |
+//! | `password_iterations` | `HR_PASSWORD_ITERATIONS` | 600000                      |
+//! | `session_ttl` | `HR_SESSION_TTL`     | 28800(seconds, 8 hours), at most a year |
+//! | `require_scorecards` | `HR_REQUIRE_SCORECARDS` | false                           |
+//! | `rejection_reasons` | `HR_REJECTION_REASONS`(comma separated) | `DEFAULT_REJECTION_REASONS` |
+//! | `maintenance_user` | `HR_MAINTENANCE_USER` | None                             |
+//! | `maintenance_password` | `HR_MAINTENANCE_PASSWORD` | None                     |
+//! | `attachments_dir` | `HR_ATTACHMENTS_DIR` | None, documents are kept in the store |
+//! | `max_attachment_size` | `HR_MAX_ATTACHMENT_SIZE` | 5242880(bytes, 5 MiB)     |
+//! | `attachment_types` | `HR_ATTACHMENT_TYPES`(comma separated) | `DEFAULT_ATTACHMENT_TYPES` |
+//! | `skill_dictionary` | `HR_SKILL_DICTIONARY`(comma separated) | `DEFAULT_SKILL_DICTIONARY` |
+use crate::session::MAX_SESSION_TTL;
+use crate::StoreError;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Environment variable with the path of the config file
+pub const CONFIG_ENV: &str = "HR_CONFIG";
+/// Config file used when `HR_CONFIG` isn't set, it's optional
+pub const DEFAULT_CONFIG_FILE: &str = "hr_system.toml";
+/// Reason codes a rejection can use unless configured otherwise
+pub const DEFAULT_REJECTION_REASONS: &[&str] = &[
+    "qualifications",
+    "experience",
+    "culture_fit",
+    "compensation",
+    "position_filled",
+    "other",
+];
+
+/// Storage backend to use
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Sqlite,
+    Memory,
+}
+
+impl std::str::FromStr for Backend {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sqlite" => Ok(Self::Sqlite),
+            "memory" => Ok(Self::Memory),
+            _ => Err(ConfigError::Invalid(format!("Unknown backend {}", s))),
+        }
+    }
+}
+
+/// Where the secret used to sign tokens comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecretSource {
+    Random,
+    Value(Vec<u8>),
+    File(PathBuf),
+}
+
+impl SecretSource {
+    /// Returns the secret, `None` if the one kept in the store(or a new random one) should be used
+    pub fn load(&self) -> Result<Option<Vec<u8>>, ConfigError> {
+        match self {
+            Self::Random => Ok(None),
+            Self::Value(secret) => Ok(Some(secret.clone())),
+            Self::File(path) => std::fs::read(path)
+                .map(Some)
+                .map_err(|e| ConfigError::Io(path.clone(), e)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub backend: Backend,
+    /// Only used by the SQLite backend
+    pub db_path: PathBuf,
+    pub secret: SecretSource,
+    /// Number of jobs per page in `HRSystem::list_jobs_page`
+    pub page_size: usize,
+    /// PBKDF2 iterations for new password hashes
+    pub password_iterations: u32,
+    /// Seconds a login session lasts
+    pub session_ttl: i64,
+    /// Whether an application needs a scorecard before being approved or rejected
+    pub require_scorecards: bool,
+    /// Reason codes a rejection can use
+    pub rejection_reasons: Vec<String>,
+    /// Admin the CLI's `maintenance` command logs in as
+    pub maintenance_user: Option<String>,
+    pub maintenance_password: Option<String>,
+    /// Directory the attached documents are kept in, they're kept in the store if not set
+    pub attachments_dir: Option<PathBuf>,
+    /// Largest document that can be attached, in bytes
+    pub max_attachment_size: u64,
+    /// MIME types of the documents that can be attached
+    pub attachment_types: Vec<String>,
+    /// Skills the résumé parser looks for
+    pub skill_dictionary: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "sqlite")]
+            backend: Backend::Sqlite,
+            #[cfg(not(feature = "sqlite"))]
+            backend: Backend::Memory,
+            db_path: "hr_store.db".into(),
+            secret: SecretSource::Random,
+            page_size: 20,
+            password_iterations: crate::password::DEFAULT_ITERATIONS,
+            session_ttl: crate::session::DEFAULT_SESSION_TTL,
+            require_scorecards: false,
+            rejection_reasons: DEFAULT_REJECTION_REASONS
+                .iter()
+                .map(|reason| reason.to_string())
+                .collect(),
+            maintenance_user: None,
+            maintenance_password: None,
+            attachments_dir: None,
+            max_attachment_size: crate::attachment::DEFAULT_MAX_ATTACHMENT_SIZE,
+            attachment_types: crate::attachment::DEFAULT_ATTACHMENT_TYPES
+                .iter()
+                .map(|mime_type| mime_type.to_string())
+                .collect(),
+            skill_dictionary: crate::resume::DEFAULT_SKILL_DICTIONARY
+                .iter()
+                .map(|skill| skill.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// The config file as written, every key is optional
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    backend: Option<Backend>,
+    db_path: Option<PathBuf>,
+    secret_file: Option<PathBuf>,
+    page_size: Option<usize>,
+    password_iterations: Option<u32>,
+    session_ttl: Option<i64>,
+    require_scorecards: Option<bool>,
+    rejection_reasons: Option<Vec<String>>,
+    maintenance_user: Option<String>,
+    maintenance_password: Option<String>,
+    attachments_dir: Option<PathBuf>,
+    max_attachment_size: Option<u64>,
+    attachment_types: Option<Vec<String>>,
+    skill_dictionary: Option<Vec<String>>,
+}
+
+/// Error loading the configuration
+/// - Io: The file at the given path couldn't be read
+/// - Parse: The config file isn't valid
+/// - Invalid: A setting has an invalid value
+/// - Store: The configured store couldn't be opened
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, std::io::Error),
+    Parse(toml::de::Error),
+    Invalid(String),
+    Store(StoreError),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "Couldn't read {}: {}", path.display(), e),
+            Self::Parse(e) => write!(f, "Invalid config file: {}", e),
+            Self::Invalid(e) => write!(f, "Invalid setting: {}", e),
+            Self::Store(e) => write!(f, "Couldn't open the store: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<StoreError> for ConfigError {
+    fn from(e: StoreError) -> Self {
+        Self::Store(e)
+    }
+}
+
+impl Config {
+    /// Loads the config from the config file and the process' environment
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_with(|var| std::env::var(var).ok())
+    }
+
+    /// Same as `load` but reading the environment variables through `env`
+    pub fn load_with<F>(env: F) -> Result<Self, ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut config = match env(CONFIG_ENV) {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(DEFAULT_CONFIG_FILE)?
+            }
+            None => Self::default(),
+        };
+        config.apply_env(env)?;
+        Ok(config)
+    }
+
+    /// Defaults overridden by the given TOML file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::Io(path.into(), e))?;
+        Self::from_toml(&contents)
+    }
+
+    /// Defaults overridden by the given TOML
+    pub fn from_toml(contents: &str) -> Result<Self, ConfigError> {
+        let file: ConfigFile = toml::from_str(contents).map_err(ConfigError::Parse)?;
+        let mut config = Self::default();
+        if let Some(backend) = file.backend {
+            config.backend = backend;
+        }
+        if let Some(db_path) = file.db_path {
+            config.db_path = db_path;
+        }
+        if let Some(secret_file) = file.secret_file {
+            config.secret = SecretSource::File(secret_file);
+        }
+        if let Some(page_size) = file.page_size {
+            config.page_size = page_size;
+        }
+        if let Some(password_iterations) = file.password_iterations {
+            config.password_iterations = password_iterations;
+        }
+        if let Some(session_ttl) = file.session_ttl {
+            config.session_ttl = session_ttl;
+        }
+        if let Some(require_scorecards) = file.require_scorecards {
+            config.require_scorecards = require_scorecards;
+        }
+        if let Some(rejection_reasons) = file.rejection_reasons {
+            config.rejection_reasons = rejection_reasons;
+        }
+        if let Some(maintenance_user) = file.maintenance_user {
+            config.maintenance_user = Some(maintenance_user);
+        }
+        if let Some(maintenance_password) = file.maintenance_password {
+            config.maintenance_password = Some(maintenance_password);
+        }
+        if let Some(attachments_dir) = file.attachments_dir {
+            config.attachments_dir = Some(attachments_dir);
+        }
+        if let Some(max_attachment_size) = file.max_attachment_size {
+            config.max_attachment_size = max_attachment_size;
+        }
+        if let Some(attachment_types) = file.attachment_types {
+            config.attachment_types = attachment_types;
+        }
+        if let Some(skill_dictionary) = file.skill_dictionary {
+            config.skill_dictionary = skill_dictionary;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Overrides the settings with the ones set in the environment
+    pub fn apply_env<F>(&mut self, env: F) -> Result<(), ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(backend) = env("HR_BACKEND") {
+            self.backend = backend.parse()?;
+        }
+        if let Some(db_path) = env("HR_DB_PATH") {
+            self.db_path = db_path.into();
+        }
+        if let Some(secret_file) = env("HR_SECRET_FILE") {
+            self.secret = SecretSource::File(secret_file.into());
+        }
+        if let Some(secret) = env("HR_SECRET") {
+            self.secret = SecretSource::Value(secret.into_bytes());
+        }
+        if let Some(page_size) = env("HR_PAGE_SIZE") {
+            self.page_size = page_size
+                .parse()
+                .map_err(|e| ConfigError::Invalid(format!("HR_PAGE_SIZE: {}", e)))?;
+        }
+        if let Some(password_iterations) = env("HR_PASSWORD_ITERATIONS") {
+            self.password_iterations = password_iterations
+                .parse()
+                .map_err(|e| ConfigError::Invalid(format!("HR_PASSWORD_ITERATIONS: {}", e)))?;
+        }
+        if let Some(session_ttl) = env("HR_SESSION_TTL") {
+            self.session_ttl = session_ttl
+                .parse()
+                .map_err(|e| ConfigError::Invalid(format!("HR_SESSION_TTL: {}", e)))?;
+        }
+        if let Some(require_scorecards) = env("HR_REQUIRE_SCORECARDS") {
+            self.require_scorecards = require_scorecards
+                .parse()
+                .map_err(|e| ConfigError::Invalid(format!("HR_REQUIRE_SCORECARDS: {}", e)))?;
+        }
+        if let Some(rejection_reasons) = env("HR_REJECTION_REASONS") {
+            self.rejection_reasons = rejection_reasons
+                .split(',')
+                .map(|reason| reason.trim().to_string())
+                .collect();
+        }
+        if let Some(maintenance_user) = env("HR_MAINTENANCE_USER") {
+            self.maintenance_user = Some(maintenance_user);
+        }
+        if let Some(maintenance_password) = env("HR_MAINTENANCE_PASSWORD") {
+            self.maintenance_password = Some(maintenance_password);
+        }
+        if let Some(attachments_dir) = env("HR_ATTACHMENTS_DIR") {
+            self.attachments_dir = Some(attachments_dir.into());
+        }
+        if let Some(max_attachment_size) = env("HR_MAX_ATTACHMENT_SIZE") {
+            self.max_attachment_size = max_attachment_size
+                .parse()
+                .map_err(|e| ConfigError::Invalid(format!("HR_MAX_ATTACHMENT_SIZE: {}", e)))?;
+        }
+        if let Some(attachment_types) = env("HR_ATTACHMENT_TYPES") {
+            self.attachment_types = attachment_types
+                .split(',')
+                .map(|mime_type| mime_type.trim().to_string())
+                .collect();
+        }
+        if let Some(skill_dictionary) = env("HR_SKILL_DICTIONARY") {
+            self.skill_dictionary = skill_dictionary
+                .split(',')
+                .map(|skill| skill.trim().to_string())
+                .collect();
+        }
+        self.validate()
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.page_size == 0 {
+            Err(ConfigError::Invalid("page_size must be at least 1".into()))
+        } else if self.password_iterations == 0 {
+            Err(ConfigError::Invalid(
+                "password_iterations must be at least 1".into(),
+            ))
+        } else if self.session_ttl <= 0 || self.session_ttl > MAX_SESSION_TTL {
+            Err(ConfigError::Invalid(format!(
+                "session_ttl must be between 1 and {}",
+                MAX_SESSION_TTL
+            )))
+        } else if self.rejection_reasons.is_empty()
+            || self.rejection_reasons.iter().any(String::is_empty)
+        {
+            Err(ConfigError::Invalid(
+                "rejection_reasons must be a list of non empty codes".into(),
+            ))
+        } else if self.max_attachment_size == 0 {
+            Err(ConfigError::Invalid(
+                "max_attachment_size must be at least 1".into(),
+            ))
+        } else if self.attachment_types.is_empty()
+            || self.attachment_types.iter().any(String::is_empty)
+        {
+            Err(ConfigError::Invalid(
+                "attachment_types must be a list of non empty MIME types".into(),
+            ))
+        } else if self.skill_dictionary.iter().any(String::is_empty) {
+            Err(ConfigError::Invalid(
+                "skill_dictionary can't have empty skills".into(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
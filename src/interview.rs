@@ -0,0 +1,101 @@
+//! Interviews of an application
+//! An application can have many interview rounds, each one with a time slot and the users that interview.
+//! An interviewer can't be in two scheduled interviews at the same time.
+use crate::{Id, Timestamp};
+
+/// Status of an interview, only scheduled ones can change
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum InterviewStatus {
+    Scheduled = 0,
+    Completed = 1,
+    NoShow = 2,
+    Cancelled = 3,
+}
+
+impl std::convert::TryFrom<u8> for InterviewStatus {
+    type Error = u8;
+
+    fn try_from(i: u8) -> Result<Self, Self::Error> {
+        match i {
+            0 => Ok(Self::Scheduled),
+            1 => Ok(Self::Completed),
+            2 => Ok(Self::NoShow),
+            3 => Ok(Self::Cancelled),
+            i => Err(i),
+        }
+    }
+}
+
+impl std::fmt::Display for InterviewStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Scheduled => write!(f, "Scheduled"),
+            Self::Completed => write!(f, "Completed"),
+            Self::NoShow => write!(f, "No-show"),
+            Self::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+/// An interview round for the application of `candidate_id` to `job_id`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Interview {
+    pub id: Id,
+    pub job_id: Id,
+    pub candidate_id: Id,
+    /// Starting from 1, cancelled rounds are reused
+    pub round: u32,
+    pub starts_at: Timestamp,
+    /// In seconds
+    pub duration: i64,
+    /// IDs of the interviewing users
+    pub interviewers: Vec<Id>,
+    pub status: InterviewStatus,
+}
+
+impl Interview {
+    pub fn ends_at(&self) -> Timestamp {
+        self.starts_at.saturating_add(self.duration)
+    }
+
+    /// Whether the time slots of both interviews intersect
+    pub fn overlaps(&self, other: &Interview) -> bool {
+        self.starts_at < other.ends_at() && other.starts_at < self.ends_at()
+    }
+}
+
+impl std::fmt::Display for Interview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Round {} of candidate {} for job {}, at {} for {} minutes, interviewers {:?}, {}",
+            self.round,
+            self.candidate_id,
+            self.job_id,
+            self.starts_at,
+            self.duration / 60,
+            self.interviewers,
+            self.status
+        )
+    }
+}
+
+/// Which interviews `Store::list_interviews` returns, unset fields match everything
+#[derive(Debug, Default, Clone, Copy)]
+pub struct InterviewFilter {
+    pub job_id: Option<Id>,
+    pub candidate_id: Option<Id>,
+    pub interviewer_id: Option<Id>,
+}
+
+impl InterviewFilter {
+    pub fn matches(&self, interview: &Interview) -> bool {
+        self.job_id.is_none_or(|id| interview.job_id == id)
+            && self
+                .candidate_id
+                .is_none_or(|id| interview.candidate_id == id)
+            && self
+                .interviewer_id
+                .is_none_or(|id| interview.interviewers.contains(&id))
+    }
+}
@@ -1,333 +1,939 @@
 //! A system for HR Management
-//! The most impportant struct is System which is completely Static, the Connection with the DB is lazily instantiated
+//! The most important struct is `HRSystem`, an instance of the system with its own store, secret and clock.
+//! `System` is a static convenience(behind the `global` feature) over a lazily instantiated `HRSystem`
+#[cfg(feature = "global")]
 use lazy_static::lazy_static;
+pub mod attachment;
+pub mod auth;
+pub mod clock;
+pub mod config;
+pub mod interview;
+pub mod matching;
+pub mod offer;
+pub mod password;
+pub mod pipeline;
+pub mod posting;
+pub mod profile;
+pub mod questionnaire;
+pub mod report;
+pub mod resume;
+pub mod scorecard;
+pub mod search;
+pub mod session;
+pub mod store;
+mod system;
+#[cfg(test)]
 mod tests;
-use std::sync::Mutex;
+
+pub use attachment::{Attachment, DocumentKind};
+pub use auth::{Action, Role};
+pub use clock::{Clock, SystemClock, Timestamp};
+pub use config::{Config, ConfigError};
+pub use interview::{Interview, InterviewFilter, InterviewStatus};
+pub use matching::{ExperienceMatch, JobSkill, MatchScore, SkillMatch};
+pub use offer::{Offer, OfferFilter, OfferStatus};
+pub use password::PasswordHash;
+pub use pipeline::{Outcome, Pipeline, Stage};
+pub use posting::{EmploymentType, JobDetails, SalaryRange, Seniority, Workplace};
+pub use profile::{Education, Proficiency, Profile, Skill, WorkExperience, YearMonth};
+pub use questionnaire::{Answer, Knockout, Question, QuestionKind};
+pub use report::{JobReport, MaintenanceReport};
+pub use resume::{Confidence, Finding, ParsedResume, ProfileField, ResumeReport};
+pub use scorecard::{Rating, Recommendation, Scorecard, ScorecardSummary};
+pub use search::{CandidateSearch, JobSearch};
+pub use session::Session;
+pub use system::{HRSystem, HRSystemBuilder};
+
+pub use store::memory::MemoryStore;
+#[cfg(feature = "sqlite")]
+pub use store::sqlite::SqliteStore;
+pub use store::{Store, StoreError, StoreResult};
 
 pub type Id = i64;
 pub type Token = ring::hmac::Tag;
 
-struct HRSystem {
-    store: DBStore,
-}
-
+#[cfg(feature = "global")]
 lazy_static! {
-    // Note: The Mutex is needed because the SQL library I'm using is not thread safe
-    static ref SYSTEM: Mutex<HRSystem> = Mutex::new(HRSystem::new());
-    // Could be nicer to generate it in HRSystem::new
-    static ref SECRET: [u8; ring::digest::SHA256_OUTPUT_LEN] = ring::rand::generate(&ring::rand::SystemRandom::new()).unwrap().expose();
+    static ref SYSTEM: HRSystem<Box<dyn Store + Send>> =
+        HRSystem::from_config(&Config::load().expect("Invalid configuration"))
+            .expect("Couldn't initialize the system");
 }
 
-/// Most important struct, it exposes all the method to communicate with the system
-/// Completely static.
+/// Static facade over a global `HRSystem` configured through `Config::load`
+/// Completely static, every call is forwarded to the global instance.
+#[cfg(feature = "global")]
 pub struct System;
 
+#[cfg(feature = "global")]
 impl System {
     /// Return all the stored Jobs
-    pub fn list_jobs() -> SQLResult<Vec<Job>> {
-        SYSTEM.lock().unwrap().list_jobs()
+    pub fn list_jobs(principal: &LoggedUser) -> Result<Vec<Job>, ErrorVariant> {
+        SYSTEM.list_jobs(principal)
+    }
+
+    /// Return the `page`th page of Jobs, starting from 0
+    pub fn list_jobs_page(principal: &LoggedUser, page: usize) -> Result<Vec<Job>, ErrorVariant> {
+        SYSTEM.list_jobs_page(principal, page)
+    }
+
+    /// Jobs matching the words and filters of the search, most relevant first
+    pub fn search_jobs(
+        principal: &LoggedUser,
+        search: &JobSearch,
+    ) -> Result<Vec<Job>, ErrorVariant> {
+        SYSTEM.search_jobs(principal, search)
+    }
+
+    /// Candidates whose profile matches the search, most relevant first
+    pub fn search_candidates(
+        principal: &LoggedUser,
+        search: &CandidateSearch,
+    ) -> Result<Vec<(String, Profile)>, ErrorVariant> {
+        SYSTEM.search_candidates(principal, search)
     }
 
     /// Adds a new job posting to the system with the `name` as the name.
-    pub fn create_job_posting(name: String) -> SQLResult<Id> {
-        SYSTEM.lock().unwrap().create_job_posting(name)
+    pub fn create_job_posting(principal: &LoggedUser, name: String) -> Result<Id, ErrorVariant> {
+        SYSTEM.create_job_posting(principal, name)
+    }
+
+    /// Replaces the details of a job posting(description, department, location, salary...)
+    pub fn update_job_posting(
+        principal: &LoggedUser,
+        job_id: Id,
+        details: &JobDetails,
+    ) -> Result<(), ErrorVariant> {
+        SYSTEM.update_job_posting(principal, job_id, details)
+    }
+
+    /// Replaces the skills a job asks for, required or nice to have
+    pub fn set_job_skills(
+        principal: &LoggedUser,
+        job_id: Id,
+        skills: &[JobSkill],
+    ) -> Result<(), ErrorVariant> {
+        SYSTEM.set_job_skills(principal, job_id, skills)
+    }
+
+    /// Skills a job asks for
+    pub fn job_skills(principal: &LoggedUser, job_id: Id) -> Result<Vec<JobSkill>, ErrorVariant> {
+        SYSTEM.job_skills(principal, job_id)
+    }
+
+    /// Applicants of a job still in the process, best matches for its skills and seniority first
+    pub fn rank_applicants(
+        principal: &LoggedUser,
+        job_id: Id,
+    ) -> Result<Vec<(String, MatchScore)>, ErrorVariant> {
+        SYSTEM.rank_applicants(principal, job_id)
+    }
+
+    /// Open jobs the logged candidate can apply to, best matches for their profile first
+    pub fn suggest_jobs(principal: &LoggedUser) -> Result<Vec<(Job, MatchScore)>, ErrorVariant> {
+        SYSTEM.suggest_jobs(principal)
+    }
+
+    /// Replaces the screening questions of a job, it can't have applications yet
+    pub fn set_questionnaire(
+        principal: &LoggedUser,
+        job_id: Id,
+        questions: &[Question],
+    ) -> Result<(), ErrorVariant> {
+        SYSTEM.set_questionnaire(principal, job_id, questions)
+    }
+
+    /// Screening questions of a job in the order they're asked
+    pub fn questionnaire(
+        principal: &LoggedUser,
+        job_id: Id,
+    ) -> Result<Vec<Question>, ErrorVariant> {
+        SYSTEM.questionnaire(principal, job_id)
+    }
+
+    /// Changes how many candidates the job hires, it's filled when that many offers are accepted and reopens if it has room again
+    pub fn set_headcount(
+        principal: &LoggedUser,
+        job_id: Id,
+        headcount: u32,
+    ) -> Result<(), ErrorVariant> {
+        SYSTEM.set_headcount(principal, job_id, headcount)
+    }
+
+    /// Adds a new job posting whose applications follow the given pipeline
+    pub fn create_job_posting_with_pipeline(
+        principal: &LoggedUser,
+        name: String,
+        pipeline_id: Id,
+    ) -> Result<Id, ErrorVariant> {
+        SYSTEM.create_job_posting_with_pipeline(principal, name, pipeline_id)
+    }
+
+    /// Adds a job posting as a draft following the given pipeline(or the default one), it opens when published
+    pub fn create_draft_job_posting(
+        principal: &LoggedUser,
+        name: String,
+        pipeline_id: Option<Id>,
+    ) -> Result<Id, ErrorVariant> {
+        SYSTEM.create_draft_job_posting(principal, name, pipeline_id)
+    }
+
+    /// Opens a draft job to applications
+    pub fn publish_job(principal: &LoggedUser, job_id: Id) -> Result<(), ErrorVariant> {
+        SYSTEM.publish_job(principal, job_id)
+    }
+
+    /// Puts an open job on hold
+    pub fn pause_job(principal: &LoggedUser, job_id: Id) -> Result<(), ErrorVariant> {
+        SYSTEM.pause_job(principal, job_id)
+    }
+
+    /// Opens a job on hold again
+    pub fn resume_job(principal: &LoggedUser, job_id: Id) -> Result<(), ErrorVariant> {
+        SYSTEM.resume_job(principal, job_id)
+    }
+
+    /// Opens a cancelled job again
+    pub fn reopen_job(principal: &LoggedUser, job_id: Id) -> Result<(), ErrorVariant> {
+        SYSTEM.reopen_job(principal, job_id)
+    }
+
+    /// Cancels a job that isn't filled yet
+    pub fn cancel_job(principal: &LoggedUser, job_id: Id) -> Result<(), ErrorVariant> {
+        SYSTEM.cancel_job(principal, job_id)
+    }
+
+    /// Sets when the job takes applications, unset ends mean it's not limited on that side
+    pub fn set_application_window(
+        principal: &LoggedUser,
+        job_id: Id,
+        opens_at: Option<Timestamp>,
+        closes_at: Option<Timestamp>,
+    ) -> Result<(), ErrorVariant> {
+        SYSTEM.set_application_window(principal, job_id, opens_at, closes_at)
+    }
+
+    /// Closes the jobs whose application window ended and expires outstanding offers, returns what it changed
+    pub fn run_maintenance(principal: &LoggedUser) -> Result<MaintenanceReport, ErrorVariant> {
+        SYSTEM.run_maintenance(principal)
+    }
+
+    /// Stores a new hiring pipeline returning its ID, a Withdrawn stage is added if it doesn't have one
+    pub fn create_pipeline(
+        principal: &LoggedUser,
+        pipeline: &Pipeline,
+    ) -> Result<Id, ErrorVariant> {
+        SYSTEM.create_pipeline(principal, pipeline)
+    }
+
+    /// Return all the stored pipelines
+    pub fn list_pipelines(principal: &LoggedUser) -> Result<Vec<Pipeline>, ErrorVariant> {
+        SYSTEM.list_pipelines(principal)
+    }
+
+    /// Registers a new user with the Candidate role
+    pub fn register_candidate(user: String, password: String) -> Result<usize, ErrorVariant> {
+        SYSTEM.register_candidate(user, password)
     }
 
-    pub fn register_candidate(user: String, password: String) -> SQLResult<usize> {
-        SYSTEM.lock().unwrap().register_candidate(user, password)
+    /// Registers the first admin, fails if there's already one
+    pub fn bootstrap_admin(user: String, password: String) -> Result<usize, ErrorVariant> {
+        SYSTEM.bootstrap_admin(user, password)
+    }
+
+    /// Changes the role of `user`, only admins can do it
+    pub fn set_role(principal: &LoggedUser, user: &str, role: Role) -> Result<(), ErrorVariant> {
+        SYSTEM.set_role(principal, user, role)
     }
 
     /// Log ins with the given user and password returns a `LoggedUser` which contains
     /// All the relevant information of the logged user.
-    pub fn login(user: &String, password: &String) -> Option<LoggedUser> {
-        SYSTEM.lock().unwrap().login(user, password)
+    pub fn login(user: &str, password: &str) -> Result<LoggedUser, ErrorVariant> {
+        SYSTEM.login(user, password)
+    }
+
+    /// Checks that the session of the logged user is still valid
+    pub fn check_token(principal: &LoggedUser) -> Result<(), ErrorVariant> {
+        SYSTEM.check_token(principal)
+    }
+
+    /// Ends the session of the logged user
+    pub fn logout(principal: &LoggedUser) -> Result<(), ErrorVariant> {
+        SYSTEM.logout(principal)
+    }
+
+    /// Applies the logged candidate for a job returns the application's ID
+    pub fn apply(principal: &LoggedUser, job_id: Id) -> Result<Id, ErrorVariant> {
+        SYSTEM.apply(principal, job_id)
     }
 
-    /// Applies for a job returns the application's ID
-    pub fn apply(
-        user: &String,
-        token: Token,
-        candidate_id: Id,
+    /// Applies answering the job's screening questions, failing a knockout rule rejects the application right away
+    pub fn apply_with_answers(
+        principal: &LoggedUser,
         job_id: Id,
+        answers: &[(usize, Answer)],
     ) -> Result<Id, ErrorVariant> {
-        SYSTEM
-            .lock()
-            .unwrap()
-            .apply(user, token, candidate_id, job_id)
+        SYSTEM.apply_with_answers(principal, job_id, answers)
     }
 
-    /// Interview a candidate for a job advancing the Application.
-    /// Has no effect if the application's state isn't Candidacy::Applied
-    pub fn interview(user: String, job_id: Id) -> Result<(), ErrorVariant> {
-        SYSTEM.lock().unwrap().interview(user, job_id)
+    /// Answers of a candidate to the job's screening questions, paired with their question
+    pub fn application_answers(
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+    ) -> Result<Vec<(Question, Answer)>, ErrorVariant> {
+        SYSTEM.application_answers(principal, user, job_id)
     }
 
-    /// Approves a candidate for a given job, advancing the application and closing the Job if successful.
-    /// Has no effect if the applications's state isn't Candidacy::Approved
-    pub fn approve(user: String, job_id: Id) -> Result<(), ErrorVariant> {
-        SYSTEM.lock().unwrap().approve(user, job_id)
+    /// Moves the application of a candidate to the named stage of the job's pipeline
+    pub fn advance(
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+        stage: &str,
+    ) -> Result<(), ErrorVariant> {
+        SYSTEM.advance(principal, user, job_id, stage)
     }
 
-    /// Rejects a candidate for a given job, advancing the application.
-    /// Has no effect if the applications's state isn't Candidacy::Approved
-    pub fn reject(user: String, job_id: Id) -> Result<(), ErrorVariant> {
-        SYSTEM.lock().unwrap().reject(user, job_id)
+    /// Schedules a new interview round for the application of `user` to the job, returns its ID
+    pub fn schedule_interview(
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+        starts_at: Timestamp,
+        duration: i64,
+        interviewers: &[&str],
+    ) -> Result<Id, ErrorVariant> {
+        SYSTEM.schedule_interview(principal, user, job_id, starts_at, duration, interviewers)
     }
-}
 
-/// Error can be either
-/// - SQL: An error regarding the DB
-/// - Error: An error generated by the flow of the System
-pub enum ErrorVariant {
-    SQL(rusqlite::Error),
-    Error(Box<dyn std::error::Error>),
-}
+    /// Marks a scheduled interview as completed, no-show or cancelled
+    pub fn mark_interview(
+        principal: &LoggedUser,
+        interview_id: Id,
+        status: InterviewStatus,
+    ) -> Result<(), ErrorVariant> {
+        SYSTEM.mark_interview(principal, interview_id, status)
+    }
 
-// TODO: Add permisioned users(For create_job_posting, and anyhing regarding advancing a process)
-impl HRSystem {
-    pub fn new() -> Self {
-        Self {
-            store: DBStore::new(),
-        }
+    /// Scheduled interviews of `interviewer` that haven't ended yet
+    pub fn upcoming_interviews_for(
+        principal: &LoggedUser,
+        interviewer: &str,
+    ) -> Result<Vec<Interview>, ErrorVariant> {
+        SYSTEM.upcoming_interviews_for(principal, interviewer)
     }
 
-    pub fn list_jobs(&self) -> SQLResult<Vec<Job>> {
-        self.store.list_jobs()
+    /// Scheduled interviews for the job that haven't ended yet
+    pub fn upcoming_interviews_for_job(
+        principal: &LoggedUser,
+        job_id: Id,
+    ) -> Result<Vec<Interview>, ErrorVariant> {
+        SYSTEM.upcoming_interviews_for_job(principal, job_id)
     }
 
-    pub fn create_job_posting(&self, name: String) -> SQLResult<Id> {
-        self.store.add_job_posting(&Job::new(name))
+    /// Submits the scorecard of the logged user for the application of `user` to the job
+    pub fn submit_scorecard(
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+        scorecard: Scorecard,
+    ) -> Result<Id, ErrorVariant> {
+        SYSTEM.submit_scorecard(principal, user, job_id, scorecard)
     }
 
-    pub fn register_candidate(&self, user: String, password: String) -> SQLResult<usize> {
-        let password = ring::digest::digest(&ring::digest::SHA256, password.as_bytes());
-        self.store.add_candidate(&Candidate {
-            id: Default::default(),
-            user,
-            password: password.as_ref().into(),
-        })
+    /// Scorecards of the application of `user` to the job
+    pub fn scorecards(
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+    ) -> Result<Vec<Scorecard>, ErrorVariant> {
+        SYSTEM.scorecards(principal, user, job_id)
     }
 
-    pub fn login(&self, user: &String, password: &String) -> Option<LoggedUser> {
-        let password = ring::digest::digest(&ring::digest::SHA256, password.as_bytes());
-        if let Ok(candidate) = self.store.get_candidate(user) {
-            if &candidate.password == password.as_ref() {
-                let s_key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, SECRET.as_ref());
-                let token = ring::hmac::sign(&s_key, user.as_bytes());
-                Some(LoggedUser {
-                    user_id: candidate.id,
-                    user: candidate.user,
-                    token,
-                })
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+    /// Aggregation of the scorecards of the application of `user` to the job
+    pub fn scorecard_summary(
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+    ) -> Result<ScorecardSummary, ErrorVariant> {
+        SYSTEM.scorecard_summary(principal, user, job_id)
+    }
+
+    /// Interview a candidate for a job, moving the Application to the next non terminal stage.
+    /// Fails with `ErrorVariant::InvalidTransition` if there's no such stage
+    pub fn interview(principal: &LoggedUser, user: &str, job_id: Id) -> Result<(), ErrorVariant> {
+        SYSTEM.interview(principal, user, job_id)
     }
 
-    fn check_token(user: String, token: Token) -> Result<(), Box<dyn std::error::Error>> {
-        let v_key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, SECRET.as_ref());
-        ring::hmac::verify(&v_key, user.as_bytes(), token.as_ref())
-            .map_err(|_| "Wrong token".into())
+    /// Approves a candidate for a given job, moving the application to a Hired stage where it can get an offer.
+    /// Fails with `ErrorVariant::InvalidTransition` if the application can't be hired from its stage
+    pub fn approve(principal: &LoggedUser, user: &str, job_id: Id) -> Result<(), ErrorVariant> {
+        SYSTEM.approve(principal, user, job_id)
     }
 
-    pub fn apply(
-        &self,
-        user: &String,
-        token: Token,
-        candidate_id: Id,
+    /// Rejects a candidate for a given job with one of the `rejection_reasons` and a note, moving the application to a Rejected stage.
+    /// Fails with `ErrorVariant::InvalidTransition` if the application can't be rejected from its stage
+    pub fn reject(
+        principal: &LoggedUser,
+        user: &str,
         job_id: Id,
+        reason: &str,
+        note: &str,
+    ) -> Result<(), ErrorVariant> {
+        SYSTEM.reject(principal, user, job_id, reason, note)
+    }
+
+    /// The reason codes a rejection can use
+    pub fn rejection_reasons() -> &'static [String] {
+        SYSTEM.rejection_reasons()
+    }
+
+    /// Withdraws the application of the logged candidate to the job
+    pub fn withdraw(principal: &LoggedUser, job_id: Id) -> Result<(), ErrorVariant> {
+        SYSTEM.withdraw(principal, job_id)
+    }
+
+    /// Drafts an offer for the approved application of `user` to the job, returns its ID
+    pub fn create_offer(
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+        salary: u64,
+        start_date: Timestamp,
+        expires_at: Timestamp,
     ) -> Result<Id, ErrorVariant> {
-        Self::check_token(user.clone(), token).map_err(|e| ErrorVariant::Error(e))?;
-        // TODO: This would need to hold all candidates in memory
-        // change this
-        let job = self
-            .store
-            .get_job_by_id(job_id)
-            .map_err(|e| ErrorVariant::SQL(e))?;
-
-        if job.state != JobState::Open {
-            Err(ErrorVariant::Error("Job not open".into()))
-        } else {
-            self.store
-                .insert_application(&Application {
-                    job_id: job.id,
-                    candidate_id,
-                    state: Candidacy::default(),
-                })
-                .map_err(|e| ErrorVariant::SQL(e))
-        }
+        SYSTEM.create_offer(principal, user, job_id, salary, start_date, expires_at)
     }
 
-    fn advance_process<F>(&self, user: String, job_id: Id, advance: F) -> Result<(), ErrorVariant>
-    where
-        F: FnOnce(Candidacy) -> Candidacy,
-    {
-        let job = self
-            .store
-            .get_job_by_id(job_id)
-            .map_err(|e| ErrorVariant::SQL(e))?;
-        if job.state != JobState::Open {
-            Err(ErrorVariant::Error("Job not open".into()))
-        } else {
-            let candidate = self
-                .store
-                .get_candidate(&user)
-                .map_err(|e| ErrorVariant::SQL(e))?;
-            let mut application = self
-                .store
-                .get_application(job_id, candidate.id)
-                .map_err(|e| ErrorVariant::SQL(e))?;
-            // TODO: Here if it doesn't change we could return an err that would save some operations
-            application.state = advance(application.state);
-            self.store
-                .update_application(&application)
-                .map(|_| ())
-                .map_err(|e| ErrorVariant::SQL(e))
+    /// Sends a drafted offer to the candidate
+    pub fn send_offer(principal: &LoggedUser, offer_id: Id) -> Result<(), ErrorVariant> {
+        SYSTEM.send_offer(principal, offer_id)
+    }
+
+    /// Accepts an offer sent to the logged candidate, the job is filled if it fills its headcount
+    pub fn accept_offer(principal: &LoggedUser, offer_id: Id) -> Result<(), ErrorVariant> {
+        SYSTEM.accept_offer(principal, offer_id)
+    }
+
+    /// Declines an offer sent to the logged candidate, freeing its slot
+    pub fn decline_offer(principal: &LoggedUser, offer_id: Id) -> Result<(), ErrorVariant> {
+        SYSTEM.decline_offer(principal, offer_id)
+    }
+
+    /// Offers made for the job
+    pub fn offers_for_job(principal: &LoggedUser, job_id: Id) -> Result<Vec<Offer>, ErrorVariant> {
+        SYSTEM.offers_for_job(principal, job_id)
+    }
+
+    /// Offers made to the logged candidate
+    pub fn my_offers(principal: &LoggedUser) -> Result<Vec<Offer>, ErrorVariant> {
+        SYSTEM.my_offers(principal)
+    }
+
+    /// Replaces the profile of the logged candidate
+    pub fn update_profile(principal: &LoggedUser, profile: &Profile) -> Result<(), ErrorVariant> {
+        SYSTEM.update_profile(principal, profile)
+    }
+
+    /// Profile of the logged candidate, empty if they didn't fill it yet
+    pub fn my_profile(principal: &LoggedUser) -> Result<Profile, ErrorVariant> {
+        SYSTEM.my_profile(principal)
+    }
+
+    /// Parses a plain text or Markdown résumé into a draft of the logged candidate's profile, with a confidence report
+    pub fn draft_profile(
+        principal: &LoggedUser,
+        resume: &str,
+    ) -> Result<ParsedResume, ErrorVariant> {
+        SYSTEM.draft_profile(principal, resume)
+    }
+
+    /// Same as `draft_profile` with a text document the logged candidate attached
+    pub fn draft_profile_from_attachment(
+        principal: &LoggedUser,
+        attachment_id: Id,
+    ) -> Result<ParsedResume, ErrorVariant> {
+        SYSTEM.draft_profile_from_attachment(principal, attachment_id)
+    }
+
+    /// Profile of a candidate
+    pub fn candidate_profile(principal: &LoggedUser, user: &str) -> Result<Profile, ErrorVariant> {
+        SYSTEM.candidate_profile(principal, user)
+    }
+
+    /// Profiles of the candidates that applied to the job with their username
+    pub fn applicant_profiles(
+        principal: &LoggedUser,
+        job_id: Id,
+    ) -> Result<Vec<(String, Profile)>, ErrorVariant> {
+        SYSTEM.applicant_profiles(principal, job_id)
+    }
+
+    /// Attaches a document(résumé, cover letter...) to the application of the logged candidate to the job, returns its ID
+    pub fn attach_document(
+        principal: &LoggedUser,
+        job_id: Id,
+        kind: DocumentKind,
+        file_name: &str,
+        mime_type: &str,
+        content: &[u8],
+    ) -> Result<Id, ErrorVariant> {
+        SYSTEM.attach_document(principal, job_id, kind, file_name, mime_type, content)
+    }
+
+    /// Attachments of the application of `user` to the job
+    pub fn list_attachments(
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+    ) -> Result<Vec<Attachment>, ErrorVariant> {
+        SYSTEM.list_attachments(principal, user, job_id)
+    }
+
+    /// An attachment with its content
+    pub fn download_attachment(
+        principal: &LoggedUser,
+        attachment_id: Id,
+    ) -> Result<(Attachment, Vec<u8>), ErrorVariant> {
+        SYSTEM.download_attachment(principal, attachment_id)
+    }
+
+    /// Deletes an attachment, and its content if no other attachment has it
+    pub fn delete_attachment(
+        principal: &LoggedUser,
+        attachment_id: Id,
+    ) -> Result<(), ErrorVariant> {
+        SYSTEM.delete_attachment(principal, attachment_id)
+    }
+
+    /// How the applications to a job are going
+    pub fn job_report(principal: &LoggedUser, job_id: Id) -> Result<JobReport, ErrorVariant> {
+        SYSTEM.job_report(principal, job_id)
+    }
+}
+
+/// Error returned by every operation of the System
+/// - JobNotOpen: The job with that ID is in `state`, only open jobs take applications and only open or closed ones process changes
+/// - JobNotFound: There's no job with that ID
+/// - CandidateNotFound: There's no user with that name
+/// - ApplicationNotFound: The user didn't apply to the job
+/// - AlreadyApplied: The candidate already applied to the job
+/// - UsernameTaken: There's already a user with that name
+/// - PipelineNotFound: There's no pipeline with that ID
+/// - InvalidPipeline: The pipeline definition isn't usable, says why
+/// - InvalidTransition: The application(or interview) can't go from its current stage(`from`) to `to`
+/// - InterviewNotFound: There's no interview with that ID
+/// - InvalidInterview: The interview can't be scheduled as requested, says why
+/// - DoubleBooked: The interviewer already has the interview with `interview_id` at that time
+/// - InvalidScorecard: The scorecard can't be submitted, says why
+/// - ScorecardRequired: The application needs a scorecard before it can be approved or rejected
+/// - InvalidRejection: The rejection has no reason or one that isn't configured, says why
+/// - InvalidJobDetails: The details of the job posting aren't valid, says why
+/// - InvalidJobTransition: The job can't go from its current state(`from`) to `to`
+/// - InvalidProfile: The profile can't be saved, says why
+/// - OutsideApplicationWindow: The job takes applications from `opens_at` until `closes_at`, not now
+/// - InvalidApplicationWindow: The application window doesn't end after it starts
+/// - InvalidHeadcount: The headcount is below 1 or below the slots taken by accepted and outstanding offers
+/// - OfferNotFound: There's no offer with that ID(for candidates, no offer of theirs)
+/// - InvalidOffer: The offer can't be made, says why
+/// - AttachmentNotFound: There's no attachment with that ID(for candidates, no attachment of theirs)
+/// - InvalidAttachment: The document can't be attached, says why
+/// - InvalidQuestionnaire: The screening questions can't be set, says why
+/// - InvalidAnswers: The answers don't fit the job's screening questions, says why
+/// - Unauthorized: Wrong credentials, or a token that's forged, expired or revoked
+/// - PermissionDenied: The user's role doesn't allow the action
+/// - Storage: An unexpected error of the storage backend
+#[derive(Debug)]
+pub enum ErrorVariant {
+    JobNotOpen {
+        job_id: Id,
+        state: JobState,
+    },
+    JobNotFound(Id),
+    CandidateNotFound(String),
+    ApplicationNotFound {
+        job_id: Id,
+        user: String,
+    },
+    AlreadyApplied,
+    UsernameTaken(String),
+    PipelineNotFound(Id),
+    InvalidPipeline(String),
+    InvalidTransition {
+        from: String,
+        to: String,
+    },
+    InterviewNotFound(Id),
+    InvalidInterview(String),
+    DoubleBooked {
+        interviewer: Id,
+        interview_id: Id,
+    },
+    InvalidScorecard(String),
+    ScorecardRequired {
+        job_id: Id,
+        user: String,
+    },
+    InvalidRejection(String),
+    InvalidJobDetails(String),
+    InvalidJobTransition {
+        job_id: Id,
+        from: JobState,
+        to: JobState,
+    },
+    InvalidProfile(String),
+    OutsideApplicationWindow {
+        job_id: Id,
+        opens_at: Option<Timestamp>,
+        closes_at: Option<Timestamp>,
+    },
+    InvalidApplicationWindow {
+        opens_at: Timestamp,
+        closes_at: Timestamp,
+    },
+    InvalidHeadcount {
+        headcount: u32,
+        taken: u32,
+    },
+    OfferNotFound(Id),
+    InvalidOffer(String),
+    AttachmentNotFound(Id),
+    InvalidAttachment(String),
+    InvalidQuestionnaire(String),
+    InvalidAnswers(String),
+    Unauthorized,
+    PermissionDenied(Action),
+    Storage(StoreError),
+}
+
+impl std::fmt::Display for ErrorVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::JobNotOpen { job_id, state } => {
+                write!(f, "Job {} is not open, it's {}", job_id, state)
+            }
+            Self::JobNotFound(id) => write!(f, "There's no job with ID {}", id),
+            Self::CandidateNotFound(user) => write!(f, "There's no user named {}", user),
+            Self::ApplicationNotFound { job_id, user } => {
+                write!(f, "{} didn't apply to job {}", user, job_id)
+            }
+            Self::AlreadyApplied => write!(f, "Already applied to this job"),
+            Self::UsernameTaken(user) => write!(f, "The username {} is taken", user),
+            Self::PipelineNotFound(id) => write!(f, "There's no pipeline with ID {}", id),
+            Self::InvalidPipeline(e) => write!(f, "Invalid pipeline: {}", e),
+            Self::InvalidTransition { from, to } => {
+                write!(f, "An application can't go from {} to {}", from, to)
+            }
+            Self::InterviewNotFound(id) => write!(f, "There's no interview with ID {}", id),
+            Self::InvalidInterview(e) => write!(f, "Invalid interview: {}", e),
+            Self::DoubleBooked {
+                interviewer,
+                interview_id,
+            } => write!(
+                f,
+                "Interviewer {} already has interview {} at that time",
+                interviewer, interview_id
+            ),
+            Self::InvalidScorecard(e) => write!(f, "Invalid scorecard: {}", e),
+            Self::ScorecardRequired { job_id, user } => write!(
+                f,
+                "The application of {} to job {} needs a scorecard first",
+                user, job_id
+            ),
+            Self::InvalidRejection(e) => write!(f, "Invalid rejection: {}", e),
+            Self::InvalidJobDetails(e) => write!(f, "Invalid job details: {}", e),
+            Self::InvalidJobTransition { job_id, from, to } => {
+                write!(f, "Job {} can't go from {} to {}", job_id, from, to)
+            }
+            Self::InvalidProfile(e) => write!(f, "Invalid profile: {}", e),
+            Self::OutsideApplicationWindow {
+                job_id,
+                opens_at,
+                closes_at,
+            } => {
+                write!(f, "Job {} takes applications", job_id)?;
+                if let Some(opens_at) = opens_at {
+                    write!(f, " from {}", opens_at)?;
+                }
+                if let Some(closes_at) = closes_at {
+                    write!(f, " until {}", closes_at)?;
+                }
+                Ok(())
+            }
+            Self::InvalidApplicationWindow {
+                opens_at,
+                closes_at,
+            } => write!(
+                f,
+                "The application window must close after it opens, not at {} if it opens at {}",
+                closes_at, opens_at
+            ),
+            Self::InvalidHeadcount { headcount, taken } => write!(
+                f,
+                "The headcount can't be {}, it must be at least 1 and {} slots are taken by offers",
+                headcount, taken
+            ),
+            Self::OfferNotFound(id) => write!(f, "There's no offer with ID {}", id),
+            Self::InvalidOffer(e) => write!(f, "Invalid offer: {}", e),
+            Self::AttachmentNotFound(id) => write!(f, "There's no attachment with ID {}", id),
+            Self::InvalidAttachment(e) => write!(f, "Invalid attachment: {}", e),
+            Self::InvalidQuestionnaire(e) => write!(f, "Invalid questionnaire: {}", e),
+            Self::InvalidAnswers(e) => write!(f, "Invalid answers: {}", e),
+            Self::Unauthorized => write!(f, "Wrong credentials or session ended"),
+            Self::PermissionDenied(action) => write!(f, "Not allowed to {}", action),
+            Self::Storage(e) => write!(f, "{}", e),
         }
     }
+}
 
-    pub fn interview(&self, user: String, job_id: Id) -> Result<(), ErrorVariant> {
-        self.advance_process(user, job_id, |s| s.interview())
-    }
-
-    pub fn approve(&self, user: String, job_id: Id) -> Result<(), ErrorVariant> {
-        let candidate = self
-            .store
-            .get_candidate(&user)
-            .map_err(|e| ErrorVariant::SQL(e))?;
-        if self.advance_process(user, job_id, |s| s.approve()).is_ok()
-            && matches!(
-                self.store
-                    .get_application(job_id, candidate.id)
-                    .map_err(|e| ErrorVariant::SQL(e))?
-                    .state,
-                Candidacy::Approved(_)
-            )
-        {
-            let mut job = self
-                .store
-                .get_job_by_id(job_id)
-                .map_err(|e| ErrorVariant::SQL(e))?;
-            job.state = JobState::Closed;
-            self.store
-                .update_job_posting(&job)
-                .map_err(|e| ErrorVariant::SQL(e))?;
-            Ok(())
-        } else {
-            Err(ErrorVariant::Error(
-                "Job not Open or user not ready to be approved".into(),
-            ))
+impl std::error::Error for ErrorVariant {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Storage(e) => Some(e),
+            _ => None,
         }
     }
+}
 
-    pub fn reject(&self, user: String, job_id: Id) -> Result<(), ErrorVariant> {
-        self.advance_process(user, job_id, |s| s.reject())
+impl From<StoreError> for ErrorVariant {
+    fn from(e: StoreError) -> Self {
+        Self::Storage(e)
     }
 }
 
 /// A representation of a logged in user(has Token instead of Password)
+/// `token` signs `session_id`, the session must be neither expired nor revoked to be used
+/// Note: `role` is informative, permissions are always checked against the stored role
 pub struct LoggedUser {
     pub user: String,
     pub user_id: Id,
+    pub role: Role,
+    pub session_id: String,
     pub token: Token,
+    pub expires_at: Timestamp,
 }
 
-/// Representation of a candidate
+/// Representation of a candidate(or any user, their role says what they can do)
 #[derive(Default, Clone)]
 pub struct Candidate {
     pub id: Id,
     pub user: String,
-    pub password: Vec<u8>, // <-- TODO: This could be &'a [u8] but it's easier to deal with this for now
+    pub password: PasswordHash,
+    pub role: Role,
 }
 
-/// A job state, either open or closed
-#[derive(PartialEq, Clone, Copy)]
+/// Where a job is in its lifecycle, only open jobs take applications and only open or closed ones process changes
+/// - Draft: Not published yet
+/// - Open: Published and hiring
+/// - OnHold: Paused, hiring resumes when it's opened again
+/// - Filled: Accepted offers filled its headcount, it opens again if the headcount is raised
+/// - Cancelled: Won't hire, it can be reopened
+/// - Closed: Its application window ended, the applications it has keep going. It opens again if the window is extended
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
 pub enum JobState {
+    #[default]
     Open = 0,
-    Closed = 1, // <-- It'd be good to have a reference here to the application that won, but I won't deal with Pin now
+    Filled = 1, // <-- It'd be good to have a reference here to the application that won, but I won't deal with Pin now
+    Draft = 2,
+    OnHold = 3,
+    Cancelled = 4,
+    Closed = 5,
 }
 
-impl From<u8> for JobState {
-    fn from(i: u8) -> Self {
-        match i {
-            0 => Self::Open,
-            1 => Self::Closed,
-            _ => Self::Open,
-        }
+impl JobState {
+    /// Whether a job can go from this state to `to`
+    pub fn can_move(self, to: JobState) -> bool {
+        use JobState::*;
+        matches!(
+            (self, to),
+            (Draft, Open)
+                | (Draft, Cancelled)
+                | (Open, OnHold)
+                | (Open, Filled)
+                | (Open, Cancelled)
+                | (OnHold, Open)
+                | (OnHold, Filled)
+                | (OnHold, Cancelled)
+                | (Filled, Open)
+                | (Cancelled, Open)
+                | (Open, Closed)
+                | (OnHold, Closed)
+                | (Closed, Open)
+                | (Closed, Filled)
+                | (Closed, Cancelled)
+        )
+    }
+
+    /// Whether the applications to the job can go through the process
+    pub fn is_hiring(self) -> bool {
+        matches!(self, Self::Open | Self::Closed)
     }
 }
 
-impl Default for JobState {
-    fn default() -> Self {
-        Self::Open
+impl std::convert::TryFrom<u8> for JobState {
+    type Error = u8;
+
+    fn try_from(i: u8) -> Result<Self, Self::Error> {
+        match i {
+            0 => Ok(Self::Open),
+            1 => Ok(Self::Filled),
+            2 => Ok(Self::Draft),
+            3 => Ok(Self::OnHold),
+            4 => Ok(Self::Cancelled),
+            5 => Ok(Self::Closed),
+            i => Err(i),
+        }
     }
 }
 
 impl std::fmt::Display for JobState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Open => write!(f, "{}", "Open"),
-            Self::Closed => write!(f, "{}", "Close"),
+            Self::Open => write!(f, "Open"),
+            Self::Filled => write!(f, "Filled"),
+            Self::Draft => write!(f, "Draft"),
+            Self::OnHold => write!(f, "On hold"),
+            Self::Cancelled => write!(f, "Cancelled"),
+            Self::Closed => write!(f, "Closed"),
         }
     }
 }
 
-/// The representation of a Job joined with its applicants
+/// The representation of a Job joined with its applicants, the stage they're at and why the rejected ones were
 #[derive(Default, Clone)]
 pub struct Job {
     pub id: Id,
     pub name: String,
-    pub applicants: std::collections::HashMap<String, Candidacy>,
+    pub applicants: std::collections::HashMap<String, String>,
+    pub rejections: std::collections::HashMap<String, Rejection>,
+    /// How many candidates the job hires, it's filled when that many offers are accepted
+    pub headcount: u32,
+    /// Accepted offers, counted by the store
+    pub hired: u32,
+    /// Outstanding offers, each one holds a slot until it's answered or expires. Counted by the store
+    pub offered: u32,
     pub state: JobState,
+    /// Pipeline the applications to this job follow
+    pub pipeline_id: Id,
+    pub details: JobDetails,
+    /// When the job starts taking applications, right away if not set
+    pub opens_at: Option<Timestamp>,
+    /// When the job stops taking applications and gets closed, never if not set
+    pub closes_at: Option<Timestamp>,
+    /// When the job was posted, 0 for jobs posted before it was recorded
+    pub created_at: Timestamp,
 }
 
 impl Job {
     fn new(name: String) -> Self {
         Job {
             name,
+            pipeline_id: pipeline::DEFAULT_PIPELINE_ID,
+            headcount: 1,
             ..Job::default()
         }
     }
+
+    /// Openings left to fill that aren't held by an outstanding offer
+    pub fn open_slots(&self) -> u32 {
+        self.headcount.saturating_sub(self.hired + self.offered)
+    }
+
+    /// Whether `now` is within the application window
+    pub fn in_application_window(&self, now: Timestamp) -> bool {
+        self.opens_at.is_none_or(|opens_at| opens_at <= now) && !self.window_ended(now)
+    }
+
+    /// Whether the application window ended by `now`
+    pub fn window_ended(&self, now: Timestamp) -> bool {
+        self.closes_at.is_some_and(|closes_at| closes_at <= now)
+    }
 }
 impl std::fmt::Display for Job {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Name: {}, State: {}\n Applicants: {:?}",
-            self.name, self.state, self.applicants
-        )
+            "Name: {}, State: {}, Open slots: {} of {}\n {}\n Applicants: {:?}",
+            self.name,
+            self.state,
+            self.open_slots(),
+            self.headcount,
+            self.details,
+            self.applicants
+        )?;
+        match (self.opens_at, self.closes_at) {
+            (Some(opens_at), Some(closes_at)) => write!(
+                f,
+                "\n Takes applications from {} until {}",
+                opens_at, closes_at
+            )?,
+            (Some(opens_at), None) => write!(f, "\n Takes applications from {}", opens_at)?,
+            (None, Some(closes_at)) => write!(f, "\n Takes applications until {}", closes_at)?,
+            (None, None) => (),
+        }
+        for (user, rejection) in self.rejections.iter() {
+            write!(f, "\n {} rejected: {}", user, rejection)?;
+        }
+        Ok(())
+    }
+}
+
+/// Why an application was rejected, `reason` is one of the configured rejection reasons
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Rejection {
+    pub reason: String,
+    pub note: String,
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.note.is_empty() {
+            write!(f, "{}", self.reason)
+        } else {
+            write!(f, "{}({})", self.reason, self.note)
+        }
     }
 }
 
-struct Application {
+/// An application of a candidate to a job
+pub struct Application {
     pub job_id: Id,
     pub candidate_id: Id,
-    pub state: Candidacy,
+    /// Position of the current stage in the job's pipeline
+    pub stage: usize,
+    /// Set when the application reaches a Rejected stage
+    pub rejection: Option<Rejection>,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct AppliedApplication;
 
+// The typed states only offer the legal transitions, so flows built with them are checked at compile time
 impl AppliedApplication {
-    fn interview(self) -> InterviewedApplication {
+    pub fn interview(self) -> InterviewedApplication {
         InterviewedApplication
     }
+
+    pub fn withdraw(self) -> WithdrawnApplication {
+        WithdrawnApplication
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct InterviewedApplication;
 
 impl InterviewedApplication {
-    fn approve(self) -> ApprovedApplication {
+    pub fn approve(self) -> ApprovedApplication {
         ApprovedApplication
     }
 
-    fn reject(self) -> RejectedApplication {
+    pub fn reject(self) -> RejectedApplication {
         RejectedApplication
     }
+
+    pub fn withdraw(self) -> WithdrawnApplication {
+        WithdrawnApplication
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -336,12 +942,17 @@ pub struct RejectedApplication;
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct ApprovedApplication;
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct WithdrawnApplication;
+
+/// The stages of the default pipeline, see `Pipeline::default_pipeline`
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Candidacy {
     Applied(AppliedApplication),
     Interviewed(InterviewedApplication),
     Rejected(RejectedApplication),
     Approved(ApprovedApplication),
+    Withdrawn(WithdrawnApplication),
 }
 
 impl From<u8> for Candidacy {
@@ -351,6 +962,7 @@ impl From<u8> for Candidacy {
             1 => Self::Interviewed(InterviewedApplication),
             2 => Self::Rejected(RejectedApplication),
             3 => Self::Approved(ApprovedApplication),
+            4 => Self::Withdrawn(WithdrawnApplication),
             _ => Default::default(),
         }
     }
@@ -363,6 +975,19 @@ impl From<Candidacy> for u8 {
             Candidacy::Interviewed(_) => 1,
             Candidacy::Rejected(_) => 2,
             Candidacy::Approved(_) => 3,
+            Candidacy::Withdrawn(_) => 4,
+        }
+    }
+}
+
+impl std::fmt::Display for Candidacy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Applied(_) => write!(f, "Applied"),
+            Self::Interviewed(_) => write!(f, "Interviewed"),
+            Self::Rejected(_) => write!(f, "Rejected"),
+            Self::Approved(_) => write!(f, "Approved"),
+            Self::Withdrawn(_) => write!(f, "Withdrawn"),
         }
     }
 }
@@ -373,216 +998,51 @@ impl Default for Candidacy {
     }
 }
 
+// Runtime checked transitions, illegal ones fail with `ErrorVariant::InvalidTransition`
 impl Candidacy {
-    // Might use Result<Self, Self> or something like that later
-    fn interview(self) -> Self {
-        if let Self::Applied(application) = self {
-            Self::Interviewed(application.interview())
-        } else {
-            self
-        }
+    /// Position of the state in the default pipeline
+    pub fn stage(self) -> usize {
+        u8::from(self).into()
     }
 
-    // Might use Result<Self, Self> or something like that later
-    fn approve(self) -> Self {
-        if let Self::Interviewed(application) = self {
-            Self::Approved(application.approve())
-        } else {
-            self
+    pub fn interview(self) -> Result<Self, ErrorVariant> {
+        match self {
+            Self::Applied(application) => Ok(Self::Interviewed(application.interview())),
+            from => Err(ErrorVariant::InvalidTransition {
+                from: from.to_string(),
+                to: Self::Interviewed(InterviewedApplication).to_string(),
+            }),
         }
     }
 
-    fn reject(self) -> Self {
-        if let Self::Interviewed(application) = self {
-            Self::Rejected(application.reject())
-        } else {
-            self
+    pub fn approve(self) -> Result<Self, ErrorVariant> {
+        match self {
+            Self::Interviewed(application) => Ok(Self::Approved(application.approve())),
+            from => Err(ErrorVariant::InvalidTransition {
+                from: from.to_string(),
+                to: Self::Approved(ApprovedApplication).to_string(),
+            }),
         }
     }
-}
 
-use rusqlite::{params, Connection, Result as SQLResult};
-struct DBStore {
-    conn: Connection,
-}
-
-// TODO: Methods here can be part of a TRAIT to be implemented in any Backend(diesel, in-memory, sqlx, etc..)
-// TODO: Also! A lot of these methods return vec or create vecs calling the DB, this is not ideal, we should've some kind of pagination
-impl DBStore {
-    fn new() -> Self {
-        DBStore {
-            conn: Self::setup_db(),
+    pub fn reject(self) -> Result<Self, ErrorVariant> {
+        match self {
+            Self::Interviewed(application) => Ok(Self::Rejected(application.reject())),
+            from => Err(ErrorVariant::InvalidTransition {
+                from: from.to_string(),
+                to: Self::Rejected(RejectedApplication).to_string(),
+            }),
         }
     }
 
-    fn setup_db() -> Connection {
-        // TODO: dotenv to configure this
-        // TODO quickest way to do testing, but should have some kind of configuration
-        #[cfg(not(test))]
-        let conn = Connection::open("hr_store.db").unwrap();
-
-        #[cfg(test)]
-        let conn = Connection::open_in_memory().unwrap();
-
-        conn.execute(
-            "
-            create table if not exists jobs (
-                id integer primary key,
-                name text not null unique,
-                state integer not null
-            )",
-            [],
-        )
-        .unwrap();
-
-        // TODO: SALT!
-        conn.execute(
-            "
-            create table if not exists candidates (
-                id integer primary key,
-                name text unique,
-                password blob not null
-            )",
-            [],
-        )
-        .unwrap();
-
-        conn.execute(
-            "
-            create table if not exists applications (
-                state integer not null,
-                job_id int,
-                candidate_id int,
-                FOREIGN KEY (job_id) REFERENCES jobs(id),
-                FOREIGN KEY (candidate_id) REFERENCES candidates(id)
-                PRIMARY KEY (job_id, candidate_id)
-            )",
-            [],
-        )
-        .unwrap();
-
-        conn
-    }
-
-    fn get_application(&self, job_id: Id, candidate_id: Id) -> SQLResult<Application> {
-        self.conn.query_row(
-            "SELECT state  FROM applications WHERE candidate_id = (?1) AND job_id = (?2)",
-            [candidate_id, job_id],
-            |row| {
-                Ok(Application {
-                    job_id,
-                    candidate_id,
-                    state: row.get::<_, u8>(0)?.into(),
-                })
-            },
-        )
-    }
-    fn add_job_posting(&self, job: &Job) -> SQLResult<Id> {
-        let state = job.state as u8;
-        self.conn.execute(
-            "INSERT INTO jobs (name, state) values (?1, ?2)",
-            params![job.name, state],
-        )?;
-
-        Ok(self.conn.last_insert_rowid())
-    }
-
-    fn list_jobs(&self) -> SQLResult<Vec<Job>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT jobs.id, jobs.name, jobs.state, applications.state, candidates.name
-            FROM jobs
-            LEFT JOIN applications ON applications.job_id = jobs.id
-            LEFT JOIN candidates ON candidates.id = applications.candidate_id",
-        )?;
-
-        let mut rows = stmt.query([])?;
-
-        let mut job_map = std::collections::HashMap::<Id, Job>::new();
-        while let Some(row) = rows.next()? {
-            let mut default_dict: std::collections::HashMap<String, Candidacy> = Default::default();
-            if row.get::<_, u8>(3).is_ok() && row.get::<_, String>(4).is_ok() {
-                default_dict.insert(row.get(4).unwrap(), row.get::<_, u8>(3).unwrap().into());
-            }
-            job_map
-                .entry(row.get(0)?)
-                .and_modify(|e| {
-                    if row.get::<_, u8>(3).is_ok() && row.get::<_, String>(4).is_ok() {
-                        e.applicants
-                            .insert(row.get(4).unwrap(), row.get::<_, u8>(3).unwrap().into());
-                    }
-                })
-                .or_insert(Job {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    state: row.get::<_, u8>(2)?.into(),
-                    applicants: default_dict,
-                });
+    pub fn withdraw(self) -> Result<Self, ErrorVariant> {
+        match self {
+            Self::Applied(application) => Ok(Self::Withdrawn(application.withdraw())),
+            Self::Interviewed(application) => Ok(Self::Withdrawn(application.withdraw())),
+            from => Err(ErrorVariant::InvalidTransition {
+                from: from.to_string(),
+                to: Self::Withdrawn(WithdrawnApplication).to_string(),
+            }),
         }
-
-        Ok(job_map.into_iter().map(|(_, job)| job).collect())
-    }
-
-    // Note: This doesn't actually construct a Job because it doesn't contain the applicants
-    // This, is more efficient since I never need the applicant when getting a job by ID.
-    fn get_job_by_id(&self, job_id: Id) -> SQLResult<Job> {
-        self.conn.query_row(
-            "SELECT name, state FROM jobs where id = (?1)",
-            [job_id],
-            |row| {
-                Ok(Job {
-                    id: job_id,
-                    name: row.get(0)?,
-                    state: row.get::<_, u8>(1)?.into(),
-                    applicants: Default::default(),
-                })
-            },
-        )
-    }
-
-    fn add_candidate(&self, candidate: &Candidate) -> SQLResult<usize> {
-        self.conn.execute(
-            "INSERT INTO candidates (name, password) values (?1, ?2)",
-            params![&candidate.user, candidate.password],
-        )
-    }
-
-    fn get_candidate(&self, candidate_name: &str) -> SQLResult<Candidate> {
-        self.conn.query_row(
-            "SELECT id, name, password FROM candidates WHERE name = (?1)",
-            &[candidate_name],
-            |row| {
-                Ok(Candidate {
-                    id: row.get(0)?,
-                    user: row.get(1)?,
-                    password: row.get(2)?,
-                })
-            },
-        )
-    }
-
-    fn update_job_posting(&self, job: &Job) -> SQLResult<usize> {
-        let state = job.state as u8;
-        self.conn.execute(
-            "UPDATE jobs SET name = (?1), state = (?2) where id = (?3)",
-            params![job.name, state, job.id],
-        )
-    }
-
-    fn insert_application(&self, application: &Application) -> SQLResult<Id> {
-        let state: u8 = application.state.into();
-        self.conn.execute(
-            "INSERT INTO applications (job_id, candidate_id, state) values (?1, ?2, ?3)",
-            params![&application.job_id, &application.candidate_id, state],
-        )?;
-
-        Ok(self.conn.last_insert_rowid())
-    }
-
-    fn update_application(&self, application: &Application) -> SQLResult<usize> {
-        let state: u8 = application.state.into();
-        self.conn.execute(
-            "UPDATE  applications SET state = (?3) WHERE job_id = (?1) AND candidate_id = (?2)",
-            params![&application.job_id, &application.candidate_id, state,],
-        )
     }
 }
@@ -0,0 +1,304 @@
+//! Candidate to job matching
+//! Compares the skills and work history of a candidate's profile with the skills a job asks for and its seniority.
+//! The result is a score from 0 to 100 that keeps the breakdown of how it was reached.
+use crate::posting::Seniority;
+use crate::profile::{Proficiency, Profile, YearMonth};
+
+/// Points of each part of the score, parts the job doesn't ask for are left out and the rest scaled to 100
+pub const REQUIRED_SKILLS_WEIGHT: f64 = 60.0;
+pub const NICE_TO_HAVE_SKILLS_WEIGHT: f64 = 20.0;
+pub const EXPERIENCE_WEIGHT: f64 = 20.0;
+/// Most skills a job can ask for
+pub const MAX_JOB_SKILLS: usize = 50;
+
+/// A skill a job asks for, with the proficiency it wants
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct JobSkill {
+    pub name: String,
+    pub proficiency: Proficiency,
+    /// Whether it's required or nice to have
+    pub required: bool,
+}
+
+impl JobSkill {
+    pub fn required(name: &str, proficiency: Proficiency) -> Self {
+        Self {
+            name: name.into(),
+            proficiency,
+            required: true,
+        }
+    }
+
+    pub fn nice_to_have(name: &str, proficiency: Proficiency) -> Self {
+        Self {
+            name: name.into(),
+            proficiency,
+            required: false,
+        }
+    }
+}
+
+impl std::fmt::Display for JobSkill {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}({}, {})",
+            self.name,
+            self.proficiency,
+            if self.required {
+                "required"
+            } else {
+                "nice to have"
+            }
+        )
+    }
+}
+
+/// Checks there aren't too many skills, they have a name and each one is asked for once(ignoring case)
+pub fn validate_job_skills(skills: &[JobSkill]) -> Result<(), String> {
+    if skills.len() > MAX_JOB_SKILLS {
+        return Err(format!(
+            "A job can't ask for more than {} skills",
+            MAX_JOB_SKILLS
+        ));
+    }
+    for (i, skill) in skills.iter().enumerate() {
+        if skill.name.trim().is_empty() {
+            return Err("Skills need a name".into());
+        }
+        if skills[..i]
+            .iter()
+            .any(|other| same_skill(&other.name, &skill.name))
+        {
+            return Err(format!("{} is asked for more than once", skill.name));
+        }
+    }
+    Ok(())
+}
+
+/// Years of experience expected for a seniority
+pub fn expected_years(seniority: Seniority) -> f64 {
+    match seniority {
+        Seniority::Junior => 0.0,
+        Seniority::Mid => 2.0,
+        Seniority::Senior => 5.0,
+        Seniority::Lead => 8.0,
+        Seniority::Principal => 10.0,
+    }
+}
+
+/// Years covered by the work history, overlapping periods count once and current ones last until `now`
+pub fn years_of_experience(profile: &Profile, now: YearMonth) -> f64 {
+    // Periods as [start, end) month indexes, both months included
+    let mut periods: Vec<(i64, i64)> = profile
+        .experience
+        .iter()
+        .map(|job| (job.start.index(), job.end.unwrap_or(now).index() + 1))
+        .filter(|(start, end)| start < end)
+        .collect();
+    periods.sort_unstable();
+    let mut months = 0;
+    let mut covered_until = i64::MIN;
+    for (start, end) in periods {
+        let start = start.max(covered_until);
+        if start < end {
+            months += end - start;
+            covered_until = end;
+        }
+    }
+    months as f64 / 12.0
+}
+
+fn same_skill(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+/// How a skill the job asks for matches the candidate's
+#[derive(Debug, PartialEq, Clone)]
+pub struct SkillMatch {
+    pub skill: String,
+    pub wanted: Proficiency,
+    /// The candidate's proficiency, `None` if they don't list it
+    pub has: Option<Proficiency>,
+    /// From 0 to 1: 1 at the wanted proficiency or above, a fraction below it and 0 without the skill
+    pub credit: f64,
+}
+
+impl SkillMatch {
+    fn new(skill: &JobSkill, profile: &Profile) -> Self {
+        let has = profile
+            .skills
+            .iter()
+            .find(|candidate| same_skill(&candidate.name, &skill.name))
+            .map(|candidate| candidate.proficiency);
+        let credit = match has {
+            None => 0.0,
+            Some(has) if has >= skill.proficiency => 1.0,
+            Some(has) => (has as u8 + 1) as f64 / (skill.proficiency as u8 + 1) as f64,
+        };
+        Self {
+            skill: skill.name.clone(),
+            wanted: skill.proficiency,
+            has,
+            credit,
+        }
+    }
+}
+
+impl std::fmt::Display for SkillMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} wanted, ", self.skill, self.wanted)?;
+        match self.has {
+            Some(has) => write!(f, "has {}", has)?,
+            None => write!(f, "missing")?,
+        }
+        write!(f, "({:.0}%)", self.credit * 100.0)
+    }
+}
+
+/// How the candidate's years of experience compare with the job's seniority
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExperienceMatch {
+    pub seniority: Seniority,
+    pub expected_years: f64,
+    pub years: f64,
+    /// From 0 to 1, the fraction of the expected years the candidate has
+    pub credit: f64,
+}
+
+impl std::fmt::Display for ExperienceMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1} years of the {} expected for {}({:.0}%)",
+            self.years,
+            self.expected_years,
+            self.seniority,
+            self.credit * 100.0
+        )
+    }
+}
+
+/// Score of a candidate for a job with its breakdown
+#[derive(Debug, PartialEq, Clone)]
+pub struct MatchScore {
+    /// From 0 to 100
+    pub score: f64,
+    /// In the order the job asks for them
+    pub required: Vec<SkillMatch>,
+    pub nice_to_have: Vec<SkillMatch>,
+    /// `None` if the job doesn't give its seniority
+    pub experience: Option<ExperienceMatch>,
+}
+
+impl MatchScore {
+    /// Scores `profile` for a job asking for `skills` and `seniority`, `None` if it asks for neither.
+    /// Each part gets its weight times the average credit of what's in it, `now` ends the current jobs
+    pub fn new(
+        skills: &[JobSkill],
+        seniority: Option<Seniority>,
+        profile: &Profile,
+        now: YearMonth,
+    ) -> Option<Self> {
+        let matches = |required: bool| -> Vec<SkillMatch> {
+            skills
+                .iter()
+                .filter(|skill| skill.required == required)
+                .map(|skill| SkillMatch::new(skill, profile))
+                .collect()
+        };
+        let required = matches(true);
+        let nice_to_have = matches(false);
+        let experience = seniority.map(|seniority| {
+            let expected_years = expected_years(seniority);
+            let years = years_of_experience(profile, now);
+            ExperienceMatch {
+                seniority,
+                expected_years,
+                years,
+                credit: if years >= expected_years {
+                    1.0
+                } else {
+                    years / expected_years
+                },
+            }
+        });
+
+        let average = |matches: &[SkillMatch]| {
+            if matches.is_empty() {
+                None
+            } else {
+                Some(matches.iter().map(|skill| skill.credit).sum::<f64>() / matches.len() as f64)
+            }
+        };
+        let parts = [
+            (REQUIRED_SKILLS_WEIGHT, average(&required)),
+            (NICE_TO_HAVE_SKILLS_WEIGHT, average(&nice_to_have)),
+            (
+                EXPERIENCE_WEIGHT,
+                experience.as_ref().map(|experience| experience.credit),
+            ),
+        ];
+        let (points, weights) = parts
+            .iter()
+            .filter_map(|(weight, credit)| credit.map(|credit| (weight * credit, *weight)))
+            .fold((0.0, 0.0), |(points, weights), (part_points, weight)| {
+                (points + part_points, weights + weight)
+            });
+        if weights == 0.0 {
+            return None;
+        }
+        Some(Self {
+            score: points / weights * 100.0,
+            required,
+            nice_to_have,
+            experience,
+        })
+    }
+
+    /// Whether the candidate has every required skill at the wanted proficiency
+    pub fn meets_requirements(&self) -> bool {
+        self.required.iter().all(|skill| skill.credit >= 1.0)
+    }
+}
+
+/// Orders matches best first: those meeting the requirements, then by score
+pub fn best_first(a: &MatchScore, b: &MatchScore) -> std::cmp::Ordering {
+    b.meets_requirements()
+        .cmp(&a.meets_requirements())
+        .then(b.score.total_cmp(&a.score))
+}
+
+impl std::fmt::Display for MatchScore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0}/100", self.score)?;
+        if !self.meets_requirements() {
+            write!(f, ", doesn't meet the requirements")?;
+        }
+        for (name, weight, skills) in [
+            ("Required skills", REQUIRED_SKILLS_WEIGHT, &self.required),
+            (
+                "Nice to have skills",
+                NICE_TO_HAVE_SKILLS_WEIGHT,
+                &self.nice_to_have,
+            ),
+        ]
+        .iter()
+        {
+            if !skills.is_empty() {
+                write!(f, "\n {}(weight {}):", name, weight)?;
+                for skill in skills.iter() {
+                    write!(f, "\n  {}", skill)?;
+                }
+            }
+        }
+        if let Some(experience) = &self.experience {
+            write!(
+                f,
+                "\n Experience(weight {}): {}",
+                EXPERIENCE_WEIGHT, experience
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+//! Job offers
+//! Approved applications get an offer, drafted first and then sent to the candidate who accepts or declines it.
+//! Outstanding offers hold an open slot of the job, accepted ones fill it and declined or expired ones free it.
+use crate::{Id, Timestamp};
+
+/// Largest salary the stores can keep, they use signed 64 bit integers
+pub const MAX_SALARY: u64 = i64::MAX as u64;
+
+/// Status of an offer, drafted and sent offers are outstanding
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OfferStatus {
+    Drafted = 0,
+    Sent = 1,
+    Accepted = 2,
+    Declined = 3,
+    Expired = 4,
+}
+
+impl OfferStatus {
+    /// Whether the offer still holds a slot waiting for an answer
+    pub fn is_outstanding(self) -> bool {
+        matches!(self, Self::Drafted | Self::Sent)
+    }
+}
+
+impl std::convert::TryFrom<u8> for OfferStatus {
+    type Error = u8;
+
+    fn try_from(i: u8) -> Result<Self, Self::Error> {
+        match i {
+            0 => Ok(Self::Drafted),
+            1 => Ok(Self::Sent),
+            2 => Ok(Self::Accepted),
+            3 => Ok(Self::Declined),
+            4 => Ok(Self::Expired),
+            i => Err(i),
+        }
+    }
+}
+
+impl std::fmt::Display for OfferStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Drafted => write!(f, "Drafted"),
+            Self::Sent => write!(f, "Sent"),
+            Self::Accepted => write!(f, "Accepted"),
+            Self::Declined => write!(f, "Declined"),
+            Self::Expired => write!(f, "Expired"),
+        }
+    }
+}
+
+/// An offer for the application of `candidate_id` to `job_id`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Offer {
+    pub id: Id,
+    pub job_id: Id,
+    pub candidate_id: Id,
+    /// Yearly salary
+    pub salary: u64,
+    pub start_date: Timestamp,
+    /// Outstanding offers expire at this time
+    pub expires_at: Timestamp,
+    pub status: OfferStatus,
+    pub created_at: Timestamp,
+}
+
+impl Offer {
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        self.status.is_outstanding() && now >= self.expires_at
+    }
+}
+
+impl std::fmt::Display for Offer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Offer for candidate {} to job {}: salary {}, starting at {}, expires at {}, {}",
+            self.candidate_id,
+            self.job_id,
+            self.salary,
+            self.start_date,
+            self.expires_at,
+            self.status
+        )
+    }
+}
+
+/// Which offers `Store::list_offers` returns, unset fields match everything
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OfferFilter {
+    pub job_id: Option<Id>,
+    pub candidate_id: Option<Id>,
+}
+
+impl OfferFilter {
+    pub fn matches(&self, offer: &Offer) -> bool {
+        self.job_id.is_none_or(|id| offer.job_id == id)
+            && self.candidate_id.is_none_or(|id| offer.candidate_id == id)
+    }
+}
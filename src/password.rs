@@ -0,0 +1,150 @@
+//! Password hashing
+//! New passwords are hashed with PBKDF2-HMAC-SHA256 and a random per user salt.
+//! Unsalted SHA-256 hashes from older versions are still accepted so they can be upgraded on login.
+use std::num::NonZeroU32;
+
+/// Default PBKDF2 iterations for new hashes
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = ring::digest::SHA256_OUTPUT_LEN;
+static PBKDF2_ALGORITHM: ring::pbkdf2::Algorithm = ring::pbkdf2::PBKDF2_HMAC_SHA256;
+
+/// A stored password hash tagged with the algorithm that generated it
+#[derive(Clone, Debug, PartialEq)]
+pub enum PasswordHash {
+    /// Unsalted SHA-256, only kept to upgrade passwords stored before PBKDF2
+    LegacySha256(Vec<u8>),
+    Pbkdf2Sha256 {
+        iterations: u32,
+        salt: Vec<u8>,
+        hash: Vec<u8>,
+    },
+}
+
+impl Default for PasswordHash {
+    fn default() -> Self {
+        Self::LegacySha256(Vec::new())
+    }
+}
+
+impl PasswordHash {
+    pub const LEGACY_SHA256: &'static str = "sha256";
+    pub const PBKDF2_SHA256: &'static str = "pbkdf2-sha256";
+
+    /// Hashes `password` with PBKDF2 with a new random salt
+    pub fn new(password: &str, iterations: u32) -> Self {
+        let salt: [u8; SALT_LEN] = ring::rand::generate(&ring::rand::SystemRandom::new())
+            .unwrap()
+            .expose();
+        let mut hash = vec![0; HASH_LEN];
+        ring::pbkdf2::derive(
+            PBKDF2_ALGORITHM,
+            Self::non_zero(iterations),
+            &salt,
+            password.as_bytes(),
+            &mut hash,
+        );
+        Self::Pbkdf2Sha256 {
+            iterations,
+            salt: salt.into(),
+            hash,
+        }
+    }
+
+    /// The unsalted SHA-256 hash used by older versions
+    pub fn legacy(password: &str) -> Self {
+        Self::LegacySha256(
+            ring::digest::digest(&ring::digest::SHA256, password.as_bytes())
+                .as_ref()
+                .into(),
+        )
+    }
+
+    /// A hash no password matches that costs as much to verify as a new one with `iterations`.
+    /// Logins of unknown users check against it, so how long they take doesn't tell which users exist
+    pub(crate) fn dummy(iterations: u32) -> Self {
+        Self::Pbkdf2Sha256 {
+            iterations,
+            salt: vec![0; SALT_LEN],
+            // Full length, ring returns early on an empty one
+            hash: vec![0; HASH_LEN],
+        }
+    }
+
+    pub fn verify(&self, password: &str) -> bool {
+        match self {
+            // Compared in constant time, ring deprecated the function without a replacement
+            #[allow(deprecated)]
+            Self::LegacySha256(hash) => ring::constant_time::verify_slices_are_equal(
+                ring::digest::digest(&ring::digest::SHA256, password.as_bytes()).as_ref(),
+                hash,
+            )
+            .is_ok(),
+            Self::Pbkdf2Sha256 {
+                iterations,
+                salt,
+                hash,
+            } => ring::pbkdf2::verify(
+                PBKDF2_ALGORITHM,
+                Self::non_zero(*iterations),
+                salt,
+                password.as_bytes(),
+                hash,
+            )
+            .is_ok(),
+        }
+    }
+
+    /// Whether the hash is weaker than a new one with `iterations` would be
+    pub fn needs_rehash(&self, iterations: u32) -> bool {
+        match self {
+            Self::LegacySha256(_) => true,
+            Self::Pbkdf2Sha256 {
+                iterations: current,
+                ..
+            } => *current < iterations,
+        }
+    }
+
+    /// Tag of the algorithm as stored in the DB
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            Self::LegacySha256(_) => Self::LEGACY_SHA256,
+            Self::Pbkdf2Sha256 { .. } => Self::PBKDF2_SHA256,
+        }
+    }
+
+    /// Splits the hash into (algorithm, iterations, salt, hash) to store it
+    pub fn to_parts(&self) -> (&'static str, u32, &[u8], &[u8]) {
+        match self {
+            Self::LegacySha256(hash) => (self.algorithm(), 0, &[], hash),
+            Self::Pbkdf2Sha256 {
+                iterations,
+                salt,
+                hash,
+            } => (self.algorithm(), *iterations, salt, hash),
+        }
+    }
+
+    /// Inverse of `to_parts`, `None` if the algorithm is unknown
+    pub fn from_parts(
+        algorithm: &str,
+        iterations: u32,
+        salt: Vec<u8>,
+        hash: Vec<u8>,
+    ) -> Option<Self> {
+        match algorithm {
+            Self::LEGACY_SHA256 => Some(Self::LegacySha256(hash)),
+            Self::PBKDF2_SHA256 => Some(Self::Pbkdf2Sha256 {
+                iterations,
+                salt,
+                hash,
+            }),
+            _ => None,
+        }
+    }
+
+    fn non_zero(iterations: u32) -> NonZeroU32 {
+        NonZeroU32::new(iterations).unwrap_or(NonZeroU32::MIN)
+    }
+}
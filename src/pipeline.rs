@@ -0,0 +1,202 @@
+//! Hiring pipelines
+//! A pipeline is an ordered list of stages, each one says which stages an application can move to from it.
+//! Stages with an outcome are terminal, reaching them ends the process for that application.
+//! Candidates can withdraw from any non terminal stage, that moves them to the pipeline's Withdrawn stage.
+//! Every job follows a pipeline, the default one is the classic Applied -> Interviewed -> Approved/Rejected.
+use crate::Id;
+
+/// ID of the pipeline every store starts with, jobs use it unless told otherwise
+pub const DEFAULT_PIPELINE_ID: Id = 1;
+
+/// How a terminal stage ends the process
+/// - Hired: The candidate got the job, reaching it closes the job
+/// - Rejected: The candidate didn't get the job
+/// - Withdrawn: The candidate left the process, only reachable through a withdrawal
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Outcome {
+    Hired = 0,
+    Rejected = 1,
+    Withdrawn = 2,
+}
+
+impl std::convert::TryFrom<u8> for Outcome {
+    type Error = u8;
+
+    fn try_from(i: u8) -> Result<Self, Self::Error> {
+        match i {
+            0 => Ok(Self::Hired),
+            1 => Ok(Self::Rejected),
+            2 => Ok(Self::Withdrawn),
+            i => Err(i),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Stage {
+    pub name: String,
+    /// Names of the stages an application can move to from this one
+    pub next: Vec<String>,
+    /// `Some` for terminal stages
+    pub outcome: Option<Outcome>,
+}
+
+/// A pipeline, applications start in its first stage
+#[derive(Debug, PartialEq, Clone)]
+pub struct Pipeline {
+    pub id: Id,
+    pub name: String,
+    pub stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    /// An empty pipeline, add the stages with `stage` and `terminal`
+    pub fn new(name: &str) -> Self {
+        Self {
+            id: Default::default(),
+            name: name.into(),
+            stages: Vec::new(),
+        }
+    }
+
+    /// Adds a stage that can move to the `next` stages, the first stage added is where applications start
+    pub fn stage(mut self, name: &str, next: &[&str]) -> Self {
+        self.stages.push(Stage {
+            name: name.into(),
+            next: next.iter().map(|stage| stage.to_string()).collect(),
+            outcome: None,
+        });
+        self
+    }
+
+    /// Adds a stage that ends the process with `outcome`
+    pub fn terminal(mut self, name: &str, outcome: Outcome) -> Self {
+        self.stages.push(Stage {
+            name: name.into(),
+            next: Vec::new(),
+            outcome: Some(outcome),
+        });
+        self
+    }
+
+    /// Adds a Withdrawn stage unless there's already one, every stored pipeline has it
+    pub fn with_withdrawal(self) -> Self {
+        if self.withdrawn_stage().is_some() {
+            self
+        } else {
+            self.terminal("Withdrawn", Outcome::Withdrawn)
+        }
+    }
+
+    /// Applied -> Interviewed -> Approved/Rejected, or Withdrawn before that
+    /// Note: The stage positions match the old `Candidacy` values so stored applications keep their state
+    pub fn default_pipeline() -> Self {
+        Self {
+            id: DEFAULT_PIPELINE_ID,
+            ..Self::new("Default")
+                .stage("Applied", &["Interviewed"])
+                .stage("Interviewed", &["Approved", "Rejected"])
+                .terminal("Rejected", Outcome::Rejected)
+                .terminal("Approved", Outcome::Hired)
+                .with_withdrawal()
+        }
+    }
+
+    /// Position of the stage with that name
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.stages.iter().position(|stage| stage.name == name)
+    }
+
+    /// Name of the stage at `position`, `?` if there's no such stage
+    pub fn stage_name(&self, position: usize) -> &str {
+        self.stages
+            .get(position)
+            .map_or("?", |stage| stage.name.as_str())
+    }
+
+    /// Whether an application can move from the stage at `from` to the one at `to`
+    pub fn can_move(&self, from: usize, to: usize) -> bool {
+        match (self.stages.get(from), self.stages.get(to)) {
+            (Some(from), Some(to)) => from.next.contains(&to.name),
+            _ => false,
+        }
+    }
+
+    /// Position of the stage candidates move to when they withdraw
+    pub fn withdrawn_stage(&self) -> Option<usize> {
+        self.stages
+            .iter()
+            .position(|stage| stage.outcome == Some(Outcome::Withdrawn))
+    }
+
+    /// Position of the first Rejected stage, where applications failing a knockout rule are moved to
+    pub fn rejected_stage(&self) -> Option<usize> {
+        self.stages
+            .iter()
+            .position(|stage| stage.outcome == Some(Outcome::Rejected))
+    }
+
+    /// Stages reachable from the one at `from` in the order they were declared
+    pub fn next_stages(&self, from: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.stages.len()).filter(move |to| self.can_move(from, *to))
+    }
+
+    /// Checks the pipeline can be used:
+    /// it has stages with unique non-empty names, the first one isn't terminal,
+    /// transitions go to existing stages, only terminal stages are dead ends
+    /// and there's at most one Withdrawn stage which no stage moves to
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("The pipeline needs a name".into());
+        }
+        match self.stages.first() {
+            None => return Err("The pipeline has no stages".into()),
+            Some(first) if first.outcome.is_some() => {
+                return Err(format!("The first stage {} can't be terminal", first.name))
+            }
+            _ => (),
+        }
+        for (position, stage) in self.stages.iter().enumerate() {
+            if stage.name.is_empty() {
+                return Err(format!("Stage {} has no name", position));
+            }
+            if self.position(&stage.name) != Some(position) {
+                return Err(format!("There's more than one stage named {}", stage.name));
+            }
+            if let Some(next) = stage.next.iter().find(|next| self.position(next).is_none()) {
+                return Err(format!(
+                    "Stage {} moves to {}, which doesn't exist",
+                    stage.name, next
+                ));
+            }
+            if stage.outcome == Some(Outcome::Withdrawn) {
+                if self.withdrawn_stage() != Some(position) {
+                    return Err("There's more than one Withdrawn stage".into());
+                }
+                if let Some(from) = self
+                    .stages
+                    .iter()
+                    .find(|from| from.next.contains(&stage.name))
+                {
+                    return Err(format!(
+                        "Stage {} can't move to {}, only candidates withdraw",
+                        from.name, stage.name
+                    ));
+                }
+            }
+            match (stage.outcome, stage.next.is_empty()) {
+                (Some(_), false) => {
+                    return Err(format!("Terminal stage {} can't move on", stage.name))
+                }
+                (None, true) => {
+                    return Err(format!(
+                        "Stage {} is a dead end but isn't terminal",
+                        stage.name
+                    ))
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
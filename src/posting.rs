@@ -0,0 +1,228 @@
+//! Job posting details
+//! What a posting tells candidates besides the job's name: a description, the department,
+//! where and how the job is done, its seniority and what it pays.
+use crate::offer::MAX_SALARY;
+
+/// Longest description a posting can have, in characters
+pub const MAX_DESCRIPTION_LEN: usize = 10_000;
+
+/// Where the job is done
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Workplace {
+    #[default]
+    OnSite = 0,
+    Hybrid = 1,
+    Remote = 2,
+}
+
+impl std::convert::TryFrom<u8> for Workplace {
+    type Error = u8;
+
+    fn try_from(i: u8) -> Result<Self, Self::Error> {
+        match i {
+            0 => Ok(Self::OnSite),
+            1 => Ok(Self::Hybrid),
+            2 => Ok(Self::Remote),
+            i => Err(i),
+        }
+    }
+}
+
+impl std::fmt::Display for Workplace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OnSite => write!(f, "On site"),
+            Self::Hybrid => write!(f, "Hybrid"),
+            Self::Remote => write!(f, "Remote"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum EmploymentType {
+    #[default]
+    FullTime = 0,
+    PartTime = 1,
+    Contract = 2,
+    Temporary = 3,
+    Internship = 4,
+}
+
+impl std::convert::TryFrom<u8> for EmploymentType {
+    type Error = u8;
+
+    fn try_from(i: u8) -> Result<Self, Self::Error> {
+        match i {
+            0 => Ok(Self::FullTime),
+            1 => Ok(Self::PartTime),
+            2 => Ok(Self::Contract),
+            3 => Ok(Self::Temporary),
+            4 => Ok(Self::Internship),
+            i => Err(i),
+        }
+    }
+}
+
+impl std::fmt::Display for EmploymentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FullTime => write!(f, "Full time"),
+            Self::PartTime => write!(f, "Part time"),
+            Self::Contract => write!(f, "Contract"),
+            Self::Temporary => write!(f, "Temporary"),
+            Self::Internship => write!(f, "Internship"),
+        }
+    }
+}
+
+/// Experience level the job is for, ordered from the least to the most senior
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub enum Seniority {
+    Junior = 0,
+    Mid = 1,
+    Senior = 2,
+    Lead = 3,
+    Principal = 4,
+}
+
+impl std::convert::TryFrom<u8> for Seniority {
+    type Error = u8;
+
+    fn try_from(i: u8) -> Result<Self, Self::Error> {
+        match i {
+            0 => Ok(Self::Junior),
+            1 => Ok(Self::Mid),
+            2 => Ok(Self::Senior),
+            3 => Ok(Self::Lead),
+            4 => Ok(Self::Principal),
+            i => Err(i),
+        }
+    }
+}
+
+impl std::fmt::Display for Seniority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Junior => write!(f, "Junior"),
+            Self::Mid => write!(f, "Mid"),
+            Self::Senior => write!(f, "Senior"),
+            Self::Lead => write!(f, "Lead"),
+            Self::Principal => write!(f, "Principal"),
+        }
+    }
+}
+
+/// Yearly salary range, `currency` is an ISO 4217 code like USD
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SalaryRange {
+    pub min: u64,
+    pub max: u64,
+    pub currency: String,
+}
+
+impl std::fmt::Display for SalaryRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} - {} {}", self.min, self.max, self.currency)
+    }
+}
+
+/// Details of a job posting, empty strings and `None`s are details the posting doesn't give
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct JobDetails {
+    pub description: String,
+    pub department: String,
+    pub location: String,
+    pub workplace: Workplace,
+    pub employment_type: EmploymentType,
+    pub seniority: Option<Seniority>,
+    pub salary: Option<SalaryRange>,
+}
+
+impl JobDetails {
+    /// Full time on site details with just a description
+    pub fn new(description: &str) -> Self {
+        Self {
+            description: description.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn department(mut self, department: &str) -> Self {
+        self.department = department.into();
+        self
+    }
+
+    pub fn location(mut self, location: &str, workplace: Workplace) -> Self {
+        self.location = location.into();
+        self.workplace = workplace;
+        self
+    }
+
+    pub fn employment_type(mut self, employment_type: EmploymentType) -> Self {
+        self.employment_type = employment_type;
+        self
+    }
+
+    pub fn seniority(mut self, seniority: Seniority) -> Self {
+        self.seniority = Some(seniority);
+        self
+    }
+
+    pub fn salary(mut self, min: u64, max: u64, currency: &str) -> Self {
+        self.salary = Some(SalaryRange {
+            min,
+            max,
+            currency: currency.into(),
+        });
+        self
+    }
+
+    /// Checks the description isn't too long and the salary range is a valid range with a currency code
+    pub fn validate(&self) -> Result<(), String> {
+        if self.description.chars().count() > MAX_DESCRIPTION_LEN {
+            return Err(format!(
+                "The description can't be longer than {} characters",
+                MAX_DESCRIPTION_LEN
+            ));
+        }
+        if let Some(salary) = &self.salary {
+            if salary.min > salary.max {
+                return Err(format!("The salary range {} ends before it starts", salary));
+            }
+            if salary.max > MAX_SALARY {
+                return Err(format!("Salaries can't be more than {}", MAX_SALARY));
+            }
+            if salary.currency.len() != 3
+                || !salary.currency.chars().all(|c| c.is_ascii_uppercase())
+            {
+                return Err(format!(
+                    "{} isn't a currency code, it should be like USD",
+                    salary.currency
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for JobDetails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, {}", self.employment_type, self.workplace)?;
+        if !self.location.is_empty() {
+            write!(f, " in {}", self.location)?;
+        }
+        if let Some(seniority) = self.seniority {
+            write!(f, ", {}", seniority)?;
+        }
+        if !self.department.is_empty() {
+            write!(f, ", {} department", self.department)?;
+        }
+        if let Some(salary) = &self.salary {
+            write!(f, ", Salary: {}", salary)?;
+        }
+        if !self.description.is_empty() {
+            write!(f, "\n {}", self.description)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+//! Candidate profiles
+//! Contact details, skills with their proficiency, work history and education of a candidate.
+//! Candidates edit their own profile, recruiters see it when reviewing applications.
+use crate::{Id, Timestamp};
+
+/// How well a candidate knows a skill, ordered from the least to the most
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub enum Proficiency {
+    Beginner = 0,
+    Intermediate = 1,
+    Advanced = 2,
+    Expert = 3,
+}
+
+impl std::convert::TryFrom<u8> for Proficiency {
+    type Error = u8;
+
+    fn try_from(i: u8) -> Result<Self, Self::Error> {
+        match i {
+            0 => Ok(Self::Beginner),
+            1 => Ok(Self::Intermediate),
+            2 => Ok(Self::Advanced),
+            3 => Ok(Self::Expert),
+            i => Err(i),
+        }
+    }
+}
+
+impl std::fmt::Display for Proficiency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Beginner => write!(f, "Beginner"),
+            Self::Intermediate => write!(f, "Intermediate"),
+            Self::Advanced => write!(f, "Advanced"),
+            Self::Expert => write!(f, "Expert"),
+        }
+    }
+}
+
+/// A month of a year, the precision résumés give dates with
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+pub struct YearMonth {
+    pub year: u16,
+    /// From 1(January) to 12
+    pub month: u8,
+}
+
+impl YearMonth {
+    pub fn new(year: u16, month: u8) -> Self {
+        Self { year, month }
+    }
+
+    pub fn is_valid(self) -> bool {
+        (1..=12).contains(&self.month)
+    }
+
+    /// The month `timestamp` falls in(UTC)
+    pub fn from_timestamp(timestamp: Timestamp) -> Self {
+        // Civil from days, see http://howardhinnant.github.io/date_algorithms.html
+        let days = timestamp.div_euclid(86_400) + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // Months counted from March, so the leap day is the last one of the year
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        Self::new(year as u16, month as u8)
+    }
+
+    /// Months since year 0, to count the months between two of them
+    pub(crate) fn index(self) -> i64 {
+        i64::from(self.year) * 12 + i64::from(self.month) - 1
+    }
+}
+
+impl std::fmt::Display for YearMonth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{:02}", self.year, self.month)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Skill {
+    pub name: String,
+    pub proficiency: Proficiency,
+}
+
+/// A job in the candidate's work history, `end` isn't set for the current one
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WorkExperience {
+    pub company: String,
+    pub title: String,
+    pub start: YearMonth,
+    pub end: Option<YearMonth>,
+    pub description: String,
+}
+
+/// A degree of the candidate, `end` isn't set while studying
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Education {
+    pub institution: String,
+    pub degree: String,
+    pub start: YearMonth,
+    pub end: Option<YearMonth>,
+}
+
+/// Profile of the candidate with `candidate_id`, empty strings are details it doesn't give
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Profile {
+    pub candidate_id: Id,
+    pub full_name: String,
+    pub email: String,
+    pub phone: String,
+    pub location: String,
+    pub skills: Vec<Skill>,
+    /// Work history in the order the candidate gave it
+    pub experience: Vec<WorkExperience>,
+    pub education: Vec<Education>,
+}
+
+impl Profile {
+    /// A profile with just a name, the system fills the candidate ID when it's saved
+    pub fn new(full_name: &str) -> Self {
+        Self {
+            full_name: full_name.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn email(mut self, email: &str) -> Self {
+        self.email = email.into();
+        self
+    }
+
+    pub fn phone(mut self, phone: &str) -> Self {
+        self.phone = phone.into();
+        self
+    }
+
+    pub fn location(mut self, location: &str) -> Self {
+        self.location = location.into();
+        self
+    }
+
+    pub fn skill(mut self, name: &str, proficiency: Proficiency) -> Self {
+        self.skills.push(Skill {
+            name: name.into(),
+            proficiency,
+        });
+        self
+    }
+
+    pub fn experience(
+        mut self,
+        company: &str,
+        title: &str,
+        start: YearMonth,
+        end: Option<YearMonth>,
+    ) -> Self {
+        self.experience.push(WorkExperience {
+            company: company.into(),
+            title: title.into(),
+            start,
+            end,
+            description: String::new(),
+        });
+        self
+    }
+
+    pub fn education(
+        mut self,
+        institution: &str,
+        degree: &str,
+        start: YearMonth,
+        end: Option<YearMonth>,
+    ) -> Self {
+        self.education.push(Education {
+            institution: institution.into(),
+            degree: degree.into(),
+            start,
+            end,
+        });
+        self
+    }
+
+    /// Checks the contact details look right, skills are named and unique, and every period ends after it starts
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.email.is_empty() && !is_email(&self.email) {
+            return Err(format!("{} isn't an email address", self.email));
+        }
+        if !self.phone.is_empty() && !is_phone(&self.phone) {
+            return Err(format!("{} isn't a phone number", self.phone));
+        }
+        for (i, skill) in self.skills.iter().enumerate() {
+            if skill.name.trim().is_empty() {
+                return Err("Skills need a name".into());
+            }
+            if self.skills[..i]
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(&skill.name))
+            {
+                return Err(format!("{} is listed more than once", skill.name));
+            }
+        }
+        for job in self.experience.iter() {
+            if job.company.is_empty() || job.title.is_empty() {
+                return Err("Work experience needs a company and a title".into());
+            }
+            check_period(&job.company, job.start, job.end)?;
+        }
+        for degree in self.education.iter() {
+            if degree.institution.is_empty() || degree.degree.is_empty() {
+                return Err("Education needs an institution and a degree".into());
+            }
+            check_period(&degree.institution, degree.start, degree.end)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn is_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty()
+                && !domain.contains('@')
+                && domain.split('.').count() > 1
+                && domain.split('.').all(|part| !part.is_empty())
+                && !email.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+/// Digits with the usual separators, an optional leading + and at least 6 digits
+pub(crate) fn is_phone(phone: &str) -> bool {
+    let number = phone.strip_prefix('+').unwrap_or(phone);
+    number
+        .chars()
+        .all(|c| c.is_ascii_digit() || " -.()".contains(c))
+        && number.chars().filter(char::is_ascii_digit).count() >= 6
+}
+
+fn check_period(what: &str, start: YearMonth, end: Option<YearMonth>) -> Result<(), String> {
+    if !start.is_valid() || end.is_some_and(|end| !end.is_valid()) {
+        return Err(format!("The dates of {} aren't valid months", what));
+    }
+    if end.is_some_and(|end| end < start) {
+        return Err(format!("The period at {} ends before it starts", what));
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.full_name)?;
+        for detail in [&self.email, &self.phone, &self.location].iter() {
+            if !detail.is_empty() {
+                write!(f, ", {}", detail)?;
+            }
+        }
+        if !self.skills.is_empty() {
+            let skills: Vec<_> = self
+                .skills
+                .iter()
+                .map(|skill| format!("{}({})", skill.name, skill.proficiency))
+                .collect();
+            write!(f, "\n Skills: {}", skills.join(", "))?;
+        }
+        for job in self.experience.iter() {
+            write!(f, "\n {} at {} since {}", job.title, job.company, job.start)?;
+            if let Some(end) = job.end {
+                write!(f, " until {}", end)?;
+            }
+            if !job.description.is_empty() {
+                write!(f, ": {}", job.description)?;
+            }
+        }
+        for degree in self.education.iter() {
+            write!(
+                f,
+                "\n {} at {} since {}",
+                degree.degree, degree.institution, degree.start
+            )?;
+            if let Some(end) = degree.end {
+                write!(f, " until {}", end)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,393 @@
+//! Screening questions
+//! A job posting can ask candidates questions when they apply, answers are validated against them.
+//! Questions with a knockout rule reject the applications whose answer doesn't meet it.
+use std::convert::TryFrom;
+
+/// Most questions a job can ask
+pub const MAX_QUESTIONS: usize = 50;
+/// Longest question, choice or free text answer, in characters
+pub const MAX_QUESTION_LEN: usize = 500;
+pub const MAX_ANSWER_LEN: usize = 5_000;
+/// Rejection reason of applications failing a knockout rule
+pub const KNOCKOUT_REASON: &str = "knockout";
+
+/// What kind of answer a question takes
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum QuestionKind {
+    FreeText = 0,
+    YesNo = 1,
+    SingleChoice = 2,
+    MultipleChoice = 3,
+    Numeric = 4,
+}
+
+impl QuestionKind {
+    fn has_choices(self) -> bool {
+        matches!(self, Self::SingleChoice | Self::MultipleChoice)
+    }
+}
+
+impl TryFrom<u8> for QuestionKind {
+    type Error = u8;
+
+    fn try_from(i: u8) -> Result<Self, Self::Error> {
+        match i {
+            0 => Ok(Self::FreeText),
+            1 => Ok(Self::YesNo),
+            2 => Ok(Self::SingleChoice),
+            3 => Ok(Self::MultipleChoice),
+            4 => Ok(Self::Numeric),
+            i => Err(i),
+        }
+    }
+}
+
+impl std::fmt::Display for QuestionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FreeText => write!(f, "Free text"),
+            Self::YesNo => write!(f, "Yes/No"),
+            Self::SingleChoice => write!(f, "Single choice"),
+            Self::MultipleChoice => write!(f, "Multiple choice"),
+            Self::Numeric => write!(f, "Numeric"),
+        }
+    }
+}
+
+/// What an answer needs to keep the application going
+/// - Requires: A yes/no answer that must be given
+/// - AnyOf: Choices of which at least one must be picked
+/// - AllOf: Choices that must all be picked, only for multiple choice questions
+/// - Range: Bounds of a numeric answer, inclusive, `None` doesn't bound that end
+#[derive(Debug, PartialEq, Clone)]
+pub enum Knockout {
+    Requires(bool),
+    AnyOf(Vec<String>),
+    AllOf(Vec<String>),
+    Range { min: Option<f64>, max: Option<f64> },
+}
+
+impl std::fmt::Display for Knockout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Requires(true) => write!(f, "must be yes"),
+            Self::Requires(false) => write!(f, "must be no"),
+            Self::AnyOf(choices) => write!(f, "must pick one of {}", choices.join(", ")),
+            Self::AllOf(choices) => write!(f, "must pick all of {}", choices.join(", ")),
+            Self::Range { min, max } => match (min, max) {
+                (Some(min), Some(max)) => write!(f, "must be between {} and {}", min, max),
+                (Some(min), None) => write!(f, "must be at least {}", min),
+                (None, Some(max)) => write!(f, "must be at most {}", max),
+                (None, None) => write!(f, "any number"),
+            },
+        }
+    }
+}
+
+/// A screening question, `choices` are only for single and multiple choice questions
+#[derive(Debug, PartialEq, Clone)]
+pub struct Question {
+    pub text: String,
+    pub kind: QuestionKind,
+    pub choices: Vec<String>,
+    /// Whether applications have to answer it
+    pub required: bool,
+    pub knockout: Option<Knockout>,
+}
+
+impl Question {
+    fn new(text: &str, kind: QuestionKind, choices: &[&str]) -> Self {
+        Self {
+            text: text.into(),
+            kind,
+            choices: choices.iter().map(|&choice| choice.into()).collect(),
+            required: false,
+            knockout: None,
+        }
+    }
+
+    pub fn free_text(text: &str) -> Self {
+        Self::new(text, QuestionKind::FreeText, &[])
+    }
+
+    pub fn yes_no(text: &str) -> Self {
+        Self::new(text, QuestionKind::YesNo, &[])
+    }
+
+    pub fn single_choice(text: &str, choices: &[&str]) -> Self {
+        Self::new(text, QuestionKind::SingleChoice, choices)
+    }
+
+    pub fn multiple_choice(text: &str, choices: &[&str]) -> Self {
+        Self::new(text, QuestionKind::MultipleChoice, choices)
+    }
+
+    pub fn numeric(text: &str) -> Self {
+        Self::new(text, QuestionKind::Numeric, &[])
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Rejects applications whose answer doesn't meet `knockout`, which makes the question required
+    pub fn knockout(mut self, knockout: Knockout) -> Self {
+        self.required = true;
+        self.knockout = Some(knockout);
+        self
+    }
+
+    /// Checks the question has a text, choices only if it's a choice question(at least 2, unique and non-empty)
+    /// and a knockout rule that fits its kind and is only on required questions
+    pub fn validate(&self) -> Result<(), String> {
+        if self.text.trim().is_empty() {
+            return Err("The question has no text".into());
+        }
+        if self.text.chars().count() > MAX_QUESTION_LEN {
+            return Err(format!(
+                "The question can't be longer than {} characters",
+                MAX_QUESTION_LEN
+            ));
+        }
+        if !self.kind.has_choices() && !self.choices.is_empty() {
+            return Err(format!("{} questions don't have choices", self.kind));
+        }
+        if self.kind.has_choices() && self.choices.len() < 2 {
+            return Err("The question needs at least 2 choices".into());
+        }
+        for (i, choice) in self.choices.iter().enumerate() {
+            if choice.trim().is_empty() || choice.contains('\n') {
+                return Err("Choices can't be empty or span lines".into());
+            }
+            if choice.chars().count() > MAX_QUESTION_LEN {
+                return Err(format!(
+                    "Choices can't be longer than {} characters",
+                    MAX_QUESTION_LEN
+                ));
+            }
+            if self.choices[..i].contains(choice) {
+                return Err(format!("{} is a choice more than once", choice));
+            }
+        }
+        let knockout = match &self.knockout {
+            None => return Ok(()),
+            Some(knockout) => knockout,
+        };
+        if !self.required {
+            return Err("Only required questions can have a knockout rule".into());
+        }
+        let fits = match knockout {
+            Knockout::Requires(_) => self.kind == QuestionKind::YesNo,
+            Knockout::AnyOf(_) => self.kind.has_choices(),
+            Knockout::AllOf(_) => self.kind == QuestionKind::MultipleChoice,
+            Knockout::Range { .. } => self.kind == QuestionKind::Numeric,
+        };
+        if !fits {
+            return Err(format!(
+                "The knockout rule({}) doesn't fit a {} question",
+                knockout, self.kind
+            ));
+        }
+        match knockout {
+            Knockout::AnyOf(choices) | Knockout::AllOf(choices) => {
+                if choices.is_empty() {
+                    return Err("The knockout rule needs choices".into());
+                }
+                if let Some(choice) = choices.iter().find(|choice| !self.choices.contains(choice)) {
+                    return Err(format!("{} isn't one of the choices", choice));
+                }
+            }
+            Knockout::Range { min, max } => {
+                if min.iter().chain(max.iter()).any(|bound| !bound.is_finite()) {
+                    return Err("The knockout range needs finite bounds".into());
+                }
+                match (min, max) {
+                    (None, None) => return Err("The knockout range has no bounds".into()),
+                    (Some(min), Some(max)) if min > max => {
+                        return Err("The knockout range ends before it starts".into())
+                    }
+                    _ => (),
+                }
+            }
+            Knockout::Requires(_) => (),
+        }
+        Ok(())
+    }
+
+    /// Checks `answer` is the kind of answer the question takes, with choices from the question's
+    pub fn check(&self, answer: &Answer) -> Result<(), String> {
+        match (self.kind, answer) {
+            (QuestionKind::FreeText, Answer::Text(text)) => {
+                if text.trim().is_empty() {
+                    return Err("The answer is empty".into());
+                }
+                if text.chars().count() > MAX_ANSWER_LEN {
+                    return Err(format!(
+                        "The answer can't be longer than {} characters",
+                        MAX_ANSWER_LEN
+                    ));
+                }
+            }
+            (QuestionKind::YesNo, Answer::YesNo(_)) => (),
+            (QuestionKind::SingleChoice, Answer::Choice(choice)) => {
+                if !self.choices.contains(choice) {
+                    return Err(format!("{} isn't one of the choices", choice));
+                }
+            }
+            (QuestionKind::MultipleChoice, Answer::Choices(choices)) => {
+                if choices.is_empty() {
+                    return Err("No choice was picked".into());
+                }
+                for (i, choice) in choices.iter().enumerate() {
+                    if !self.choices.contains(choice) {
+                        return Err(format!("{} isn't one of the choices", choice));
+                    }
+                    if choices[..i].contains(choice) {
+                        return Err(format!("{} was picked more than once", choice));
+                    }
+                }
+            }
+            (QuestionKind::Numeric, Answer::Number(number)) => {
+                if !number.is_finite() {
+                    return Err("The answer isn't a number".into());
+                }
+            }
+            (kind, _) => return Err(format!("The question takes a {} answer", kind)),
+        }
+        Ok(())
+    }
+
+    /// Whether `answer` meets the knockout rule, always true without one
+    pub fn passes(&self, answer: Option<&Answer>) -> bool {
+        match (&self.knockout, answer) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(Knockout::Requires(expected)), Some(Answer::YesNo(answer))) => expected == answer,
+            (Some(Knockout::AnyOf(accepted)), Some(Answer::Choice(choice))) => {
+                accepted.contains(choice)
+            }
+            (Some(Knockout::AnyOf(accepted)), Some(Answer::Choices(choices))) => {
+                choices.iter().any(|choice| accepted.contains(choice))
+            }
+            (Some(Knockout::AllOf(needed)), Some(Answer::Choices(choices))) => {
+                needed.iter().all(|choice| choices.contains(choice))
+            }
+            (Some(Knockout::Range { min, max }), Some(Answer::Number(number))) => {
+                min.is_none_or(|min| *number >= min) && max.is_none_or(|max| *number <= max)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Question {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}", self.text, self.kind)?;
+        if !self.choices.is_empty() {
+            write!(f, ": {}", self.choices.join(", "))?;
+        }
+        if self.required {
+            write!(f, ", required")?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Answer to a question, it has to be of the question's kind
+#[derive(Debug, PartialEq, Clone)]
+pub enum Answer {
+    Text(String),
+    YesNo(bool),
+    Choice(String),
+    Choices(Vec<String>),
+    Number(f64),
+}
+
+impl Answer {
+    /// Kind of question the answer is for
+    pub fn kind(&self) -> QuestionKind {
+        match self {
+            Self::Text(_) => QuestionKind::FreeText,
+            Self::YesNo(_) => QuestionKind::YesNo,
+            Self::Choice(_) => QuestionKind::SingleChoice,
+            Self::Choices(_) => QuestionKind::MultipleChoice,
+            Self::Number(_) => QuestionKind::Numeric,
+        }
+    }
+}
+
+impl std::fmt::Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text(text) | Self::Choice(text) => write!(f, "{}", text),
+            Self::YesNo(true) => write!(f, "Yes"),
+            Self::YesNo(false) => write!(f, "No"),
+            Self::Choices(choices) => write!(f, "{}", choices.join(", ")),
+            Self::Number(number) => write!(f, "{}", number),
+        }
+    }
+}
+
+/// Checks there aren't too many questions, they're valid and each one is asked once
+pub fn validate_questionnaire(questions: &[Question]) -> Result<(), String> {
+    if questions.len() > MAX_QUESTIONS {
+        return Err(format!(
+            "A job can't ask more than {} questions",
+            MAX_QUESTIONS
+        ));
+    }
+    for (position, question) in questions.iter().enumerate() {
+        question
+            .validate()
+            .map_err(|e| format!("Question {}: {}", position, e))?;
+        if questions[..position]
+            .iter()
+            .any(|other| other.text == question.text)
+        {
+            return Err(format!("{} is asked more than once", question.text));
+        }
+    }
+    Ok(())
+}
+
+/// Checks `answers`, pairs of a question's position and its answer, answer each question at most once,
+/// the required ones included, with answers the questions take
+pub fn check_answers(questions: &[Question], answers: &[(usize, Answer)]) -> Result<(), String> {
+    for (i, (position, answer)) in answers.iter().enumerate() {
+        let question = questions
+            .get(*position)
+            .ok_or_else(|| format!("There's no question {}", position))?;
+        if answers[..i].iter().any(|(other, _)| other == position) {
+            return Err(format!("{} is answered more than once", question.text));
+        }
+        question
+            .check(answer)
+            .map_err(|e| format!("{}: {}", question.text, e))?;
+    }
+    match questions.iter().enumerate().find(|(position, question)| {
+        question.required && !answers.iter().any(|(answered, _)| answered == position)
+    }) {
+        Some((_, question)) => Err(format!("{} must be answered", question.text)),
+        None => Ok(()),
+    }
+}
+
+/// Questions whose knockout rule `answers` don't meet
+pub fn knocked_out<'a>(
+    questions: &'a [Question],
+    answers: &[(usize, Answer)],
+) -> Vec<&'a Question> {
+    questions
+        .iter()
+        .enumerate()
+        .filter(|(position, question)| {
+            let answer = answers
+                .iter()
+                .find(|(answered, _)| answered == position)
+                .map(|(_, answer)| answer);
+            !question.passes(answer)
+        })
+        .map(|(_, question)| question)
+        .collect()
+}
@@ -0,0 +1,98 @@
+//! Reports over the applications of a job and of what the maintenance routine changed
+//! Counts the applications per stage and outcome, and the rejections per reason.
+use crate::pipeline::{Outcome, Pipeline};
+use crate::{Id, Job};
+use std::collections::BTreeMap;
+
+/// How the applications to a job are going
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct JobReport {
+    pub job_id: Id,
+    pub applications: usize,
+    /// Applications per stage in the pipeline's order, stages without applications included
+    pub stages: Vec<(String, usize)>,
+    /// Applications that haven't reached a terminal stage
+    pub active: usize,
+    pub hired: usize,
+    pub rejected: usize,
+    pub withdrawn: usize,
+    /// Rejected applications per reason code
+    pub rejection_reasons: BTreeMap<String, usize>,
+}
+
+impl JobReport {
+    /// `job` must come with its applicants, `pipeline` is the one it follows
+    pub fn new(job: &Job, pipeline: &Pipeline) -> Self {
+        let mut stages: Vec<(String, usize)> = pipeline
+            .stages
+            .iter()
+            .map(|stage| (stage.name.clone(), 0))
+            .collect();
+        let mut report = Self {
+            job_id: job.id,
+            applications: job.applicants.len(),
+            ..Self::default()
+        };
+        for stage in job.applicants.values() {
+            let position = pipeline.position(stage);
+            if let Some(position) = position {
+                stages[position].1 += 1;
+            }
+            match position.and_then(|position| pipeline.stages[position].outcome) {
+                None => report.active += 1,
+                Some(Outcome::Hired) => report.hired += 1,
+                Some(Outcome::Rejected) => report.rejected += 1,
+                Some(Outcome::Withdrawn) => report.withdrawn += 1,
+            }
+        }
+        for rejection in job.rejections.values() {
+            *report
+                .rejection_reasons
+                .entry(rejection.reason.clone())
+                .or_insert(0) += 1;
+        }
+        report.stages = stages;
+        report
+    }
+}
+
+impl std::fmt::Display for JobReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} applications: {} active, {} hired, {} rejected, {} withdrawn",
+            self.applications, self.active, self.hired, self.rejected, self.withdrawn
+        )?;
+        for (stage, count) in self.stages.iter() {
+            write!(f, "\n {}: {}", stage, count)?;
+        }
+        for (reason, count) in self.rejection_reasons.iter() {
+            write!(f, "\n Rejected for {}: {}", reason, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// What `HRSystem::run_maintenance` changed
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct MaintenanceReport {
+    /// Jobs closed because their application window ended, with their name
+    pub closed_jobs: Vec<(Id, String)>,
+    /// Outstanding offers that expired
+    pub expired_offers: usize,
+}
+
+impl std::fmt::Display for MaintenanceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Closed {} jobs, expired {} offers",
+            self.closed_jobs.len(),
+            self.expired_offers
+        )?;
+        for (id, name) in self.closed_jobs.iter() {
+            write!(f, "\n Closed job {}: {}", id, name)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,764 @@
+//! Résumé parsing
+//! Reads plain text or Markdown résumés into a profile draft: the name, email and phone, the skills
+//! found in the skill dictionary, and the periods of the work history and education.
+//! Every finding says how confident the parser is, so the candidate can review the draft before saving it.
+use crate::profile::{self, Education, Proficiency, Profile, Skill, WorkExperience, YearMonth};
+
+/// Skills looked for unless configured otherwise
+/// Note: Words that are common in English(like Go, C or REST) are left out since they'd match almost every résumé
+pub const DEFAULT_SKILL_DICTIONARY: &[&str] = &[
+    "Rust",
+    "C++",
+    "C#",
+    "Golang",
+    "Java",
+    "JavaScript",
+    "TypeScript",
+    "Python",
+    "Ruby",
+    "PHP",
+    "Kotlin",
+    "Swift",
+    "Scala",
+    "Haskell",
+    "SQL",
+    "PostgreSQL",
+    "MySQL",
+    "SQLite",
+    "MongoDB",
+    "Redis",
+    "Docker",
+    "Kubernetes",
+    "AWS",
+    "Azure",
+    "GCP",
+    "Terraform",
+    "Linux",
+    "Git",
+    "React",
+    "Angular",
+    "Vue",
+    "Node.js",
+    "Django",
+    "Rails",
+    "GraphQL",
+    "Machine Learning",
+    "Data Analysis",
+    "Figma",
+    "Photoshop",
+    "Project Management",
+    "Scrum",
+    "Leadership",
+];
+
+/// How sure the parser is about a finding, ordered from the least to the most
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub enum Confidence {
+    Low = 0,
+    Medium = 1,
+    High = 2,
+}
+
+impl Confidence {
+    fn lower(self) -> Self {
+        match self {
+            Self::High => Self::Medium,
+            _ => Self::Low,
+        }
+    }
+}
+
+impl std::fmt::Display for Confidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Low => write!(f, "Low"),
+            Self::Medium => write!(f, "Medium"),
+            Self::High => write!(f, "High"),
+        }
+    }
+}
+
+/// The profile field a finding fills
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ProfileField {
+    FullName,
+    Email,
+    Phone,
+    Skill,
+    Experience,
+    Education,
+}
+
+impl std::fmt::Display for ProfileField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FullName => write!(f, "Full name"),
+            Self::Email => write!(f, "Email"),
+            Self::Phone => write!(f, "Phone"),
+            Self::Skill => write!(f, "Skill"),
+            Self::Experience => write!(f, "Work experience"),
+            Self::Education => write!(f, "Education"),
+        }
+    }
+}
+
+/// Something the parser put in the draft and the line(starting from 1) it came from
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Finding {
+    pub field: ProfileField,
+    pub value: String,
+    pub line: usize,
+    pub confidence: Confidence,
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Line {}: {} {}({} confidence)",
+            self.line, self.field, self.value, self.confidence
+        )
+    }
+}
+
+/// What the parser found and what it couldn't make sense of
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ResumeReport {
+    /// In the order they appear in the résumé, but the name, email and phone go first
+    pub findings: Vec<Finding>,
+    /// Things left out of the draft or missing from the résumé
+    pub warnings: Vec<String>,
+}
+
+impl ResumeReport {
+    /// Findings the candidate should check, those with less than high confidence
+    pub fn to_review(&self) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(|finding| finding.confidence < Confidence::High)
+    }
+}
+
+impl std::fmt::Display for ResumeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Found {} details", self.findings.len())?;
+        for finding in self.findings.iter() {
+            write!(f, "\n {}", finding)?;
+        }
+        for warning in self.warnings.iter() {
+            write!(f, "\n Warning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+/// A profile draft taken from a résumé with its report
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParsedResume {
+    pub profile: Profile,
+    pub report: ResumeReport,
+}
+
+impl ParsedResume {
+    /// `current` with the name, email and phone found replacing its own, and the skills and periods it doesn't have added
+    pub fn merged_with(&self, current: &Profile) -> Profile {
+        let parsed = &self.profile;
+        let or_current = |found: &String, current: &String| {
+            if found.is_empty() {
+                current.clone()
+            } else {
+                found.clone()
+            }
+        };
+        let mut profile = Profile {
+            full_name: or_current(&parsed.full_name, &current.full_name),
+            email: or_current(&parsed.email, &current.email),
+            phone: or_current(&parsed.phone, &current.phone),
+            ..current.clone()
+        };
+        for skill in parsed.skills.iter() {
+            if !profile
+                .skills
+                .iter()
+                .any(|known| known.name.eq_ignore_ascii_case(&skill.name))
+            {
+                profile.skills.push(skill.clone());
+            }
+        }
+        for job in parsed.experience.iter() {
+            if !profile.experience.iter().any(|known| {
+                known.start == job.start && known.company.eq_ignore_ascii_case(&job.company)
+            }) {
+                profile.experience.push(job.clone());
+            }
+        }
+        for degree in parsed.education.iter() {
+            if !profile.education.iter().any(|known| {
+                known.start == degree.start
+                    && known.institution.eq_ignore_ascii_case(&degree.institution)
+            }) {
+                profile.education.push(degree.clone());
+            }
+        }
+        profile
+    }
+}
+
+/// Parses a plain text or Markdown résumé, the skills are the ones of `skill_dictionary` it mentions.
+/// Skills get the proficiency written next to them like `Rust (expert)`, intermediate otherwise.
+/// Periods are taken from date ranges like `2018-03 - 2020-05`, `Mar 2018 to Present` or `2016 - 2018`,
+/// the text around them(or the line before) gives the title and company, or the degree and institution
+pub fn parse_resume(text: &str, skill_dictionary: &[String]) -> ParsedResume {
+    let mut parser = Parser {
+        skill_dictionary: skill_dictionary
+            .iter()
+            .filter(|skill| !skill.is_empty())
+            .map(|skill| (skill.to_lowercase(), skill.as_str()))
+            .collect(),
+        profile: Profile::default(),
+        report: ResumeReport::default(),
+        section: Section::Other,
+        last_text: None,
+        emails: Vec::new(),
+        phones: Vec::new(),
+    };
+    let mut first_line = true;
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let (text, hashes) = strip_markdown(line);
+        if text.is_empty() {
+            continue;
+        }
+        if first_line {
+            first_line = false;
+            if looks_like_name(&text) {
+                parser.profile.full_name = text.clone();
+                parser.report.findings.push(Finding {
+                    field: ProfileField::FullName,
+                    value: text.clone(),
+                    line: line_number,
+                    confidence: Confidence::Medium,
+                });
+                continue;
+            }
+        }
+        parser.line(line_number, &text, hashes);
+    }
+    parser.finish()
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Section {
+    Other,
+    Experience,
+    Education,
+    Skills,
+}
+
+struct Parser<'a> {
+    /// Lowercased skills with the name they're saved with
+    skill_dictionary: Vec<(String, &'a str)>,
+    profile: Profile,
+    report: ResumeReport,
+    section: Section,
+    /// Text of the last line that wasn't a section heading nor had a period, it names periods on their own line
+    last_text: Option<String>,
+    /// Found with their line, only the first one is used
+    emails: Vec<(String, usize)>,
+    /// Found with their line and whether it was labeled as a phone
+    phones: Vec<(String, usize, bool)>,
+}
+
+impl Parser<'_> {
+    fn line(&mut self, line_number: usize, text: &str, hashes: usize) {
+        let mut content = text;
+        // Headings of one or two hashes start a section, and so do short lines in capitals naming one
+        let capitals = !text.chars().any(char::is_lowercase) && section_of(text).is_some();
+        if (hashes == 1 || hashes == 2 || capitals) && text.split_whitespace().count() <= 4 {
+            self.section = section_of(text).unwrap_or(Section::Other);
+            self.last_text = None;
+            return;
+        }
+        // And so do labels like `Skills: Rust, Python`
+        if let Some((label, rest)) = text.split_once(':') {
+            if let Some(section) =
+                section_of(label).filter(|_| label.split_whitespace().count() <= 3)
+            {
+                self.section = section;
+                self.last_text = None;
+                content = rest.trim();
+                if content.is_empty() {
+                    return;
+                }
+            }
+        }
+
+        let mut rest = content.to_string();
+        if let Some((range, span)) = find_date_range(content) {
+            rest.replace_range(span, " ");
+            self.period(line_number, range, trim_separators(&rest));
+        } else {
+            self.last_text = Some(content.to_string());
+        }
+        self.contact_details(line_number, &rest);
+        self.skills(line_number, content);
+    }
+
+    fn period(&mut self, line_number: usize, range: DateRange, text: &str) {
+        let (text, borrowed) = match (text.is_empty(), &self.last_text) {
+            (true, Some(last_text)) => (last_text.clone(), true),
+            _ => (text.to_string(), false),
+        };
+        let (start, end) = range.months();
+        if end.is_some_and(|end| end < start) {
+            self.report.warnings.push(format!(
+                "The period on line {} ends before it starts, it was left out",
+                line_number
+            ));
+            return;
+        }
+        let (what, place) = split_role(&text);
+        let mut confidence = if what.is_empty() || place.is_empty() {
+            Confidence::Low
+        } else if borrowed || !range.month_precision() {
+            Confidence::Medium
+        } else {
+            Confidence::High
+        };
+        if self.section == Section::Other || self.section == Section::Skills {
+            confidence = confidence.lower();
+        }
+        let until = end.map_or("present".to_string(), |end| end.to_string());
+        if self.section == Section::Education {
+            self.report.findings.push(Finding {
+                field: ProfileField::Education,
+                value: format!("{} at {}, {} - {}", what, place, start, until),
+                line: line_number,
+                confidence,
+            });
+            self.profile.education.push(Education {
+                institution: place,
+                degree: what,
+                start,
+                end,
+            });
+        } else {
+            self.report.findings.push(Finding {
+                field: ProfileField::Experience,
+                value: format!("{} at {}, {} - {}", what, place, start, until),
+                line: line_number,
+                confidence,
+            });
+            self.profile.experience.push(WorkExperience {
+                company: place,
+                title: what,
+                start,
+                end,
+                description: String::new(),
+            });
+        }
+    }
+
+    fn contact_details(&mut self, line_number: usize, text: &str) {
+        for token in text.split(|c: char| c.is_whitespace() || "<>()[],;|".contains(c)) {
+            let token = token.trim_start_matches("mailto:").trim_end_matches('.');
+            if profile::is_email(token) && !self.emails.iter().any(|(email, _)| email == token) {
+                self.emails.push((token.to_string(), line_number));
+            }
+        }
+        let lower = text.to_lowercase();
+        let labeled = ["phone", "tel", "mobile", "cell"]
+            .iter()
+            .any(|label| lower.contains(label));
+        let mut run = String::new();
+        // A trailing newline ends the last run
+        for c in text.chars().chain(std::iter::once('\n')) {
+            if c.is_ascii_digit()
+                || (run.is_empty() && "+(".contains(c))
+                || (!run.is_empty() && " -.()".contains(c))
+            {
+                run.push(c);
+                continue;
+            }
+            let phone = run.trim().trim_end_matches(['-', '.', '(']).trim();
+            let digits = phone.chars().filter(char::is_ascii_digit).count();
+            if (7..=15).contains(&digits) && profile::is_phone(phone) {
+                self.phones.push((phone.to_string(), line_number, labeled));
+            }
+            run.clear();
+        }
+    }
+
+    fn skills(&mut self, line_number: usize, text: &str) {
+        let lower = text.to_lowercase();
+        let mut found: Vec<(usize, usize, &str)> = self
+            .skill_dictionary
+            .iter()
+            .filter_map(|(skill, name)| {
+                find_word(&lower, skill).map(|start| (start, start + skill.len(), *name))
+            })
+            .collect();
+        found.sort_unstable();
+        for (_, end, name) in found {
+            if self
+                .profile
+                .skills
+                .iter()
+                .any(|skill| skill.name.eq_ignore_ascii_case(name))
+            {
+                continue;
+            }
+            let proficiency = proficiency_after(&lower[end..]).unwrap_or(Proficiency::Intermediate);
+            self.report.findings.push(Finding {
+                field: ProfileField::Skill,
+                value: format!("{}({})", name, proficiency),
+                line: line_number,
+                confidence: if self.section == Section::Skills {
+                    Confidence::High
+                } else {
+                    Confidence::Medium
+                },
+            });
+            self.profile.skills.push(Skill {
+                name: name.to_string(),
+                proficiency,
+            });
+        }
+    }
+
+    fn finish(mut self) -> ParsedResume {
+        let mut contact = Vec::new();
+        match self.emails.split_first() {
+            Some(((email, line), others)) => {
+                self.profile.email = email.clone();
+                contact.push(Finding {
+                    field: ProfileField::Email,
+                    value: email.clone(),
+                    line: *line,
+                    confidence: if others.is_empty() {
+                        Confidence::High
+                    } else {
+                        Confidence::Medium
+                    },
+                });
+                for (other, line) in others {
+                    self.report.warnings.push(format!(
+                        "The email {} on line {} was left out, only the first one is used",
+                        other, line
+                    ));
+                }
+            }
+            None => self.report.warnings.push("No email found".into()),
+        }
+        // Phones labeled as such go first
+        self.phones.sort_by_key(|(_, _, labeled)| !labeled);
+        match self.phones.split_first() {
+            Some(((phone, line, labeled), others)) => {
+                self.profile.phone = phone.clone();
+                contact.push(Finding {
+                    field: ProfileField::Phone,
+                    value: phone.clone(),
+                    line: *line,
+                    confidence: if *labeled && others.is_empty() {
+                        Confidence::High
+                    } else {
+                        Confidence::Medium
+                    },
+                });
+                for (other, line, _) in others {
+                    self.report.warnings.push(format!(
+                        "The phone {} on line {} was left out, only one is used",
+                        other, line
+                    ));
+                }
+            }
+            None => self.report.warnings.push("No phone found".into()),
+        }
+        if self.profile.full_name.is_empty() {
+            self.report
+                .warnings
+                .push("No full name found, the first line should be it".into());
+        }
+        // The name goes first, then the contact details
+        let name = usize::from(!self.profile.full_name.is_empty());
+        self.report.findings.splice(name..name, contact);
+        ParsedResume {
+            profile: self.profile,
+            report: self.report,
+        }
+    }
+}
+
+/// The text of a Markdown line without its heading, list or emphasis markers, and how many hashes its heading had
+fn strip_markdown(line: &str) -> (String, usize) {
+    let line = line.trim();
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    let mut text = line[hashes..].trim_start();
+    for bullet in ["- ", "* ", "+ ", "> "].iter() {
+        text = text.strip_prefix(bullet).unwrap_or(text);
+    }
+    let text = text.replace("**", "").replace("__", "").replace('`', "");
+    (text.trim().to_string(), hashes)
+}
+
+fn section_of(heading: &str) -> Option<Section> {
+    let heading = heading.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|word| heading.contains(word));
+    if has(&["experience", "employment", "work history", "career"]) {
+        Some(Section::Experience)
+    } else if has(&["education", "studies", "academic"]) {
+        Some(Section::Education)
+    } else if has(&["skill", "technolog", "competenc"]) {
+        Some(Section::Skills)
+    } else {
+        None
+    }
+}
+
+fn looks_like_name(text: &str) -> bool {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    (2..=5).contains(&words.len())
+        && words.iter().all(|word| {
+            word.chars().next().is_some_and(char::is_uppercase)
+                && word.chars().all(|c| c.is_alphabetic() || "-'.".contains(c))
+        })
+}
+
+fn trim_separators(text: &str) -> &str {
+    text.trim_matches(|c: char| c.is_whitespace() || ",|-–—:()[]*".contains(c))
+}
+
+/// Splits `Title at Company` or `Title, Company`(also with `|` or dashes) in its two parts
+fn split_role(text: &str) -> (String, String) {
+    // ASCII lowercasing keeps the byte positions
+    if let Some(i) = text.to_ascii_lowercase().find(" at ") {
+        return (
+            trim_separators(&text[..i]).to_string(),
+            trim_separators(&text[i + 4..]).to_string(),
+        );
+    }
+    for separator in [" @ ", ", ", " | ", " - ", " – ", " — "].iter() {
+        if let Some((what, place)) = text.split_once(separator) {
+            return (
+                trim_separators(what).to_string(),
+                trim_separators(place).to_string(),
+            );
+        }
+    }
+    (trim_separators(text).to_string(), String::new())
+}
+
+/// Position of the first occurrence of `word` in `text` that isn't part of a longer word
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '+' || c == '#';
+    let mut from = 0;
+    while let Some(found) = text[from..].find(word) {
+        let start = from + found;
+        let end = start + word.len();
+        if !text[..start].chars().next_back().is_some_and(is_word)
+            && !text[end..].chars().next().is_some_and(is_word)
+        {
+            return Some(start);
+        }
+        from = start + text[start..].chars().next().map_or(1, char::len_utf8);
+    }
+    None
+}
+
+/// The proficiency in parentheses at the start of `text`, like ` (expert)`
+fn proficiency_after(text: &str) -> Option<Proficiency> {
+    let text = text.trim_start().strip_prefix('(')?;
+    let word = text.split(|c: char| !c.is_alphabetic()).next()?;
+    match word {
+        "beginner" | "basic" => Some(Proficiency::Beginner),
+        "intermediate" => Some(Proficiency::Intermediate),
+        "advanced" => Some(Proficiency::Advanced),
+        "expert" => Some(Proficiency::Expert),
+        _ => None,
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// A date as résumés give it, sometimes without the month
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct PartialDate {
+    year: u16,
+    month: Option<u8>,
+}
+
+/// `end` is `None` for periods up to the present
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct DateRange {
+    start: PartialDate,
+    end: Option<PartialDate>,
+}
+
+impl DateRange {
+    /// Dates without a month start in January and end in December
+    fn months(self) -> (YearMonth, Option<YearMonth>) {
+        (
+            YearMonth::new(self.start.year, self.start.month.unwrap_or(1)),
+            self.end
+                .map(|end| YearMonth::new(end.year, end.month.unwrap_or(12))),
+        )
+    }
+
+    fn month_precision(self) -> bool {
+        self.start.month.is_some() && self.end.is_none_or(|end| end.month.is_some())
+    }
+}
+
+fn leading_digits(text: &str) -> usize {
+    text.find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len())
+}
+
+fn plausible_year(year: &str) -> Option<u16> {
+    year.parse()
+        .ok()
+        .filter(|year| (1950..=2100).contains(year))
+}
+
+/// Parses a date at the start of `text`(`2018-03`, `03/2018`, `Mar 2018`, `March 2018` or `2018`), returns it with its length
+fn parse_date(text: &str) -> Option<(PartialDate, usize)> {
+    let letters = text
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(text.len());
+    if letters >= 3 {
+        let word = text[..letters].to_ascii_lowercase();
+        let month = MONTHS.iter().position(|name| name.starts_with(&word))?;
+        let mut len = letters;
+        len += usize::from(text[len..].starts_with('.'));
+        len += text[len..].len() - text[len..].trim_start().len();
+        if leading_digits(&text[len..]) != 4 {
+            return None;
+        }
+        let year = plausible_year(&text[len..len + 4])?;
+        return Some((
+            PartialDate {
+                year,
+                month: Some(month as u8 + 1),
+            },
+            len + 4,
+        ));
+    }
+    let is_separator = |rest: &str| rest.starts_with(['-', '/', '.']);
+    match leading_digits(text) {
+        4 => {
+            let year = plausible_year(&text[..4])?;
+            let rest = &text[4..];
+            if is_separator(rest) {
+                let digits = leading_digits(&rest[1..]);
+                let month = rest[1..1 + digits]
+                    .parse()
+                    .ok()
+                    .filter(|month| (1..=12).contains(month));
+                if let (1..=2, Some(month)) = (digits, month) {
+                    return Some((
+                        PartialDate {
+                            year,
+                            month: Some(month),
+                        },
+                        5 + digits,
+                    ));
+                }
+            }
+            Some((PartialDate { year, month: None }, 4))
+        }
+        digits @ 1..=2 => {
+            let month: u8 = text[..digits]
+                .parse()
+                .ok()
+                .filter(|month| (1..=12).contains(month))?;
+            let rest = &text[digits..];
+            if !is_separator(rest) || leading_digits(&rest[1..]) != 4 {
+                return None;
+            }
+            let year = plausible_year(&rest[1..5])?;
+            Some((
+                PartialDate {
+                    year,
+                    month: Some(month),
+                },
+                digits + 5,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// The first date range of `text` with the bytes it spans
+fn find_date_range(text: &str) -> Option<(DateRange, std::ops::Range<usize>)> {
+    let ends_word = |rest: &str| !rest.chars().next().is_some_and(char::is_alphanumeric);
+    let skip_spaces = |rest: &str| rest.len() - rest.trim_start().len();
+    for (start, _) in text.char_indices() {
+        if text[..start]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric)
+        {
+            continue;
+        }
+        let (from, len) = match parse_date(&text[start..]) {
+            Some(date) => date,
+            None => continue,
+        };
+        let mut end = start + len;
+        if !ends_word(&text[end..]) {
+            continue;
+        }
+        end += skip_spaces(&text[end..]);
+        let separator = ["--", "-", "–", "—", "to ", "until ", "till ", "through "]
+            .iter()
+            .find(|separator| {
+                text.get(end..end + separator.len())
+                    .is_some_and(|found| found.eq_ignore_ascii_case(separator))
+            });
+        let separator = match separator {
+            Some(separator) => separator,
+            None => continue,
+        };
+        end += separator.len();
+        end += skip_spaces(&text[end..]);
+        let rest = &text[end..];
+        if let Some((to, len)) = parse_date(rest).filter(|(_, len)| ends_word(&rest[*len..])) {
+            return Some((
+                DateRange {
+                    start: from,
+                    end: Some(to),
+                },
+                start..end + len,
+            ));
+        }
+        let word = rest
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(rest.len());
+        if ["present", "current", "now", "today", "ongoing"]
+            .iter()
+            .any(|present| rest[..word].eq_ignore_ascii_case(present))
+        {
+            return Some((
+                DateRange {
+                    start: from,
+                    end: None,
+                },
+                start..end + word,
+            ));
+        }
+    }
+    None
+}
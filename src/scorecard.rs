@@ -0,0 +1,213 @@
+//! Interview scorecards
+//! Interviewers rate an application per competency and give an overall recommendation with notes.
+//! The scorecards of an application are aggregated in a `ScorecardSummary`.
+use crate::{Id, Timestamp};
+use std::collections::BTreeMap;
+
+/// Lowest and highest score of a competency rating
+pub const MIN_SCORE: u8 = 1;
+pub const MAX_SCORE: u8 = 5;
+
+/// Overall recommendation of an interviewer
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub enum Recommendation {
+    StrongNo = 0,
+    No = 1,
+    Yes = 2,
+    StrongYes = 3,
+}
+
+impl Recommendation {
+    /// Weight of the recommendation when averaging them, from -2(strong no) to 2(strong yes)
+    pub fn weight(self) -> i8 {
+        match self {
+            Self::StrongNo => -2,
+            Self::No => -1,
+            Self::Yes => 1,
+            Self::StrongYes => 2,
+        }
+    }
+}
+
+impl std::convert::TryFrom<u8> for Recommendation {
+    type Error = u8;
+
+    fn try_from(i: u8) -> Result<Self, Self::Error> {
+        match i {
+            0 => Ok(Self::StrongNo),
+            1 => Ok(Self::No),
+            2 => Ok(Self::Yes),
+            3 => Ok(Self::StrongYes),
+            i => Err(i),
+        }
+    }
+}
+
+impl std::fmt::Display for Recommendation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StrongNo => write!(f, "Strong no"),
+            Self::No => write!(f, "No"),
+            Self::Yes => write!(f, "Yes"),
+            Self::StrongYes => write!(f, "Strong yes"),
+        }
+    }
+}
+
+/// Score of a competency between `MIN_SCORE` and `MAX_SCORE`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Rating {
+    pub competency: String,
+    pub score: u8,
+}
+
+/// Feedback of an interviewer for the application of `candidate_id` to `job_id`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Scorecard {
+    pub id: Id,
+    pub job_id: Id,
+    pub candidate_id: Id,
+    pub interviewer_id: Id,
+    /// The interview round it's about, if any
+    pub interview_id: Option<Id>,
+    pub ratings: Vec<Rating>,
+    pub recommendation: Recommendation,
+    pub notes: String,
+    pub submitted_at: Timestamp,
+}
+
+impl Scorecard {
+    /// A scorecard without ratings or notes, the system fills the IDs and time when submitting it
+    pub fn new(recommendation: Recommendation) -> Self {
+        Self {
+            id: Default::default(),
+            job_id: Default::default(),
+            candidate_id: Default::default(),
+            interviewer_id: Default::default(),
+            interview_id: None,
+            ratings: Vec::new(),
+            recommendation,
+            notes: String::new(),
+            submitted_at: Default::default(),
+        }
+    }
+
+    pub fn rating(mut self, competency: &str, score: u8) -> Self {
+        self.ratings.push(Rating {
+            competency: competency.into(),
+            score,
+        });
+        self
+    }
+
+    pub fn notes(mut self, notes: &str) -> Self {
+        self.notes = notes.into();
+        self
+    }
+
+    /// The interview round the scorecard is about
+    pub fn interview(mut self, interview_id: Id) -> Self {
+        self.interview_id = Some(interview_id);
+        self
+    }
+
+    /// Checks the ratings are in range and there's at most one per competency
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, rating) in self.ratings.iter().enumerate() {
+            if rating.competency.is_empty() {
+                return Err("Ratings need a competency".into());
+            }
+            if !(MIN_SCORE..=MAX_SCORE).contains(&rating.score) {
+                return Err(format!(
+                    "The score of {} must be between {} and {}",
+                    rating.competency, MIN_SCORE, MAX_SCORE
+                ));
+            }
+            if self.ratings[..i]
+                .iter()
+                .any(|other| other.competency == rating.competency)
+            {
+                return Err(format!("{} is rated more than once", rating.competency));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Average score of a competency across scorecards
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompetencySummary {
+    pub competency: String,
+    pub average: f64,
+    pub ratings: usize,
+}
+
+/// Aggregation of the scorecards of an application
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ScorecardSummary {
+    pub scorecards: usize,
+    /// How many scorecards gave each recommendation
+    pub recommendations: BTreeMap<Recommendation, usize>,
+    /// Average `Recommendation::weight`, `None` without scorecards
+    pub average_recommendation: Option<f64>,
+    /// Ordered by competency name
+    pub competencies: Vec<CompetencySummary>,
+}
+
+impl ScorecardSummary {
+    pub fn new(scorecards: &[Scorecard]) -> Self {
+        let mut recommendations = BTreeMap::new();
+        let mut competencies = BTreeMap::<&str, (u32, usize)>::new();
+        for scorecard in scorecards {
+            *recommendations.entry(scorecard.recommendation).or_insert(0) += 1;
+            for rating in scorecard.ratings.iter() {
+                let (total, count) = competencies.entry(&rating.competency).or_insert((0, 0));
+                *total += u32::from(rating.score);
+                *count += 1;
+            }
+        }
+        Self {
+            scorecards: scorecards.len(),
+            recommendations,
+            average_recommendation: if scorecards.is_empty() {
+                None
+            } else {
+                Some(
+                    scorecards
+                        .iter()
+                        .map(|scorecard| f64::from(scorecard.recommendation.weight()))
+                        .sum::<f64>()
+                        / scorecards.len() as f64,
+                )
+            },
+            competencies: competencies
+                .into_iter()
+                .map(|(competency, (total, count))| CompetencySummary {
+                    competency: competency.into(),
+                    average: f64::from(total) / count as f64,
+                    ratings: count,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for ScorecardSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} scorecards", self.scorecards)?;
+        if let Some(average) = self.average_recommendation {
+            write!(f, ", average recommendation {:.2}(-2 to 2)", average)?;
+        }
+        for (recommendation, count) in self.recommendations.iter() {
+            write!(f, "\n {}: {}", recommendation, count)?;
+        }
+        for competency in self.competencies.iter() {
+            write!(
+                f,
+                "\n {}: {:.2} from {} ratings",
+                competency.competency, competency.average, competency.ratings
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+//! Full-text search over jobs and candidate profiles
+//! Queries are split into words, every word has to appear in what's found as the start of a word,
+//! so `eng` finds "Engineer". Words are made of letters and digits and compared ignoring case.
+//! The SQLite store uses FTS5, which also ignores diacritics, other stores use `relevance`.
+use crate::profile::Profile;
+use crate::{Job, JobState, Timestamp};
+
+/// Which jobs `Store::search_jobs` returns, unset filters match every job
+#[derive(Debug, Default, Clone)]
+pub struct JobSearch {
+    /// Words to look for in the name, description, department and location, empty matches every job
+    pub query: String,
+    pub state: Option<JobState>,
+    /// Whole department name, ignoring case
+    pub department: Option<String>,
+    /// Part of the location, ignoring case
+    pub location: Option<String>,
+    /// Jobs posted at or after it
+    pub posted_after: Option<Timestamp>,
+    /// Jobs posted before it
+    pub posted_before: Option<Timestamp>,
+}
+
+impl JobSearch {
+    pub fn new(query: &str) -> Self {
+        Self {
+            query: query.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether the job passes the filters, the query isn't checked
+    pub fn matches(&self, job: &Job) -> bool {
+        self.state.is_none_or(|state| job.state == state)
+            && self.department.as_ref().is_none_or(|department| {
+                department
+                    .trim()
+                    .eq_ignore_ascii_case(job.details.department.trim())
+            })
+            && self
+                .location
+                .as_ref()
+                .is_none_or(|location| contains_ignoring_case(&job.details.location, location))
+            && self
+                .posted_after
+                .is_none_or(|after| job.created_at >= after)
+            && self
+                .posted_before
+                .is_none_or(|before| job.created_at < before)
+    }
+
+    /// Text the query is matched against
+    pub(crate) fn fields(job: &Job) -> [&str; 4] {
+        [
+            &job.name,
+            &job.details.description,
+            &job.details.department,
+            &job.details.location,
+        ]
+    }
+}
+
+/// Which candidates `Store::search_candidates` returns, unset filters match every candidate with a profile
+#[derive(Debug, Default, Clone)]
+pub struct CandidateSearch {
+    /// Words to look for in the name, location, skills, work history and education, empty matches every profile
+    pub query: String,
+    /// Part of the location, ignoring case
+    pub location: Option<String>,
+}
+
+impl CandidateSearch {
+    pub fn new(query: &str) -> Self {
+        Self {
+            query: query.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether the profile passes the filters, the query isn't checked
+    pub fn matches(&self, profile: &Profile) -> bool {
+        self.location
+            .as_ref()
+            .is_none_or(|location| contains_ignoring_case(&profile.location, location))
+    }
+
+    /// Text the query is matched against
+    pub(crate) fn fields(profile: &Profile) -> Vec<&str> {
+        let mut fields = vec![profile.full_name.as_str(), &profile.location];
+        fields.extend(profile.skills.iter().map(|skill| skill.name.as_str()));
+        for job in profile.experience.iter() {
+            fields.extend([job.title.as_str(), &job.company, &job.description].iter());
+        }
+        for degree in profile.education.iter() {
+            fields.extend([degree.degree.as_str(), &degree.institution].iter());
+        }
+        fields
+    }
+}
+
+// Like SQLite's `lower` only ASCII letters are folded
+fn contains_ignoring_case(text: &str, part: &str) -> bool {
+    text.to_ascii_lowercase()
+        .contains(part.trim().to_ascii_lowercase().as_str())
+}
+
+/// Lowercased words of `text`
+pub fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// How many times the words of `query` start a word of `fields`, `None` if one of them doesn't.
+/// An empty query matches anything with a relevance of 0
+pub fn relevance(query: &[String], fields: &[&str]) -> Option<usize> {
+    let text: Vec<String> = fields.iter().flat_map(|field| words(field)).collect();
+    query.iter().try_fold(0, |total, word| {
+        match text
+            .iter()
+            .filter(|found| found.starts_with(word.as_str()))
+            .count()
+        {
+            0 => None,
+            count => Some(total + count),
+        }
+    })
+}
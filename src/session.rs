@@ -0,0 +1,40 @@
+//! Login sessions
+//! Every login creates a session with a random ID stored in the store, the token handed to the user
+//! is the HMAC of that ID with the system's key. Sessions expire and can be revoked(logout).
+use crate::{Id, Timestamp};
+
+/// Default lifetime of a session in seconds(8 hours)
+pub const DEFAULT_SESSION_TTL: i64 = 8 * 60 * 60;
+/// Longest lifetime a session can be configured with in seconds(a year)
+pub const MAX_SESSION_TTL: i64 = 365 * 24 * 60 * 60;
+const SESSION_ID_LEN: usize = 16;
+
+/// A stored session
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    pub id: String,
+    pub user_id: Id,
+    pub issued_at: Timestamp,
+    pub expires_at: Timestamp,
+    pub revoked: bool,
+}
+
+impl Session {
+    /// A new session with a random ID valid for `ttl` seconds from `now`
+    pub fn new(user_id: Id, now: Timestamp, ttl: i64) -> Self {
+        let id: [u8; SESSION_ID_LEN] = ring::rand::generate(&ring::rand::SystemRandom::new())
+            .unwrap()
+            .expose();
+        Self {
+            id: id.iter().map(|b| format!("{:02x}", b)).collect(),
+            user_id,
+            issued_at: now,
+            expires_at: now.saturating_add(ttl),
+            revoked: false,
+        }
+    }
+
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        now >= self.expires_at
+    }
+}
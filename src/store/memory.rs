@@ -0,0 +1,787 @@
+//! In-memory implementation of `Store`, nothing is persisted
+//! Follows the same rules as the SQLite schema: unique job and candidate names,
+//! one application per job and candidate, and applications referencing existing rows.
+//! Like a new SQLite DB it starts with the default pipeline.
+use super::{Store, StoreError, StoreResult};
+use crate::attachment::Attachment;
+use crate::interview::{Interview, InterviewFilter};
+use crate::matching::JobSkill;
+use crate::offer::{Offer, OfferFilter, OfferStatus};
+use crate::pipeline::Pipeline;
+use crate::profile::Profile;
+use crate::questionnaire::{Answer, Question};
+use crate::scorecard::Scorecard;
+use crate::search::{self, CandidateSearch, JobSearch};
+use crate::session::Session;
+use crate::{Application, Candidate, Id, Job, Rejection, Role, Timestamp};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Note: Uses a `RefCell` since `Store` takes `&self`, it's `Send` but not `Sync`
+/// like the SQLite connection, so it's expected to be used behind a `Mutex` when shared.
+pub struct MemoryStore {
+    inner: RefCell<Tables>,
+}
+
+#[derive(Default)]
+struct Tables {
+    // Jobs are kept without their applicants, those are joined in `list_jobs`
+    jobs: HashMap<Id, Job>,
+    candidates: HashMap<Id, Candidate>,
+    // Keyed by (job_id, candidate_id) holding (application id, stage, rejection)
+    applications: HashMap<(Id, Id), (Id, usize, Option<Rejection>)>,
+    pipelines: HashMap<Id, Pipeline>,
+    interviews: HashMap<Id, Interview>,
+    scorecards: HashMap<Id, Scorecard>,
+    offers: HashMap<Id, Offer>,
+    // Keyed by candidate ID
+    profiles: HashMap<Id, Profile>,
+    attachments: HashMap<Id, Attachment>,
+    // Keyed by the hash of the content
+    contents: HashMap<String, Vec<u8>>,
+    // Keyed by job ID, in the order they were given
+    job_skills: HashMap<Id, Vec<JobSkill>>,
+    // Keyed by job ID, in the order they're asked
+    questions: HashMap<Id, Vec<Question>>,
+    // Keyed by (job_id, candidate_id), ordered by the position of their question
+    answers: HashMap<(Id, Id), Vec<(usize, Answer)>>,
+    sessions: HashMap<String, Session>,
+    signing_key: Option<Vec<u8>>,
+    last_job_id: Id,
+    last_candidate_id: Id,
+    last_application_id: Id,
+    last_pipeline_id: Id,
+    last_interview_id: Id,
+    last_scorecard_id: Id,
+    last_offer_id: Id,
+    last_attachment_id: Id,
+}
+
+fn next_id(last: &mut Id) -> Id {
+    *last += 1;
+    *last
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        let default_pipeline = Pipeline::default_pipeline();
+        let tables = Tables {
+            last_pipeline_id: default_pipeline.id,
+            pipelines: std::iter::once((default_pipeline.id, default_pipeline)).collect(),
+            ..Tables::default()
+        };
+        Self {
+            inner: RefCell::new(tables),
+        }
+    }
+}
+
+impl Tables {
+    /// `updating` is the job being updated, which can keep its own name
+    fn check_unique_job_name(&self, name: &str, updating: Option<Id>) -> StoreResult<()> {
+        if self
+            .jobs
+            .values()
+            .any(|stored| stored.name == name && Some(stored.id) != updating)
+        {
+            Err(StoreError::Constraint(
+                "UNIQUE constraint failed: jobs.name".into(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_interview_references(&self, interview: &Interview) -> StoreResult<()> {
+        if self
+            .applications
+            .contains_key(&(interview.job_id, interview.candidate_id))
+            && interview
+                .interviewers
+                .iter()
+                .all(|id| self.candidates.contains_key(id))
+        {
+            Ok(())
+        } else {
+            Err(StoreError::Constraint(
+                "FOREIGN KEY constraint failed".into(),
+            ))
+        }
+    }
+
+    /// The job with its accepted and outstanding offers counted
+    fn with_offer_counts(&self, job: &Job) -> Job {
+        let offers = || self.offers.values().filter(|offer| offer.job_id == job.id);
+        Job {
+            hired: offers()
+                .filter(|offer| offer.status == OfferStatus::Accepted)
+                .count() as u32,
+            offered: offers()
+                .filter(|offer| offer.status.is_outstanding())
+                .count() as u32,
+            ..job.clone()
+        }
+    }
+
+    fn check_pipeline_exists(&self, pipeline_id: Id) -> StoreResult<()> {
+        if self.pipelines.contains_key(&pipeline_id) {
+            Ok(())
+        } else {
+            Err(StoreError::Constraint(
+                "FOREIGN KEY constraint failed".into(),
+            ))
+        }
+    }
+}
+
+impl Store for MemoryStore {
+    fn add_job_posting(&self, job: &Job) -> StoreResult<Id> {
+        let mut tables = self.inner.borrow_mut();
+        tables.check_unique_job_name(&job.name, None)?;
+        tables.check_pipeline_exists(job.pipeline_id)?;
+        let id = next_id(&mut tables.last_job_id);
+        tables.jobs.insert(
+            id,
+            Job {
+                id,
+                applicants: Default::default(),
+                rejections: Default::default(),
+                ..job.clone()
+            },
+        );
+        Ok(id)
+    }
+
+    fn list_jobs(&self) -> StoreResult<Vec<Job>> {
+        let tables = self.inner.borrow();
+        let mut jobs = tables.jobs.clone();
+        for job in jobs.values_mut() {
+            *job = tables.with_offer_counts(job);
+        }
+        for ((job_id, candidate_id), (_, stage, rejection)) in tables.applications.iter() {
+            if let (Some(job), Some(candidate)) =
+                (jobs.get_mut(job_id), tables.candidates.get(candidate_id))
+            {
+                let stage = tables
+                    .pipelines
+                    .get(&job.pipeline_id)
+                    .map_or("?", |pipeline| pipeline.stage_name(*stage));
+                job.applicants
+                    .insert(candidate.user.clone(), stage.to_string());
+                if let Some(rejection) = rejection {
+                    job.rejections
+                        .insert(candidate.user.clone(), rejection.clone());
+                }
+            }
+        }
+        let mut jobs: Vec<Job> = jobs.into_values().collect();
+        jobs.sort_by_key(|job| job.id);
+        Ok(jobs)
+    }
+
+    fn list_jobs_page(&self, page: usize, page_size: usize) -> StoreResult<Vec<Job>> {
+        Ok(self
+            .list_jobs()?
+            .into_iter()
+            .skip(page.saturating_mul(page_size))
+            .take(page_size)
+            .collect())
+    }
+
+    fn get_job_by_id(&self, job_id: Id) -> StoreResult<Job> {
+        let tables = self.inner.borrow();
+        tables
+            .jobs
+            .get(&job_id)
+            .map(|job| tables.with_offer_counts(job))
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn get_job_with_applicants(&self, job_id: Id) -> StoreResult<Job> {
+        self.list_jobs()?
+            .into_iter()
+            .find(|job| job.id == job_id)
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn update_job_posting(&self, job: &Job) -> StoreResult<usize> {
+        let mut tables = self.inner.borrow_mut();
+        if !tables.jobs.contains_key(&job.id) {
+            return Ok(0);
+        }
+        tables.check_unique_job_name(&job.name, Some(job.id))?;
+        tables.check_pipeline_exists(job.pipeline_id)?;
+        tables.jobs.insert(
+            job.id,
+            Job {
+                applicants: Default::default(),
+                rejections: Default::default(),
+                ..job.clone()
+            },
+        );
+        Ok(1)
+    }
+
+    fn add_candidate(&self, candidate: &Candidate) -> StoreResult<usize> {
+        let mut tables = self.inner.borrow_mut();
+        if tables
+            .candidates
+            .values()
+            .any(|stored| stored.user == candidate.user)
+        {
+            return Err(StoreError::Constraint(
+                "UNIQUE constraint failed: candidates.name".into(),
+            ));
+        }
+        let id = next_id(&mut tables.last_candidate_id);
+        tables.candidates.insert(
+            id,
+            Candidate {
+                id,
+                ..candidate.clone()
+            },
+        );
+        Ok(1)
+    }
+
+    fn get_candidate(&self, candidate_name: &str) -> StoreResult<Candidate> {
+        self.inner
+            .borrow()
+            .candidates
+            .values()
+            .find(|candidate| candidate.user == candidate_name)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn update_candidate(&self, candidate: &Candidate) -> StoreResult<usize> {
+        let mut tables = self.inner.borrow_mut();
+        if !tables.candidates.contains_key(&candidate.id) {
+            return Ok(0);
+        }
+        if tables
+            .candidates
+            .values()
+            .any(|stored| stored.user == candidate.user && stored.id != candidate.id)
+        {
+            return Err(StoreError::Constraint(
+                "UNIQUE constraint failed: candidates.name".into(),
+            ));
+        }
+        tables.candidates.insert(candidate.id, candidate.clone());
+        Ok(1)
+    }
+
+    fn count_by_role(&self, role: Role) -> StoreResult<usize> {
+        Ok(self
+            .inner
+            .borrow()
+            .candidates
+            .values()
+            .filter(|candidate| candidate.role == role)
+            .count())
+    }
+
+    fn insert_application(
+        &self,
+        application: &Application,
+        answers: &[(usize, Answer)],
+    ) -> StoreResult<Id> {
+        let mut tables = self.inner.borrow_mut();
+        if !tables.jobs.contains_key(&application.job_id)
+            || !tables.candidates.contains_key(&application.candidate_id)
+        {
+            return Err(StoreError::Constraint(
+                "FOREIGN KEY constraint failed".into(),
+            ));
+        }
+        let key = (application.job_id, application.candidate_id);
+        if tables.applications.contains_key(&key) {
+            return Err(StoreError::Constraint(
+                "UNIQUE constraint failed: applications.job_id, applications.candidate_id".into(),
+            ));
+        }
+        let id = next_id(&mut tables.last_application_id);
+        tables
+            .applications
+            .insert(key, (id, application.stage, application.rejection.clone()));
+        let mut answers = answers.to_vec();
+        answers.sort_by_key(|(position, _)| *position);
+        tables.answers.insert(key, answers);
+        Ok(id)
+    }
+
+    fn get_application(&self, job_id: Id, candidate_id: Id) -> StoreResult<Application> {
+        self.inner
+            .borrow()
+            .applications
+            .get(&(job_id, candidate_id))
+            .map(|(_, stage, rejection)| Application {
+                job_id,
+                candidate_id,
+                stage: *stage,
+                rejection: rejection.clone(),
+            })
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn update_application(&self, application: &Application) -> StoreResult<usize> {
+        Ok(self
+            .inner
+            .borrow_mut()
+            .applications
+            .get_mut(&(application.job_id, application.candidate_id))
+            .map(|(_, stage, rejection)| {
+                *stage = application.stage;
+                *rejection = application.rejection.clone();
+            })
+            .map_or(0, |_| 1))
+    }
+
+    fn add_pipeline(&self, pipeline: &Pipeline) -> StoreResult<Id> {
+        let mut tables = self.inner.borrow_mut();
+        if tables
+            .pipelines
+            .values()
+            .any(|stored| stored.name == pipeline.name)
+        {
+            return Err(StoreError::Constraint(
+                "UNIQUE constraint failed: pipelines.name".into(),
+            ));
+        }
+        let id = next_id(&mut tables.last_pipeline_id);
+        tables.pipelines.insert(
+            id,
+            Pipeline {
+                id,
+                ..pipeline.clone()
+            },
+        );
+        Ok(id)
+    }
+
+    fn get_pipeline(&self, pipeline_id: Id) -> StoreResult<Pipeline> {
+        self.inner
+            .borrow()
+            .pipelines
+            .get(&pipeline_id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn list_pipelines(&self) -> StoreResult<Vec<Pipeline>> {
+        let mut pipelines: Vec<Pipeline> =
+            self.inner.borrow().pipelines.values().cloned().collect();
+        pipelines.sort_by_key(|pipeline| pipeline.id);
+        Ok(pipelines)
+    }
+
+    fn add_interview(&self, interview: &Interview) -> StoreResult<Id> {
+        let mut tables = self.inner.borrow_mut();
+        tables.check_interview_references(interview)?;
+        let id = next_id(&mut tables.last_interview_id);
+        tables.interviews.insert(
+            id,
+            Interview {
+                id,
+                ..interview.clone()
+            },
+        );
+        Ok(id)
+    }
+
+    fn get_interview(&self, interview_id: Id) -> StoreResult<Interview> {
+        self.inner
+            .borrow()
+            .interviews
+            .get(&interview_id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn update_interview(&self, interview: &Interview) -> StoreResult<usize> {
+        let mut tables = self.inner.borrow_mut();
+        if !tables.interviews.contains_key(&interview.id) {
+            return Ok(0);
+        }
+        tables.check_interview_references(interview)?;
+        tables.interviews.insert(interview.id, interview.clone());
+        Ok(1)
+    }
+
+    fn list_interviews(&self, filter: &InterviewFilter) -> StoreResult<Vec<Interview>> {
+        let mut interviews: Vec<Interview> = self
+            .inner
+            .borrow()
+            .interviews
+            .values()
+            .filter(|interview| filter.matches(interview))
+            .cloned()
+            .collect();
+        interviews.sort_by_key(|interview| (interview.starts_at, interview.id));
+        Ok(interviews)
+    }
+
+    fn add_scorecard(&self, scorecard: &Scorecard) -> StoreResult<Id> {
+        let mut tables = self.inner.borrow_mut();
+        if !tables
+            .applications
+            .contains_key(&(scorecard.job_id, scorecard.candidate_id))
+            || !tables.candidates.contains_key(&scorecard.interviewer_id)
+            || scorecard
+                .interview_id
+                .is_some_and(|id| !tables.interviews.contains_key(&id))
+        {
+            return Err(StoreError::Constraint(
+                "FOREIGN KEY constraint failed".into(),
+            ));
+        }
+        let id = next_id(&mut tables.last_scorecard_id);
+        tables.scorecards.insert(
+            id,
+            Scorecard {
+                id,
+                ..scorecard.clone()
+            },
+        );
+        Ok(id)
+    }
+
+    fn list_scorecards(&self, job_id: Id, candidate_id: Id) -> StoreResult<Vec<Scorecard>> {
+        let mut scorecards: Vec<Scorecard> = self
+            .inner
+            .borrow()
+            .scorecards
+            .values()
+            .filter(|scorecard| {
+                scorecard.job_id == job_id && scorecard.candidate_id == candidate_id
+            })
+            .cloned()
+            .collect();
+        scorecards.sort_by_key(|scorecard| scorecard.id);
+        Ok(scorecards)
+    }
+
+    fn add_offer(&self, offer: &Offer) -> StoreResult<Id> {
+        let mut tables = self.inner.borrow_mut();
+        if !tables
+            .applications
+            .contains_key(&(offer.job_id, offer.candidate_id))
+        {
+            return Err(StoreError::Constraint(
+                "FOREIGN KEY constraint failed".into(),
+            ));
+        }
+        let id = next_id(&mut tables.last_offer_id);
+        tables.offers.insert(
+            id,
+            Offer {
+                id,
+                ..offer.clone()
+            },
+        );
+        Ok(id)
+    }
+
+    fn get_offer(&self, offer_id: Id) -> StoreResult<Offer> {
+        self.inner
+            .borrow()
+            .offers
+            .get(&offer_id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn update_offer(&self, offer: &Offer) -> StoreResult<usize> {
+        Ok(self
+            .inner
+            .borrow_mut()
+            .offers
+            .get_mut(&offer.id)
+            .map(|stored| stored.status = offer.status)
+            .map_or(0, |_| 1))
+    }
+
+    fn list_offers(&self, filter: &OfferFilter) -> StoreResult<Vec<Offer>> {
+        let mut offers: Vec<Offer> = self
+            .inner
+            .borrow()
+            .offers
+            .values()
+            .filter(|offer| filter.matches(offer))
+            .cloned()
+            .collect();
+        offers.sort_by_key(|offer| offer.id);
+        Ok(offers)
+    }
+
+    fn expire_offers(&self, now: Timestamp) -> StoreResult<usize> {
+        let mut tables = self.inner.borrow_mut();
+        let mut expired = 0;
+        for offer in tables
+            .offers
+            .values_mut()
+            .filter(|offer| offer.is_expired(now))
+        {
+            offer.status = OfferStatus::Expired;
+            expired += 1;
+        }
+        Ok(expired)
+    }
+
+    fn set_profile(&self, profile: &Profile) -> StoreResult<()> {
+        let mut tables = self.inner.borrow_mut();
+        if !tables.candidates.contains_key(&profile.candidate_id) {
+            return Err(StoreError::Constraint(
+                "FOREIGN KEY constraint failed".into(),
+            ));
+        }
+        tables
+            .profiles
+            .insert(profile.candidate_id, profile.clone());
+        Ok(())
+    }
+
+    fn get_profile(&self, candidate_id: Id) -> StoreResult<Profile> {
+        self.inner
+            .borrow()
+            .profiles
+            .get(&candidate_id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn add_attachment(&self, attachment: &Attachment) -> StoreResult<Id> {
+        let mut tables = self.inner.borrow_mut();
+        if !tables
+            .applications
+            .contains_key(&(attachment.job_id, attachment.candidate_id))
+        {
+            return Err(StoreError::Constraint(
+                "FOREIGN KEY constraint failed".into(),
+            ));
+        }
+        let id = next_id(&mut tables.last_attachment_id);
+        tables.attachments.insert(
+            id,
+            Attachment {
+                id,
+                ..attachment.clone()
+            },
+        );
+        Ok(id)
+    }
+
+    fn get_attachment(&self, attachment_id: Id) -> StoreResult<Attachment> {
+        self.inner
+            .borrow()
+            .attachments
+            .get(&attachment_id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn list_attachments(&self, job_id: Id, candidate_id: Id) -> StoreResult<Vec<Attachment>> {
+        let mut attachments: Vec<Attachment> = self
+            .inner
+            .borrow()
+            .attachments
+            .values()
+            .filter(|attachment| {
+                attachment.job_id == job_id && attachment.candidate_id == candidate_id
+            })
+            .cloned()
+            .collect();
+        attachments.sort_by_key(|attachment| attachment.id);
+        Ok(attachments)
+    }
+
+    fn delete_attachment(&self, attachment_id: Id) -> StoreResult<usize> {
+        Ok(self
+            .inner
+            .borrow_mut()
+            .attachments
+            .remove(&attachment_id)
+            .map_or(0, |_| 1))
+    }
+
+    fn count_content_references(&self, sha256: &str) -> StoreResult<usize> {
+        Ok(self
+            .inner
+            .borrow()
+            .attachments
+            .values()
+            .filter(|attachment| attachment.sha256 == sha256)
+            .count())
+    }
+
+    fn put_content(&self, sha256: &str, content: &[u8]) -> StoreResult<()> {
+        self.inner
+            .borrow_mut()
+            .contents
+            .entry(sha256.to_string())
+            .or_insert_with(|| content.to_vec());
+        Ok(())
+    }
+
+    fn get_content(&self, sha256: &str) -> StoreResult<Vec<u8>> {
+        self.inner
+            .borrow()
+            .contents
+            .get(sha256)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn delete_content(&self, sha256: &str) -> StoreResult<usize> {
+        Ok(self
+            .inner
+            .borrow_mut()
+            .contents
+            .remove(sha256)
+            .map_or(0, |_| 1))
+    }
+
+    fn set_job_skills(&self, job_id: Id, skills: &[JobSkill]) -> StoreResult<()> {
+        let mut tables = self.inner.borrow_mut();
+        if !tables.jobs.contains_key(&job_id) {
+            return Err(StoreError::Constraint(
+                "FOREIGN KEY constraint failed".into(),
+            ));
+        }
+        tables.job_skills.insert(job_id, skills.to_vec());
+        Ok(())
+    }
+
+    fn get_job_skills(&self, job_id: Id) -> StoreResult<Vec<JobSkill>> {
+        Ok(self
+            .inner
+            .borrow()
+            .job_skills
+            .get(&job_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn set_questions(&self, job_id: Id, questions: &[Question]) -> StoreResult<()> {
+        let mut tables = self.inner.borrow_mut();
+        if !tables.jobs.contains_key(&job_id) {
+            return Err(StoreError::Constraint(
+                "FOREIGN KEY constraint failed".into(),
+            ));
+        }
+        tables.questions.insert(job_id, questions.to_vec());
+        Ok(())
+    }
+
+    fn get_questions(&self, job_id: Id) -> StoreResult<Vec<Question>> {
+        Ok(self
+            .inner
+            .borrow()
+            .questions
+            .get(&job_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn get_answers(&self, job_id: Id, candidate_id: Id) -> StoreResult<Vec<(usize, Answer)>> {
+        Ok(self
+            .inner
+            .borrow()
+            .answers
+            .get(&(job_id, candidate_id))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    // Note: Relevance is how often the query words appear, ties keep the order of the IDs
+    fn search_jobs(&self, search: &JobSearch) -> StoreResult<Vec<Job>> {
+        let query = search::words(&search.query);
+        let mut found: Vec<(usize, Job)> = self
+            .list_jobs()?
+            .into_iter()
+            .filter(|job| search.matches(job))
+            .filter_map(|job| {
+                search::relevance(&query, &JobSearch::fields(&job))
+                    .map(|relevance| (relevance, job))
+            })
+            .collect();
+        found.sort_by(|(a, _), (b, _)| b.cmp(a));
+        Ok(found.into_iter().map(|(_, job)| job).collect())
+    }
+
+    fn search_candidates(&self, search: &CandidateSearch) -> StoreResult<Vec<(String, Profile)>> {
+        let tables = self.inner.borrow();
+        let query = search::words(&search.query);
+        let mut found: Vec<(usize, Id, String, Profile)> = tables
+            .profiles
+            .values()
+            .filter(|profile| search.matches(profile))
+            .filter_map(|profile| {
+                let relevance = search::relevance(&query, &CandidateSearch::fields(profile))?;
+                let candidate = tables.candidates.get(&profile.candidate_id)?;
+                Some((
+                    relevance,
+                    profile.candidate_id,
+                    candidate.user.clone(),
+                    profile.clone(),
+                ))
+            })
+            .collect();
+        found.sort_by(|(a, a_id, ..), (b, b_id, ..)| b.cmp(a).then(a_id.cmp(b_id)));
+        Ok(found
+            .into_iter()
+            .map(|(_, _, user, profile)| (user, profile))
+            .collect())
+    }
+
+    fn insert_session(&self, session: &Session) -> StoreResult<()> {
+        let mut tables = self.inner.borrow_mut();
+        if !tables.candidates.contains_key(&session.user_id) {
+            return Err(StoreError::Constraint(
+                "FOREIGN KEY constraint failed".into(),
+            ));
+        }
+        if tables.sessions.contains_key(&session.id) {
+            return Err(StoreError::Constraint(
+                "UNIQUE constraint failed: sessions.id".into(),
+            ));
+        }
+        tables.sessions.insert(session.id.clone(), session.clone());
+        Ok(())
+    }
+
+    fn get_session(&self, session_id: &str) -> StoreResult<Session> {
+        self.inner
+            .borrow()
+            .sessions
+            .get(session_id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn revoke_session(&self, session_id: &str) -> StoreResult<usize> {
+        Ok(self
+            .inner
+            .borrow_mut()
+            .sessions
+            .get_mut(session_id)
+            .map(|session| session.revoked = true)
+            .map_or(0, |_| 1))
+    }
+
+    fn get_signing_key(&self) -> StoreResult<Option<Vec<u8>>> {
+        Ok(self.inner.borrow().signing_key.clone())
+    }
+
+    fn set_signing_key(&self, key: &[u8]) -> StoreResult<()> {
+        self.inner.borrow_mut().signing_key = Some(key.into());
+        Ok(())
+    }
+}
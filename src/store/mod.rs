@@ -0,0 +1,406 @@
+//! Storage backends for the HR system
+//! `HRSystem` is generic over `Store`, any backend implementing it can be plugged in.
+//! The SQLite backend lives behind the `sqlite` feature, `MemoryStore` is always available.
+//! To pick a backend at runtime use a `Box<dyn Store>`.
+use crate::attachment::Attachment;
+use crate::interview::{Interview, InterviewFilter};
+use crate::matching::JobSkill;
+use crate::offer::{Offer, OfferFilter};
+use crate::pipeline::Pipeline;
+use crate::profile::Profile;
+use crate::questionnaire::{Answer, Question};
+use crate::scorecard::Scorecard;
+use crate::search::{CandidateSearch, JobSearch};
+use crate::session::Session;
+use crate::{Application, Candidate, Id, Job, Role, Timestamp};
+
+pub mod memory;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+pub type StoreResult<T> = Result<T, StoreError>;
+
+/// Error returned by a `Store`
+/// - NotFound: The requested record doesn't exist
+/// - Constraint: A unique or foreign key constraint was violated
+/// - UnsupportedSchema: The stored schema is newer than the one this version understands
+/// - Backend: Any other error generated by the underlying backend
+#[derive(Debug)]
+pub enum StoreError {
+    NotFound,
+    Constraint(String),
+    UnsupportedSchema { found: u32, supported: u32 },
+    Backend(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "Record not found"),
+            Self::Constraint(constraint) => write!(f, "Constraint violated: {}", constraint),
+            Self::UnsupportedSchema { found, supported } => write!(
+                f,
+                "Schema version {} is newer than the supported version {}",
+                found, supported
+            ),
+            Self::Backend(e) => write!(f, "Storage backend error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+/// Every operation the system needs from its persistence layer.
+pub trait Store {
+    /// Stores a new job returning its ID, job names are unique and its pipeline must exist
+    fn add_job_posting(&self, job: &Job) -> StoreResult<Id>;
+
+    /// Returns every job joined with its applicants(and the name of their stage) ordered by ID
+    fn list_jobs(&self) -> StoreResult<Vec<Job>>;
+
+    /// Returns the `page`th(starting from 0) group of `page_size` jobs ordered by ID
+    fn list_jobs_page(&self, page: usize, page_size: usize) -> StoreResult<Vec<Job>>;
+
+    /// Returns a job without its applicants
+    fn get_job_by_id(&self, job_id: Id) -> StoreResult<Job>;
+
+    /// Returns a job joined with its applicants like `list_jobs` does
+    fn get_job_with_applicants(&self, job_id: Id) -> StoreResult<Job>;
+
+    /// Updates the job with the same ID, but its applicants and offer counts which are stored elsewhere
+    fn update_job_posting(&self, job: &Job) -> StoreResult<usize>;
+
+    /// Stores a new candidate, usernames are unique
+    fn add_candidate(&self, candidate: &Candidate) -> StoreResult<usize>;
+
+    fn get_candidate(&self, candidate_name: &str) -> StoreResult<Candidate>;
+
+    /// Updates the candidate with the same ID
+    fn update_candidate(&self, candidate: &Candidate) -> StoreResult<usize>;
+
+    /// Number of users with the given role
+    fn count_by_role(&self, role: Role) -> StoreResult<usize>;
+
+    /// Stores a new application with its answers returning its ID, either both are stored or neither.
+    /// There can only be one application per job and candidate, both of which must exist.
+    /// Each answer is paired with the position of its question.
+    fn insert_application(
+        &self,
+        application: &Application,
+        answers: &[(usize, Answer)],
+    ) -> StoreResult<Id>;
+
+    fn get_application(&self, job_id: Id, candidate_id: Id) -> StoreResult<Application>;
+
+    fn update_application(&self, application: &Application) -> StoreResult<usize>;
+
+    /// Stores a new pipeline returning its ID, pipeline names are unique.
+    /// Note: The pipeline must be valid(see `Pipeline::validate`), stores don't check it
+    fn add_pipeline(&self, pipeline: &Pipeline) -> StoreResult<Id>;
+
+    fn get_pipeline(&self, pipeline_id: Id) -> StoreResult<Pipeline>;
+
+    /// Returns every pipeline ordered by ID, there's always at least the default one
+    fn list_pipelines(&self) -> StoreResult<Vec<Pipeline>>;
+
+    /// Stores a new interview returning its ID.
+    /// The application and the interviewers must exist
+    fn add_interview(&self, interview: &Interview) -> StoreResult<Id>;
+
+    fn get_interview(&self, interview_id: Id) -> StoreResult<Interview>;
+
+    /// Updates the interview with the same ID, including its interviewers
+    fn update_interview(&self, interview: &Interview) -> StoreResult<usize>;
+
+    /// Returns the interviews matching `filter` ordered by start time
+    fn list_interviews(&self, filter: &InterviewFilter) -> StoreResult<Vec<Interview>>;
+
+    /// Stores a new scorecard returning its ID.
+    /// The application, the interviewer and the interview(if any) must exist
+    fn add_scorecard(&self, scorecard: &Scorecard) -> StoreResult<Id>;
+
+    /// Returns the scorecards of an application ordered by ID
+    fn list_scorecards(&self, job_id: Id, candidate_id: Id) -> StoreResult<Vec<Scorecard>>;
+
+    /// Stores a new offer returning its ID, the application must exist
+    fn add_offer(&self, offer: &Offer) -> StoreResult<Id>;
+
+    fn get_offer(&self, offer_id: Id) -> StoreResult<Offer>;
+
+    /// Updates the status of the offer with the same ID, the rest of it is fixed once created
+    fn update_offer(&self, offer: &Offer) -> StoreResult<usize>;
+
+    /// Returns the offers matching `filter` ordered by ID
+    fn list_offers(&self, filter: &OfferFilter) -> StoreResult<Vec<Offer>>;
+
+    /// Marks the outstanding offers that expired by `now` as expired, returns how many
+    fn expire_offers(&self, now: Timestamp) -> StoreResult<usize>;
+
+    /// Replaces the profile of its candidate, who must exist
+    fn set_profile(&self, profile: &Profile) -> StoreResult<()>;
+
+    /// Returns the profile of the candidate, `StoreError::NotFound` if they don't have one
+    fn get_profile(&self, candidate_id: Id) -> StoreResult<Profile>;
+
+    /// Stores a new attachment returning its ID, the application must exist.
+    /// Note: Its content is stored apart, see `put_content`
+    fn add_attachment(&self, attachment: &Attachment) -> StoreResult<Id>;
+
+    fn get_attachment(&self, attachment_id: Id) -> StoreResult<Attachment>;
+
+    /// Returns the attachments of an application ordered by ID
+    fn list_attachments(&self, job_id: Id, candidate_id: Id) -> StoreResult<Vec<Attachment>>;
+
+    /// Deletes the attachment, returns the number of deleted attachments(0 if it didn't exist)
+    fn delete_attachment(&self, attachment_id: Id) -> StoreResult<usize>;
+
+    /// Number of attachments whose content has the given hash
+    fn count_content_references(&self, sha256: &str) -> StoreResult<usize>;
+
+    /// Stores content under its hash, storing the same hash again keeps the stored content
+    fn put_content(&self, sha256: &str, content: &[u8]) -> StoreResult<()>;
+
+    fn get_content(&self, sha256: &str) -> StoreResult<Vec<u8>>;
+
+    /// Deletes the content with the hash, returns the number of deleted contents(0 if it didn't exist)
+    fn delete_content(&self, sha256: &str) -> StoreResult<usize>;
+
+    /// Replaces the skills the job asks for, it must exist
+    fn set_job_skills(&self, job_id: Id, skills: &[JobSkill]) -> StoreResult<()>;
+
+    /// Returns the skills the job asks for in the order they were given, empty if it asks for none
+    fn get_job_skills(&self, job_id: Id) -> StoreResult<Vec<JobSkill>>;
+
+    /// Replaces the screening questions of the job, which must exist
+    fn set_questions(&self, job_id: Id, questions: &[Question]) -> StoreResult<()>;
+
+    /// Returns the screening questions of the job in the order they're asked, empty if it has none
+    fn get_questions(&self, job_id: Id) -> StoreResult<Vec<Question>>;
+
+    /// Returns the answers of an application ordered by the position of their question
+    fn get_answers(&self, job_id: Id, candidate_id: Id) -> StoreResult<Vec<(usize, Answer)>>;
+
+    /// Returns the jobs with their applicants matching the search, most relevant first.
+    /// Without a query they're ordered by ID
+    fn search_jobs(&self, search: &JobSearch) -> StoreResult<Vec<Job>>;
+
+    /// Returns the names and profiles of the candidates matching the search, most relevant first.
+    /// Without a query they're ordered by candidate ID, candidates without a profile are never found
+    fn search_candidates(&self, search: &CandidateSearch) -> StoreResult<Vec<(String, Profile)>>;
+
+    /// Stores a new session, session IDs are unique and the user must exist
+    fn insert_session(&self, session: &Session) -> StoreResult<()>;
+
+    fn get_session(&self, session_id: &str) -> StoreResult<Session>;
+
+    /// Marks the session as revoked, returns the number of revoked sessions(0 if it didn't exist)
+    fn revoke_session(&self, session_id: &str) -> StoreResult<usize>;
+
+    /// Key used to sign the session tokens, `None` if none was stored yet
+    fn get_signing_key(&self) -> StoreResult<Option<Vec<u8>>>;
+
+    /// Stores the signing key replacing the previous one
+    fn set_signing_key(&self, key: &[u8]) -> StoreResult<()>;
+}
+
+impl<T: Store + ?Sized> Store for Box<T> {
+    fn add_job_posting(&self, job: &Job) -> StoreResult<Id> {
+        (**self).add_job_posting(job)
+    }
+
+    fn list_jobs(&self) -> StoreResult<Vec<Job>> {
+        (**self).list_jobs()
+    }
+
+    fn list_jobs_page(&self, page: usize, page_size: usize) -> StoreResult<Vec<Job>> {
+        (**self).list_jobs_page(page, page_size)
+    }
+
+    fn get_job_by_id(&self, job_id: Id) -> StoreResult<Job> {
+        (**self).get_job_by_id(job_id)
+    }
+
+    fn get_job_with_applicants(&self, job_id: Id) -> StoreResult<Job> {
+        (**self).get_job_with_applicants(job_id)
+    }
+
+    fn update_job_posting(&self, job: &Job) -> StoreResult<usize> {
+        (**self).update_job_posting(job)
+    }
+
+    fn add_candidate(&self, candidate: &Candidate) -> StoreResult<usize> {
+        (**self).add_candidate(candidate)
+    }
+
+    fn get_candidate(&self, candidate_name: &str) -> StoreResult<Candidate> {
+        (**self).get_candidate(candidate_name)
+    }
+
+    fn update_candidate(&self, candidate: &Candidate) -> StoreResult<usize> {
+        (**self).update_candidate(candidate)
+    }
+
+    fn count_by_role(&self, role: Role) -> StoreResult<usize> {
+        (**self).count_by_role(role)
+    }
+
+    fn insert_application(
+        &self,
+        application: &Application,
+        answers: &[(usize, Answer)],
+    ) -> StoreResult<Id> {
+        (**self).insert_application(application, answers)
+    }
+
+    fn get_application(&self, job_id: Id, candidate_id: Id) -> StoreResult<Application> {
+        (**self).get_application(job_id, candidate_id)
+    }
+
+    fn update_application(&self, application: &Application) -> StoreResult<usize> {
+        (**self).update_application(application)
+    }
+
+    fn add_pipeline(&self, pipeline: &Pipeline) -> StoreResult<Id> {
+        (**self).add_pipeline(pipeline)
+    }
+
+    fn get_pipeline(&self, pipeline_id: Id) -> StoreResult<Pipeline> {
+        (**self).get_pipeline(pipeline_id)
+    }
+
+    fn list_pipelines(&self) -> StoreResult<Vec<Pipeline>> {
+        (**self).list_pipelines()
+    }
+
+    fn add_interview(&self, interview: &Interview) -> StoreResult<Id> {
+        (**self).add_interview(interview)
+    }
+
+    fn get_interview(&self, interview_id: Id) -> StoreResult<Interview> {
+        (**self).get_interview(interview_id)
+    }
+
+    fn update_interview(&self, interview: &Interview) -> StoreResult<usize> {
+        (**self).update_interview(interview)
+    }
+
+    fn list_interviews(&self, filter: &InterviewFilter) -> StoreResult<Vec<Interview>> {
+        (**self).list_interviews(filter)
+    }
+
+    fn add_scorecard(&self, scorecard: &Scorecard) -> StoreResult<Id> {
+        (**self).add_scorecard(scorecard)
+    }
+
+    fn list_scorecards(&self, job_id: Id, candidate_id: Id) -> StoreResult<Vec<Scorecard>> {
+        (**self).list_scorecards(job_id, candidate_id)
+    }
+
+    fn add_offer(&self, offer: &Offer) -> StoreResult<Id> {
+        (**self).add_offer(offer)
+    }
+
+    fn get_offer(&self, offer_id: Id) -> StoreResult<Offer> {
+        (**self).get_offer(offer_id)
+    }
+
+    fn update_offer(&self, offer: &Offer) -> StoreResult<usize> {
+        (**self).update_offer(offer)
+    }
+
+    fn list_offers(&self, filter: &OfferFilter) -> StoreResult<Vec<Offer>> {
+        (**self).list_offers(filter)
+    }
+
+    fn expire_offers(&self, now: Timestamp) -> StoreResult<usize> {
+        (**self).expire_offers(now)
+    }
+
+    fn set_profile(&self, profile: &Profile) -> StoreResult<()> {
+        (**self).set_profile(profile)
+    }
+
+    fn get_profile(&self, candidate_id: Id) -> StoreResult<Profile> {
+        (**self).get_profile(candidate_id)
+    }
+
+    fn add_attachment(&self, attachment: &Attachment) -> StoreResult<Id> {
+        (**self).add_attachment(attachment)
+    }
+
+    fn get_attachment(&self, attachment_id: Id) -> StoreResult<Attachment> {
+        (**self).get_attachment(attachment_id)
+    }
+
+    fn list_attachments(&self, job_id: Id, candidate_id: Id) -> StoreResult<Vec<Attachment>> {
+        (**self).list_attachments(job_id, candidate_id)
+    }
+
+    fn delete_attachment(&self, attachment_id: Id) -> StoreResult<usize> {
+        (**self).delete_attachment(attachment_id)
+    }
+
+    fn count_content_references(&self, sha256: &str) -> StoreResult<usize> {
+        (**self).count_content_references(sha256)
+    }
+
+    fn put_content(&self, sha256: &str, content: &[u8]) -> StoreResult<()> {
+        (**self).put_content(sha256, content)
+    }
+
+    fn get_content(&self, sha256: &str) -> StoreResult<Vec<u8>> {
+        (**self).get_content(sha256)
+    }
+
+    fn delete_content(&self, sha256: &str) -> StoreResult<usize> {
+        (**self).delete_content(sha256)
+    }
+
+    fn set_job_skills(&self, job_id: Id, skills: &[JobSkill]) -> StoreResult<()> {
+        (**self).set_job_skills(job_id, skills)
+    }
+
+    fn get_job_skills(&self, job_id: Id) -> StoreResult<Vec<JobSkill>> {
+        (**self).get_job_skills(job_id)
+    }
+
+    fn set_questions(&self, job_id: Id, questions: &[Question]) -> StoreResult<()> {
+        (**self).set_questions(job_id, questions)
+    }
+
+    fn get_questions(&self, job_id: Id) -> StoreResult<Vec<Question>> {
+        (**self).get_questions(job_id)
+    }
+
+    fn get_answers(&self, job_id: Id, candidate_id: Id) -> StoreResult<Vec<(usize, Answer)>> {
+        (**self).get_answers(job_id, candidate_id)
+    }
+
+    fn search_jobs(&self, search: &JobSearch) -> StoreResult<Vec<Job>> {
+        (**self).search_jobs(search)
+    }
+
+    fn search_candidates(&self, search: &CandidateSearch) -> StoreResult<Vec<(String, Profile)>> {
+        (**self).search_candidates(search)
+    }
+
+    fn insert_session(&self, session: &Session) -> StoreResult<()> {
+        (**self).insert_session(session)
+    }
+
+    fn get_session(&self, session_id: &str) -> StoreResult<Session> {
+        (**self).get_session(session_id)
+    }
+
+    fn revoke_session(&self, session_id: &str) -> StoreResult<usize> {
+        (**self).revoke_session(session_id)
+    }
+
+    fn get_signing_key(&self) -> StoreResult<Option<Vec<u8>>> {
+        (**self).get_signing_key()
+    }
+
+    fn set_signing_key(&self, key: &[u8]) -> StoreResult<()> {
+        (**self).set_signing_key(key)
+    }
+}
@@ -0,0 +1,423 @@
+//! Versioned schema migrations for the SQLite store
+//! The schema version is the number of migrations applied, it's tracked in the `schema_version` table.
+//! Migrations are applied in order on startup, each one in its own transaction.
+//! Never edit or reorder a migration once released, add a new one at the end instead.
+use crate::{StoreError, StoreResult};
+use rusqlite::{params, Connection};
+
+const MIGRATIONS: &[&str] = &[
+    // 1: Initial schema, `if not exists` so DBs created before migrations existed are adopted
+    "
+    create table if not exists jobs (
+        id integer primary key,
+        name text not null unique,
+        state integer not null
+    );
+
+    create table if not exists candidates (
+        id integer primary key,
+        name text unique,
+        password blob not null
+    );
+
+    create table if not exists applications (
+        state integer not null,
+        job_id int,
+        candidate_id int,
+        FOREIGN KEY (job_id) REFERENCES jobs(id),
+        FOREIGN KEY (candidate_id) REFERENCES candidates(id)
+        PRIMARY KEY (job_id, candidate_id)
+    );
+    ",
+    // 2: Salted password hashes, existing passwords are unsalted SHA-256
+    "
+    alter table candidates add column password_algorithm text not null default 'sha256';
+    alter table candidates add column password_iterations integer not null default 0;
+    alter table candidates add column password_salt blob not null default x'';
+    ",
+    // 3: User roles, existing users are candidates
+    "
+    alter table candidates add column role integer not null default 0;
+    ",
+    // 4: Login sessions and the key that signs them
+    "
+    create table sessions (
+        id text primary key,
+        user_id integer not null,
+        issued_at integer not null,
+        expires_at integer not null,
+        revoked integer not null default 0,
+        FOREIGN KEY (user_id) REFERENCES candidates(id)
+    );
+
+    create table settings (
+        name text primary key,
+        value blob not null
+    );
+    ",
+    // 5: Hiring pipelines, existing jobs follow the default one whose stage positions match the old states.
+    // SQLite can't add a column with a foreign key and a default, so triggers check jobs.pipeline_id
+    "
+    create table pipelines (
+        id integer primary key,
+        name text not null unique
+    );
+
+    create table pipeline_stages (
+        pipeline_id integer not null,
+        position integer not null,
+        name text not null,
+        outcome integer,
+        PRIMARY KEY (pipeline_id, position),
+        UNIQUE (pipeline_id, name),
+        FOREIGN KEY (pipeline_id) REFERENCES pipelines(id)
+    );
+
+    create table pipeline_transitions (
+        pipeline_id integer not null,
+        from_position integer not null,
+        to_position integer not null,
+        PRIMARY KEY (pipeline_id, from_position, to_position),
+        FOREIGN KEY (pipeline_id, from_position) REFERENCES pipeline_stages(pipeline_id, position),
+        FOREIGN KEY (pipeline_id, to_position) REFERENCES pipeline_stages(pipeline_id, position)
+    );
+
+    insert into pipelines (id, name) values (1, 'Default');
+    insert into pipeline_stages (pipeline_id, position, name, outcome) values
+        (1, 0, 'Applied', null),
+        (1, 1, 'Interviewed', null),
+        (1, 2, 'Rejected', 1),
+        (1, 3, 'Approved', 0);
+    insert into pipeline_transitions (pipeline_id, from_position, to_position) values
+        (1, 0, 1),
+        (1, 1, 3),
+        (1, 1, 2);
+
+    alter table jobs add column pipeline_id integer not null default 1;
+
+    create trigger jobs_pipeline_insert before insert on jobs
+    when not exists (select 1 from pipelines where id = new.pipeline_id)
+    begin
+        select raise(abort, 'FOREIGN KEY constraint failed');
+    end;
+
+    create trigger jobs_pipeline_update before update of pipeline_id on jobs
+    when not exists (select 1 from pipelines where id = new.pipeline_id)
+    begin
+        select raise(abort, 'FOREIGN KEY constraint failed');
+    end;
+    ",
+    // 6: Interview rounds and their interviewers
+    "
+    create table interviews (
+        id integer primary key,
+        job_id integer not null,
+        candidate_id integer not null,
+        round integer not null,
+        starts_at integer not null,
+        duration integer not null,
+        status integer not null default 0,
+        FOREIGN KEY (job_id, candidate_id) REFERENCES applications(job_id, candidate_id)
+    );
+
+    create table interview_interviewers (
+        interview_id integer not null,
+        interviewer_id integer not null,
+        PRIMARY KEY (interview_id, interviewer_id),
+        FOREIGN KEY (interview_id) REFERENCES interviews(id),
+        FOREIGN KEY (interviewer_id) REFERENCES candidates(id)
+    );
+
+    create index interviews_job on interviews (job_id, starts_at);
+    create index interview_interviewers_interviewer on interview_interviewers (interviewer_id);
+    ",
+    // 7: Interview scorecards
+    "
+    create table scorecards (
+        id integer primary key,
+        job_id integer not null,
+        candidate_id integer not null,
+        interviewer_id integer not null,
+        interview_id integer,
+        recommendation integer not null,
+        notes text not null default '',
+        submitted_at integer not null,
+        FOREIGN KEY (job_id, candidate_id) REFERENCES applications(job_id, candidate_id),
+        FOREIGN KEY (interviewer_id) REFERENCES candidates(id),
+        FOREIGN KEY (interview_id) REFERENCES interviews(id)
+    );
+
+    create table scorecard_ratings (
+        scorecard_id integer not null,
+        competency text not null,
+        score integer not null,
+        PRIMARY KEY (scorecard_id, competency),
+        FOREIGN KEY (scorecard_id) REFERENCES scorecards(id)
+    );
+
+    create index scorecards_application on scorecards (job_id, candidate_id);
+    ",
+    // 8: Rejection reasons and a Withdrawn stage(outcome 2) at the end of every pipeline
+    "
+    alter table applications add column rejection_reason text;
+    alter table applications add column rejection_note text;
+
+    insert into pipeline_stages (pipeline_id, position, name, outcome)
+    select pipelines.id,
+        (select count(*) from pipeline_stages where pipeline_id = pipelines.id),
+        'Withdrawn',
+        2
+    from pipelines
+    where not exists (
+        select 1 from pipeline_stages
+        where pipeline_id = pipelines.id and (outcome = 2 or name = 'Withdrawn')
+    );
+    ",
+    // 9: Openings per job
+    "
+    alter table jobs add column headcount integer not null default 1;
+    ",
+    // 10: Offers, hires made before them are kept as accepted offers(status 2)
+    "
+    create table offers (
+        id integer primary key,
+        job_id integer not null,
+        candidate_id integer not null,
+        salary integer not null,
+        start_date integer not null,
+        expires_at integer not null,
+        status integer not null default 0,
+        created_at integer not null,
+        FOREIGN KEY (job_id, candidate_id) REFERENCES applications(job_id, candidate_id)
+    );
+
+    create index offers_job on offers (job_id, status);
+
+    insert into offers (job_id, candidate_id, salary, start_date, expires_at, status, created_at)
+    select applications.job_id, applications.candidate_id, 0, 0, 0, 2, 0
+    from applications
+    join jobs on jobs.id = applications.job_id
+    join pipeline_stages on pipeline_stages.pipeline_id = jobs.pipeline_id
+        and pipeline_stages.position = applications.state
+    where pipeline_stages.outcome = 0;
+    ",
+    // 11: Job posting details, the salary range is NULL when the posting doesn't give one
+    "
+    alter table jobs add column description text not null default '';
+    alter table jobs add column department text not null default '';
+    alter table jobs add column location text not null default '';
+    alter table jobs add column workplace integer not null default 0;
+    alter table jobs add column employment_type integer not null default 0;
+    alter table jobs add column seniority integer;
+    alter table jobs add column salary_min integer;
+    alter table jobs add column salary_max integer;
+    alter table jobs add column salary_currency text;
+    ",
+    // 12: Application windows, NULL ends aren't limited
+    "
+    alter table jobs add column opens_at integer;
+    alter table jobs add column closes_at integer;
+    ",
+    // 13: Candidate profiles, months are stored as year * 100 + month
+    "
+    create table profiles (
+        candidate_id integer primary key,
+        full_name text not null,
+        email text not null,
+        phone text not null,
+        location text not null,
+        FOREIGN KEY (candidate_id) REFERENCES candidates(id)
+    );
+
+    create table profile_skills (
+        candidate_id integer not null,
+        position integer not null,
+        name text not null,
+        proficiency integer not null,
+        PRIMARY KEY (candidate_id, position),
+        FOREIGN KEY (candidate_id) REFERENCES profiles(candidate_id)
+    );
+
+    create table work_experience (
+        candidate_id integer not null,
+        position integer not null,
+        company text not null,
+        title text not null,
+        start_month integer not null,
+        end_month integer,
+        description text not null,
+        PRIMARY KEY (candidate_id, position),
+        FOREIGN KEY (candidate_id) REFERENCES profiles(candidate_id)
+    );
+
+    create table education (
+        candidate_id integer not null,
+        position integer not null,
+        institution text not null,
+        degree text not null,
+        start_month integer not null,
+        end_month integer,
+        PRIMARY KEY (candidate_id, position),
+        FOREIGN KEY (candidate_id) REFERENCES profiles(candidate_id)
+    );
+    ",
+    // 14: Documents attached to applications, contents are shared by every attachment with the same hash
+    "
+    create table attachments (
+        id integer primary key,
+        job_id integer not null,
+        candidate_id integer not null,
+        kind integer not null,
+        file_name text not null,
+        mime_type text not null,
+        size integer not null,
+        sha256 text not null,
+        uploaded_at integer not null,
+        FOREIGN KEY (job_id, candidate_id) REFERENCES applications(job_id, candidate_id)
+    );
+
+    create index attachments_application on attachments (job_id, candidate_id);
+    create index attachments_content on attachments (sha256);
+
+    create table attachment_contents (
+        sha256 text primary key,
+        content blob not null
+    );
+    ",
+    // 15: Screening questions of jobs and the answers of applications.
+    // `knockout` is the kind of rule, its choices are flagged in `question_choices`.
+    // Multiple choice answers are stored as their choices joined by newlines, which choices can't contain
+    "
+    create table questions (
+        job_id integer not null,
+        position integer not null,
+        text text not null,
+        kind integer not null,
+        required integer not null,
+        knockout integer,
+        knockout_answer integer,
+        knockout_min real,
+        knockout_max real,
+        PRIMARY KEY (job_id, position),
+        FOREIGN KEY (job_id) REFERENCES jobs(id)
+    );
+
+    create table question_choices (
+        job_id integer not null,
+        question integer not null,
+        position integer not null,
+        text text not null,
+        knockout integer not null,
+        PRIMARY KEY (job_id, question, position),
+        FOREIGN KEY (job_id, question) REFERENCES questions(job_id, position)
+    );
+
+    create table answers (
+        job_id integer not null,
+        candidate_id integer not null,
+        question integer not null,
+        kind integer not null,
+        text text,
+        number real,
+        PRIMARY KEY (job_id, candidate_id, question),
+        FOREIGN KEY (job_id, candidate_id) REFERENCES applications(job_id, candidate_id)
+    );
+    ",
+    // 16: Skills jobs ask for, required or nice to have
+    "
+    create table job_skills (
+        job_id integer not null,
+        position integer not null,
+        name text not null,
+        proficiency integer not null,
+        required integer not null,
+        PRIMARY KEY (job_id, position),
+        FOREIGN KEY (job_id) REFERENCES jobs(id)
+    );
+    ",
+    // 17: Full-text search, when jobs were posted(0 for existing ones). Jobs are indexed by triggers,
+    // profiles by the store when they're set
+    "
+    alter table jobs add column created_at integer not null default 0;
+
+    create virtual table jobs_search using fts5(
+        name, description, department, location, content='jobs', content_rowid='id'
+    );
+    insert into jobs_search(jobs_search) values ('rebuild');
+
+    create trigger jobs_search_insert after insert on jobs
+    begin
+        insert into jobs_search (rowid, name, description, department, location)
+        values (new.id, new.name, new.description, new.department, new.location);
+    end;
+
+    create trigger jobs_search_delete after delete on jobs
+    begin
+        insert into jobs_search (jobs_search, rowid, name, description, department, location)
+        values ('delete', old.id, old.name, old.description, old.department, old.location);
+    end;
+
+    create trigger jobs_search_update after update of name, description, department, location on jobs
+    begin
+        insert into jobs_search (jobs_search, rowid, name, description, department, location)
+        values ('delete', old.id, old.name, old.description, old.department, old.location);
+        insert into jobs_search (rowid, name, description, department, location)
+        values (new.id, new.name, new.description, new.department, new.location);
+    end;
+
+    create virtual table candidates_search using fts5(full_name, location, skills, experience, education);
+    insert into candidates_search (rowid, full_name, location, skills, experience, education)
+    select candidate_id, full_name, location,
+        coalesce((select group_concat(name, ' ') from profile_skills
+            where profile_skills.candidate_id = profiles.candidate_id), ''),
+        coalesce((select group_concat(title || ' ' || company || ' ' || description, ' ') from work_experience
+            where work_experience.candidate_id = profiles.candidate_id), ''),
+        coalesce((select group_concat(degree || ' ' || institution, ' ') from education
+            where education.candidate_id = profiles.candidate_id), '')
+    from profiles;
+    ",
+];
+
+/// Latest schema version this binary understands
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Current schema version of the DB, 0 if it was never migrated
+pub fn schema_version(conn: &Connection) -> StoreResult<u32> {
+    Ok(conn.query_row(
+        "SELECT coalesce(max(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+/// Brings the DB up to `SCHEMA_VERSION`, refuses to touch DBs with a newer schema
+pub fn migrate(conn: &mut Connection) -> StoreResult<()> {
+    conn.execute(
+        "
+        create table if not exists schema_version (
+            version integer primary key,
+            applied_at integer not null default (strftime('%s', 'now'))
+        )",
+        [],
+    )?;
+
+    let current = schema_version(conn)?;
+    if current > SCHEMA_VERSION {
+        return Err(StoreError::UnsupportedSchema {
+            found: current,
+            supported: SCHEMA_VERSION,
+        });
+    }
+
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.execute(
+            "INSERT INTO schema_version (version) values (?1)",
+            params![version as u32 + 1],
+        )?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
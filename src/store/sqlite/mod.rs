@@ -0,0 +1,1373 @@
+//! SQLite implementation of `Store` using rusqlite
+use super::{Store, StoreError, StoreResult};
+use crate::attachment::Attachment;
+use crate::interview::{Interview, InterviewFilter};
+use crate::matching::JobSkill;
+use crate::offer::{Offer, OfferFilter, OfferStatus};
+use crate::pipeline::{Pipeline, Stage};
+use crate::posting::{JobDetails, SalaryRange};
+use crate::profile::{Education, Profile, Skill, WorkExperience, YearMonth};
+use crate::questionnaire::{Answer, Knockout, Question, QuestionKind};
+use crate::scorecard::{Rating, Scorecard};
+use crate::search::{self, CandidateSearch, JobSearch};
+use crate::session::Session;
+use crate::{Application, Candidate, Id, Job, PasswordHash, Rejection, Role, Timestamp};
+use rusqlite::{params, Connection};
+use std::convert::TryFrom;
+use std::path::Path;
+
+mod migrations;
+
+pub use migrations::SCHEMA_VERSION;
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e {
+            rusqlite::Error::QueryReturnedNoRows => Self::NotFound,
+            rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error {
+                    code: rusqlite::ErrorCode::ConstraintViolation,
+                    ..
+                },
+                msg,
+            ) => Self::Constraint(msg.unwrap_or_default()),
+            e => Self::Backend(Box::new(e)),
+        }
+    }
+}
+
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Opens(or creates) the DB at the given path
+    pub fn open<P: AsRef<Path>>(path: P) -> StoreResult<Self> {
+        Self::setup_db(Connection::open(path)?)
+    }
+
+    /// Opens a DB that only lives as long as the returned store
+    pub fn open_in_memory() -> StoreResult<Self> {
+        Self::setup_db(Connection::open_in_memory()?)
+    }
+
+    /// Schema version of the opened DB, after opening it's always `SCHEMA_VERSION`
+    pub fn schema_version(&self) -> StoreResult<u32> {
+        migrations::schema_version(&self.conn)
+    }
+
+    fn setup_db(mut conn: Connection) -> StoreResult<Self> {
+        // SQLite doesn't check foreign keys unless asked to
+        conn.pragma_update(None, "foreign_keys", &true)?;
+        migrations::migrate(&mut conn)?;
+
+        Ok(SqliteStore { conn })
+    }
+}
+
+/// Number of accepted(status 2) and outstanding(status 0 or 1) offers of `jobs`
+const OFFER_COUNTS: &str =
+    "(SELECT count(*) FROM offers WHERE offers.job_id = jobs.id AND offers.status = 2),
+    (SELECT count(*) FROM offers WHERE offers.job_id = jobs.id AND offers.status IN (0, 1))";
+
+/// Columns of the job posting details, read by `read_details`
+const DETAILS_COLUMNS: &str =
+    "jobs.description, jobs.department, jobs.location, jobs.workplace, jobs.employment_type,
+    jobs.seniority, jobs.salary_min, jobs.salary_max, jobs.salary_currency";
+
+/// FTS5 query for `query`, each word is a quoted prefix and all of them have to match
+fn fts_query(query: &[String]) -> String {
+    query
+        .iter()
+        .map(|word| format!("\"{}\"*", word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Reads an enum stored as its discriminant
+fn read_enum<T: TryFrom<u8, Error = u8>>(
+    row: &rusqlite::Row,
+    column: usize,
+    what: &str,
+) -> rusqlite::Result<T> {
+    T::try_from(row.get(column)?).map_err(|i| {
+        rusqlite::Error::FromSqlConversionFailure(
+            column,
+            rusqlite::types::Type::Integer,
+            format!("Unknown {} {}", what, i).into(),
+        )
+    })
+}
+
+/// The job posting details stored in the `DETAILS_COLUMNS` starting at `column`
+fn read_details(row: &rusqlite::Row, column: usize) -> rusqlite::Result<JobDetails> {
+    let seniority: Option<u8> = row.get(column + 5)?;
+    let salary_min: Option<i64> = row.get(column + 6)?;
+    let salary_max: Option<i64> = row.get(column + 7)?;
+    let salary_currency: Option<String> = row.get(column + 8)?;
+    Ok(JobDetails {
+        description: row.get(column)?,
+        department: row.get(column + 1)?,
+        location: row.get(column + 2)?,
+        workplace: read_enum(row, column + 3, "workplace")?,
+        employment_type: read_enum(row, column + 4, "employment type")?,
+        seniority: match seniority {
+            Some(_) => Some(read_enum(row, column + 5, "seniority")?),
+            None => None,
+        },
+        salary: match (salary_min, salary_max, salary_currency) {
+            (Some(min), Some(max), Some(currency)) => Some(SalaryRange {
+                min: min as u64,
+                max: max as u64,
+                currency,
+            }),
+            _ => None,
+        },
+    })
+}
+
+/// Months are stored as year * 100 + month
+fn month_to_sql(month: YearMonth) -> i64 {
+    month.year as i64 * 100 + month.month as i64
+}
+
+fn month_from_sql(month: i64) -> YearMonth {
+    YearMonth::new((month / 100) as u16, (month % 100) as u8)
+}
+
+/// The rejection stored in the reason and note columns starting at `column`, if there's a reason
+fn read_rejection(row: &rusqlite::Row, column: usize) -> rusqlite::Result<Option<Rejection>> {
+    let reason: Option<String> = row.get(column)?;
+    Ok(match reason {
+        Some(reason) => Some(Rejection {
+            reason,
+            note: row
+                .get::<_, Option<String>>(column + 1)?
+                .unwrap_or_default(),
+        }),
+        None => None,
+    })
+}
+
+impl SqliteStore {
+    /// Joins the jobs selected by `jobs`(a table or subquery) with their applicants, their stage and rejection
+    fn query_jobs<P: rusqlite::Params>(&self, jobs: &str, params: P) -> StoreResult<Vec<Job>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT jobs.id, jobs.name, jobs.state, jobs.pipeline_id, coalesce(pipeline_stages.name, '?'), candidates.name,
+                applications.rejection_reason, applications.rejection_note, jobs.headcount, {}, {},
+                jobs.opens_at, jobs.closes_at, jobs.created_at
+            FROM {}
+            LEFT JOIN applications ON applications.job_id = jobs.id
+            LEFT JOIN candidates ON candidates.id = applications.candidate_id
+            LEFT JOIN pipeline_stages ON pipeline_stages.pipeline_id = jobs.pipeline_id
+                AND pipeline_stages.position = applications.state
+            ORDER BY jobs.id",
+            OFFER_COUNTS, DETAILS_COLUMNS, jobs
+        ))?;
+
+        let mut rows = stmt.query(params)?;
+
+        let mut jobs = Vec::<Job>::new();
+        while let Some(row) = rows.next()? {
+            let id: Id = row.get(0)?;
+            if jobs.last().map(|job| job.id) != Some(id) {
+                jobs.push(Job {
+                    id,
+                    name: row.get(1)?,
+                    state: read_enum(row, 2, "job state")?,
+                    applicants: Default::default(),
+                    rejections: Default::default(),
+                    headcount: row.get(8)?,
+                    hired: row.get(9)?,
+                    offered: row.get(10)?,
+                    pipeline_id: row.get(3)?,
+                    details: read_details(row, 11)?,
+                    opens_at: row.get(20)?,
+                    closes_at: row.get(21)?,
+                    created_at: row.get(22)?,
+                });
+            }
+            // Jobs without applications get NULLs from the LEFT JOIN
+            if let Ok(name) = row.get::<_, String>(5) {
+                if let Some(job) = jobs.last_mut() {
+                    if let Some(rejection) = read_rejection(row, 6)? {
+                        job.rejections.insert(name.clone(), rejection);
+                    }
+                    job.applicants.insert(name, row.get(4)?);
+                }
+            }
+        }
+
+        Ok(jobs)
+    }
+
+    fn read_pipeline(&self, id: Id, name: String) -> StoreResult<Pipeline> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, outcome FROM pipeline_stages WHERE pipeline_id = (?1) ORDER BY position",
+        )?;
+        let mut stages = stmt
+            .query_map([id], |row| {
+                let outcome: Option<u8> = row.get(1)?;
+                Ok(Stage {
+                    name: row.get(0)?,
+                    next: Vec::new(),
+                    outcome: match outcome {
+                        Some(_) => Some(read_enum(row, 1, "outcome")?),
+                        None => None,
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Ordered by rowid so the transitions keep the order they were declared in
+        let mut stmt = self.conn.prepare(
+            "SELECT from_position, to_position FROM pipeline_transitions
+            WHERE pipeline_id = (?1) ORDER BY rowid",
+        )?;
+        let mut rows = stmt.query([id])?;
+        while let Some(row) = rows.next()? {
+            let (from, to): (usize, usize) = (row.get(0)?, row.get(1)?);
+            if let Some(to) = stages.get(to).map(|stage| stage.name.clone()) {
+                if let Some(from) = stages.get_mut(from) {
+                    from.next.push(to);
+                }
+            }
+        }
+
+        Ok(Pipeline { id, name, stages })
+    }
+
+    /// Selects interviews with `conditions` filling their interviewers
+    fn query_interviews<P: rusqlite::Params>(
+        &self,
+        conditions: &str,
+        params: P,
+    ) -> StoreResult<Vec<Interview>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, job_id, candidate_id, round, starts_at, duration, status FROM interviews
+            WHERE {} ORDER BY starts_at, id",
+            conditions
+        ))?;
+        let mut interviews = stmt
+            .query_map(params, |row| {
+                Ok(Interview {
+                    id: row.get(0)?,
+                    job_id: row.get(1)?,
+                    candidate_id: row.get(2)?,
+                    round: row.get(3)?,
+                    starts_at: row.get(4)?,
+                    duration: row.get(5)?,
+                    interviewers: Vec::new(),
+                    status: read_enum(row, 6, "interview status")?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT interviewer_id FROM interview_interviewers WHERE interview_id = (?1) ORDER BY rowid",
+        )?;
+        for interview in interviews.iter_mut() {
+            interview.interviewers = stmt
+                .query_map([interview.id], |row| row.get(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+        }
+        Ok(interviews)
+    }
+
+    /// Selects offers with `conditions`
+    fn query_offers<P: rusqlite::Params>(
+        &self,
+        conditions: &str,
+        params: P,
+    ) -> StoreResult<Vec<Offer>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, job_id, candidate_id, salary, start_date, expires_at, status, created_at FROM offers
+            WHERE {} ORDER BY id",
+            conditions
+        ))?;
+        let offers = stmt
+            .query_map(params, |row| {
+                Ok(Offer {
+                    id: row.get(0)?,
+                    job_id: row.get(1)?,
+                    candidate_id: row.get(2)?,
+                    salary: row.get::<_, i64>(3)? as u64,
+                    start_date: row.get(4)?,
+                    expires_at: row.get(5)?,
+                    status: read_enum(row, 6, "offer status")?,
+                    created_at: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(offers)
+    }
+
+    /// Selects attachments with `conditions`
+    fn query_attachments<P: rusqlite::Params>(
+        &self,
+        conditions: &str,
+        params: P,
+    ) -> StoreResult<Vec<Attachment>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, job_id, candidate_id, kind, file_name, mime_type, size, sha256, uploaded_at
+            FROM attachments WHERE {} ORDER BY id",
+            conditions
+        ))?;
+        let attachments = stmt
+            .query_map(params, |row| {
+                Ok(Attachment {
+                    id: row.get(0)?,
+                    job_id: row.get(1)?,
+                    candidate_id: row.get(2)?,
+                    kind: read_enum(row, 3, "document kind")?,
+                    file_name: row.get(4)?,
+                    mime_type: row.get(5)?,
+                    size: row.get::<_, i64>(6)? as u64,
+                    sha256: row.get(7)?,
+                    uploaded_at: row.get(8)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(attachments)
+    }
+
+    fn insert_interviewers(&self, interview_id: Id, interviewers: &[Id]) -> StoreResult<()> {
+        for interviewer in interviewers {
+            self.conn.execute(
+                "INSERT INTO interview_interviewers (interview_id, interviewer_id) values (?1, ?2)",
+                [interview_id, *interviewer],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Store for SqliteStore {
+    fn get_application(&self, job_id: Id, candidate_id: Id) -> StoreResult<Application> {
+        Ok(self.conn.query_row(
+            "SELECT state, rejection_reason, rejection_note FROM applications
+            WHERE candidate_id = (?1) AND job_id = (?2)",
+            [candidate_id, job_id],
+            |row| {
+                Ok(Application {
+                    job_id,
+                    candidate_id,
+                    stage: row.get(0)?,
+                    rejection: read_rejection(row, 1)?,
+                })
+            },
+        )?)
+    }
+
+    fn add_job_posting(&self, job: &Job) -> StoreResult<Id> {
+        let state = job.state as u8;
+        self.conn.execute(
+            "INSERT INTO jobs (name, state, pipeline_id, headcount, description, department, location, workplace,
+                employment_type, seniority, salary_min, salary_max, salary_currency, opens_at, closes_at, created_at)
+            values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            params![
+                job.name,
+                state,
+                job.pipeline_id,
+                job.headcount,
+                job.details.description,
+                job.details.department,
+                job.details.location,
+                job.details.workplace as u8,
+                job.details.employment_type as u8,
+                job.details.seniority.map(|seniority| seniority as u8),
+                job.details.salary.as_ref().map(|salary| salary.min as i64),
+                job.details.salary.as_ref().map(|salary| salary.max as i64),
+                job.details.salary.as_ref().map(|salary| &salary.currency),
+                job.opens_at,
+                job.closes_at,
+                job.created_at
+            ],
+        )?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    fn list_jobs(&self) -> StoreResult<Vec<Job>> {
+        self.query_jobs("jobs", [])
+    }
+
+    fn list_jobs_page(&self, page: usize, page_size: usize) -> StoreResult<Vec<Job>> {
+        self.query_jobs(
+            "(SELECT * FROM jobs ORDER BY id LIMIT (?1) OFFSET (?2)) AS jobs",
+            [
+                i64::try_from(page_size).unwrap_or(i64::MAX),
+                i64::try_from(page.saturating_mul(page_size)).unwrap_or(i64::MAX),
+            ],
+        )
+    }
+
+    // Note: This doesn't actually construct a Job because it doesn't contain the applicants
+    // This, is more efficient since I never need the applicant when getting a job by ID.
+    fn get_job_by_id(&self, job_id: Id) -> StoreResult<Job> {
+        Ok(self.conn.query_row(
+            &format!(
+                "SELECT name, state, pipeline_id, headcount, {}, {}, opens_at, closes_at, created_at FROM jobs where id = (?1)",
+                OFFER_COUNTS, DETAILS_COLUMNS
+            ),
+            [job_id],
+            |row| {
+                Ok(Job {
+                    id: job_id,
+                    name: row.get(0)?,
+                    state: read_enum(row, 1, "job state")?,
+                    applicants: Default::default(),
+                    rejections: Default::default(),
+                    headcount: row.get(3)?,
+                    hired: row.get(4)?,
+                    offered: row.get(5)?,
+                    pipeline_id: row.get(2)?,
+                    details: read_details(row, 6)?,
+                    opens_at: row.get(15)?,
+                    closes_at: row.get(16)?,
+                    created_at: row.get(17)?,
+                })
+            },
+        )?)
+    }
+
+    fn get_job_with_applicants(&self, job_id: Id) -> StoreResult<Job> {
+        self.query_jobs("(SELECT * FROM jobs WHERE id = (?1)) AS jobs", [job_id])?
+            .pop()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn add_candidate(&self, candidate: &Candidate) -> StoreResult<usize> {
+        let (algorithm, iterations, salt, hash) = candidate.password.to_parts();
+        Ok(self.conn.execute(
+            "INSERT INTO candidates (name, password, password_algorithm, password_iterations, password_salt, role)
+            values (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                &candidate.user,
+                hash,
+                algorithm,
+                iterations,
+                salt,
+                candidate.role as u8
+            ],
+        )?)
+    }
+
+    fn get_candidate(&self, candidate_name: &str) -> StoreResult<Candidate> {
+        Ok(self.conn.query_row(
+            "SELECT id, name, password, password_algorithm, password_iterations, password_salt, role
+            FROM candidates WHERE name = (?1)",
+            [candidate_name],
+            |row| {
+                let algorithm: String = row.get(3)?;
+                Ok(Candidate {
+                    id: row.get(0)?,
+                    user: row.get(1)?,
+                    password: PasswordHash::from_parts(
+                        &algorithm,
+                        row.get(4)?,
+                        row.get(5)?,
+                        row.get(2)?,
+                    )
+                    .ok_or_else(|| {
+                        rusqlite::Error::FromSqlConversionFailure(
+                            3,
+                            rusqlite::types::Type::Text,
+                            format!("Unknown password algorithm {}", algorithm).into(),
+                        )
+                    })?,
+                    role: read_enum(row, 6, "role")?,
+                })
+            },
+        )?)
+    }
+
+    fn update_candidate(&self, candidate: &Candidate) -> StoreResult<usize> {
+        let (algorithm, iterations, salt, hash) = candidate.password.to_parts();
+        Ok(self.conn.execute(
+            "UPDATE candidates SET name = (?1), password = (?2), password_algorithm = (?3),
+            password_iterations = (?4), password_salt = (?5), role = (?6) where id = (?7)",
+            params![
+                &candidate.user,
+                hash,
+                algorithm,
+                iterations,
+                salt,
+                candidate.role as u8,
+                candidate.id
+            ],
+        )?)
+    }
+
+    fn count_by_role(&self, role: Role) -> StoreResult<usize> {
+        Ok(self.conn.query_row(
+            "SELECT count(*) FROM candidates WHERE role = (?1)",
+            [role as u8],
+            |row| row.get(0),
+        )?)
+    }
+
+    fn update_job_posting(&self, job: &Job) -> StoreResult<usize> {
+        let state = job.state as u8;
+        Ok(self.conn.execute(
+            "UPDATE jobs SET name = (?1), state = (?2), pipeline_id = (?3), headcount = (?4), description = (?6),
+                department = (?7), location = (?8), workplace = (?9), employment_type = (?10), seniority = (?11),
+                salary_min = (?12), salary_max = (?13), salary_currency = (?14), opens_at = (?15), closes_at = (?16)
+            where id = (?5)",
+            params![
+                job.name,
+                state,
+                job.pipeline_id,
+                job.headcount,
+                job.id,
+                job.details.description,
+                job.details.department,
+                job.details.location,
+                job.details.workplace as u8,
+                job.details.employment_type as u8,
+                job.details.seniority.map(|seniority| seniority as u8),
+                job.details.salary.as_ref().map(|salary| salary.min as i64),
+                job.details.salary.as_ref().map(|salary| salary.max as i64),
+                job.details.salary.as_ref().map(|salary| &salary.currency),
+                job.opens_at,
+                job.closes_at
+            ],
+        )?)
+    }
+
+    // Note: The stage is kept in the `state` column, the old states are the default pipeline's positions
+    fn insert_application(
+        &self,
+        application: &Application,
+        answers: &[(usize, Answer)],
+    ) -> StoreResult<Id> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO applications (job_id, candidate_id, state, rejection_reason, rejection_note)
+            values (?1, ?2, ?3, ?4, ?5)",
+            params![
+                &application.job_id,
+                &application.candidate_id,
+                application.stage as i64,
+                application
+                    .rejection
+                    .as_ref()
+                    .map(|rejection| &rejection.reason),
+                application
+                    .rejection
+                    .as_ref()
+                    .map(|rejection| &rejection.note)
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        // Multiple choices are joined by new lines
+        for (position, answer) in answers.iter() {
+            let (text, number) = match answer {
+                Answer::Text(text) | Answer::Choice(text) => (Some(text.clone()), None),
+                Answer::Choices(choices) => (Some(choices.join("\n")), None),
+                Answer::YesNo(answer) => (None, Some(if *answer { 1.0 } else { 0.0 })),
+                Answer::Number(number) => (None, Some(*number)),
+            };
+            tx.execute(
+                "INSERT INTO answers (job_id, candidate_id, question, kind, text, number) values (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    application.job_id,
+                    application.candidate_id,
+                    *position as i64,
+                    answer.kind() as u8,
+                    text,
+                    number
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    fn update_application(&self, application: &Application) -> StoreResult<usize> {
+        Ok(self.conn.execute(
+            "UPDATE  applications SET state = (?3), rejection_reason = (?4), rejection_note = (?5)
+            WHERE job_id = (?1) AND candidate_id = (?2)",
+            params![
+                &application.job_id,
+                &application.candidate_id,
+                application.stage as i64,
+                application
+                    .rejection
+                    .as_ref()
+                    .map(|rejection| &rejection.reason),
+                application
+                    .rejection
+                    .as_ref()
+                    .map(|rejection| &rejection.note)
+            ],
+        )?)
+    }
+
+    fn add_pipeline(&self, pipeline: &Pipeline) -> StoreResult<Id> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("INSERT INTO pipelines (name) values (?1)", [&pipeline.name])?;
+        let id = tx.last_insert_rowid();
+        for (position, stage) in pipeline.stages.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO pipeline_stages (pipeline_id, position, name, outcome) values (?1, ?2, ?3, ?4)",
+                params![
+                    id,
+                    position as i64,
+                    &stage.name,
+                    stage.outcome.map(|outcome| outcome as u8)
+                ],
+            )?;
+        }
+        for (from, stage) in pipeline.stages.iter().enumerate() {
+            for next in stage.next.iter() {
+                tx.execute(
+                    "INSERT INTO pipeline_transitions (pipeline_id, from_position, to_position)
+                    SELECT (?1), (?2), position FROM pipeline_stages WHERE pipeline_id = (?1) AND name = (?3)",
+                    params![id, from as i64, next],
+                )?;
+            }
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    fn get_pipeline(&self, pipeline_id: Id) -> StoreResult<Pipeline> {
+        let name = self.conn.query_row(
+            "SELECT name FROM pipelines WHERE id = (?1)",
+            [pipeline_id],
+            |row| row.get(0),
+        )?;
+        self.read_pipeline(pipeline_id, name)
+    }
+
+    fn list_pipelines(&self) -> StoreResult<Vec<Pipeline>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, name FROM pipelines ORDER BY id")?;
+        let pipelines = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        pipelines
+            .into_iter()
+            .map(|(id, name)| self.read_pipeline(id, name))
+            .collect()
+    }
+
+    fn add_interview(&self, interview: &Interview) -> StoreResult<Id> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO interviews (job_id, candidate_id, round, starts_at, duration, status)
+            values (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                interview.job_id,
+                interview.candidate_id,
+                interview.round,
+                interview.starts_at,
+                interview.duration,
+                interview.status as u8
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        self.insert_interviewers(id, &interview.interviewers)?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    fn get_interview(&self, interview_id: Id) -> StoreResult<Interview> {
+        self.query_interviews("id = (?1)", [interview_id])?
+            .pop()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn update_interview(&self, interview: &Interview) -> StoreResult<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let updated = tx.execute(
+            "UPDATE interviews SET job_id = (?1), candidate_id = (?2), round = (?3), starts_at = (?4),
+            duration = (?5), status = (?6) WHERE id = (?7)",
+            params![
+                interview.job_id,
+                interview.candidate_id,
+                interview.round,
+                interview.starts_at,
+                interview.duration,
+                interview.status as u8,
+                interview.id
+            ],
+        )?;
+        if updated > 0 {
+            tx.execute(
+                "DELETE FROM interview_interviewers WHERE interview_id = (?1)",
+                [interview.id],
+            )?;
+            self.insert_interviewers(interview.id, &interview.interviewers)?;
+        }
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    fn list_interviews(&self, filter: &InterviewFilter) -> StoreResult<Vec<Interview>> {
+        self.query_interviews(
+            "(?1 IS NULL OR job_id = (?1)) AND (?2 IS NULL OR candidate_id = (?2))
+            AND (?3 IS NULL OR id IN (SELECT interview_id FROM interview_interviewers WHERE interviewer_id = (?3)))",
+            params![filter.job_id, filter.candidate_id, filter.interviewer_id],
+        )
+    }
+
+    fn add_scorecard(&self, scorecard: &Scorecard) -> StoreResult<Id> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO scorecards (job_id, candidate_id, interviewer_id, interview_id, recommendation, notes, submitted_at)
+            values (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                scorecard.job_id,
+                scorecard.candidate_id,
+                scorecard.interviewer_id,
+                scorecard.interview_id,
+                scorecard.recommendation as u8,
+                &scorecard.notes,
+                scorecard.submitted_at
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        for rating in scorecard.ratings.iter() {
+            tx.execute(
+                "INSERT INTO scorecard_ratings (scorecard_id, competency, score) values (?1, ?2, ?3)",
+                params![id, &rating.competency, rating.score],
+            )?;
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    // Note: The profile is replaced by deleting the old rows, positions keep the order of the lists.
+    // Its search row is replaced along with it
+    fn set_profile(&self, profile: &Profile) -> StoreResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM candidates_search WHERE rowid = (?1)",
+            [profile.candidate_id],
+        )?;
+        for table in ["profile_skills", "work_experience", "education", "profiles"].iter() {
+            tx.execute(
+                &format!("DELETE FROM {} WHERE candidate_id = (?1)", table),
+                [profile.candidate_id],
+            )?;
+        }
+        tx.execute(
+            "INSERT INTO profiles (candidate_id, full_name, email, phone, location) values (?1, ?2, ?3, ?4, ?5)",
+            params![
+                profile.candidate_id,
+                &profile.full_name,
+                &profile.email,
+                &profile.phone,
+                &profile.location
+            ],
+        )?;
+        for (position, skill) in profile.skills.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO profile_skills (candidate_id, position, name, proficiency) values (?1, ?2, ?3, ?4)",
+                params![
+                    profile.candidate_id,
+                    position as i64,
+                    &skill.name,
+                    skill.proficiency as u8
+                ],
+            )?;
+        }
+        for (position, job) in profile.experience.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO work_experience (candidate_id, position, company, title, start_month, end_month, description)
+                values (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    profile.candidate_id,
+                    position as i64,
+                    &job.company,
+                    &job.title,
+                    month_to_sql(job.start),
+                    job.end.map(month_to_sql),
+                    &job.description
+                ],
+            )?;
+        }
+        for (position, degree) in profile.education.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO education (candidate_id, position, institution, degree, start_month, end_month)
+                values (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    profile.candidate_id,
+                    position as i64,
+                    &degree.institution,
+                    &degree.degree,
+                    month_to_sql(degree.start),
+                    degree.end.map(month_to_sql)
+                ],
+            )?;
+        }
+        let joined = |parts: Vec<&str>| parts.join(" ");
+        tx.execute(
+            "INSERT INTO candidates_search (rowid, full_name, location, skills, experience, education)
+            values (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                profile.candidate_id,
+                &profile.full_name,
+                &profile.location,
+                joined(profile.skills.iter().map(|skill| skill.name.as_str()).collect()),
+                joined(
+                    profile
+                        .experience
+                        .iter()
+                        .flat_map(|job| vec![job.title.as_str(), &job.company, &job.description])
+                        .collect()
+                ),
+                joined(
+                    profile
+                        .education
+                        .iter()
+                        .flat_map(|degree| vec![degree.degree.as_str(), &degree.institution])
+                        .collect()
+                )
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_profile(&self, candidate_id: Id) -> StoreResult<Profile> {
+        let mut profile = self.conn.query_row(
+            "SELECT full_name, email, phone, location FROM profiles WHERE candidate_id = (?1)",
+            [candidate_id],
+            |row| {
+                Ok(Profile {
+                    candidate_id,
+                    full_name: row.get(0)?,
+                    email: row.get(1)?,
+                    phone: row.get(2)?,
+                    location: row.get(3)?,
+                    ..Profile::default()
+                })
+            },
+        )?;
+        profile.skills = self
+            .conn
+            .prepare(
+                "SELECT name, proficiency FROM profile_skills WHERE candidate_id = (?1) ORDER BY position",
+            )?
+            .query_map([candidate_id], |row| {
+                Ok(Skill {
+                    name: row.get(0)?,
+                    proficiency: read_enum(row, 1, "proficiency")?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        profile.experience = self
+            .conn
+            .prepare(
+                "SELECT company, title, start_month, end_month, description FROM work_experience
+                WHERE candidate_id = (?1) ORDER BY position",
+            )?
+            .query_map([candidate_id], |row| {
+                Ok(WorkExperience {
+                    company: row.get(0)?,
+                    title: row.get(1)?,
+                    start: month_from_sql(row.get(2)?),
+                    end: row.get::<_, Option<i64>>(3)?.map(month_from_sql),
+                    description: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        profile.education = self
+            .conn
+            .prepare(
+                "SELECT institution, degree, start_month, end_month FROM education
+                WHERE candidate_id = (?1) ORDER BY position",
+            )?
+            .query_map([candidate_id], |row| {
+                Ok(Education {
+                    institution: row.get(0)?,
+                    degree: row.get(1)?,
+                    start: month_from_sql(row.get(2)?),
+                    end: row.get::<_, Option<i64>>(3)?.map(month_from_sql),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(profile)
+    }
+
+    fn list_scorecards(&self, job_id: Id, candidate_id: Id) -> StoreResult<Vec<Scorecard>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, interviewer_id, interview_id, recommendation, notes, submitted_at FROM scorecards
+            WHERE job_id = (?1) AND candidate_id = (?2) ORDER BY id",
+        )?;
+        let mut scorecards = stmt
+            .query_map([job_id, candidate_id], |row| {
+                Ok(Scorecard {
+                    id: row.get(0)?,
+                    job_id,
+                    candidate_id,
+                    interviewer_id: row.get(1)?,
+                    interview_id: row.get(2)?,
+                    ratings: Vec::new(),
+                    recommendation: read_enum(row, 3, "recommendation")?,
+                    notes: row.get(4)?,
+                    submitted_at: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT competency, score FROM scorecard_ratings WHERE scorecard_id = (?1) ORDER BY rowid",
+        )?;
+        for scorecard in scorecards.iter_mut() {
+            scorecard.ratings = stmt
+                .query_map([scorecard.id], |row| {
+                    Ok(Rating {
+                        competency: row.get(0)?,
+                        score: row.get(1)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+        }
+        Ok(scorecards)
+    }
+
+    fn add_offer(&self, offer: &Offer) -> StoreResult<Id> {
+        self.conn.execute(
+            "INSERT INTO offers (job_id, candidate_id, salary, start_date, expires_at, status, created_at)
+            values (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                offer.job_id,
+                offer.candidate_id,
+                offer.salary as i64,
+                offer.start_date,
+                offer.expires_at,
+                offer.status as u8,
+                offer.created_at
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    fn get_offer(&self, offer_id: Id) -> StoreResult<Offer> {
+        self.query_offers("id = (?1)", [offer_id])?
+            .pop()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn update_offer(&self, offer: &Offer) -> StoreResult<usize> {
+        Ok(self.conn.execute(
+            "UPDATE offers SET status = (?1) WHERE id = (?2)",
+            params![offer.status as u8, offer.id],
+        )?)
+    }
+
+    fn list_offers(&self, filter: &OfferFilter) -> StoreResult<Vec<Offer>> {
+        self.query_offers(
+            "(?1 IS NULL OR job_id = (?1)) AND (?2 IS NULL OR candidate_id = (?2))",
+            params![filter.job_id, filter.candidate_id],
+        )
+    }
+
+    fn expire_offers(&self, now: Timestamp) -> StoreResult<usize> {
+        Ok(self.conn.execute(
+            "UPDATE offers SET status = (?1) WHERE status IN (?2, ?3) AND expires_at <= (?4)",
+            params![
+                OfferStatus::Expired as u8,
+                OfferStatus::Drafted as u8,
+                OfferStatus::Sent as u8,
+                now
+            ],
+        )?)
+    }
+
+    fn add_attachment(&self, attachment: &Attachment) -> StoreResult<Id> {
+        self.conn.execute(
+            "INSERT INTO attachments (job_id, candidate_id, kind, file_name, mime_type, size, sha256, uploaded_at)
+            values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                attachment.job_id,
+                attachment.candidate_id,
+                attachment.kind as u8,
+                &attachment.file_name,
+                &attachment.mime_type,
+                attachment.size as i64,
+                &attachment.sha256,
+                attachment.uploaded_at
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    fn get_attachment(&self, attachment_id: Id) -> StoreResult<Attachment> {
+        self.query_attachments("id = (?1)", [attachment_id])?
+            .pop()
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn list_attachments(&self, job_id: Id, candidate_id: Id) -> StoreResult<Vec<Attachment>> {
+        self.query_attachments(
+            "job_id = (?1) AND candidate_id = (?2)",
+            [job_id, candidate_id],
+        )
+    }
+
+    fn delete_attachment(&self, attachment_id: Id) -> StoreResult<usize> {
+        Ok(self
+            .conn
+            .execute("DELETE FROM attachments WHERE id = (?1)", [attachment_id])?)
+    }
+
+    fn count_content_references(&self, sha256: &str) -> StoreResult<usize> {
+        Ok(self.conn.query_row(
+            "SELECT count(*) FROM attachments WHERE sha256 = (?1)",
+            [sha256],
+            |row| row.get(0),
+        )?)
+    }
+
+    fn put_content(&self, sha256: &str, content: &[u8]) -> StoreResult<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO attachment_contents (sha256, content) values (?1, ?2)",
+            params![sha256, content],
+        )?;
+        Ok(())
+    }
+
+    fn get_content(&self, sha256: &str) -> StoreResult<Vec<u8>> {
+        Ok(self.conn.query_row(
+            "SELECT content FROM attachment_contents WHERE sha256 = (?1)",
+            [sha256],
+            |row| row.get(0),
+        )?)
+    }
+
+    fn delete_content(&self, sha256: &str) -> StoreResult<usize> {
+        Ok(self.conn.execute(
+            "DELETE FROM attachment_contents WHERE sha256 = (?1)",
+            [sha256],
+        )?)
+    }
+
+    fn set_job_skills(&self, job_id: Id, skills: &[JobSkill]) -> StoreResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        // Without skills nothing is inserted, so the job is checked explicitly
+        let jobs: i64 = tx.query_row(
+            "SELECT count(*) FROM jobs WHERE id = (?1)",
+            [job_id],
+            |row| row.get(0),
+        )?;
+        if jobs == 0 {
+            return Err(StoreError::Constraint(
+                "FOREIGN KEY constraint failed".into(),
+            ));
+        }
+        tx.execute("DELETE FROM job_skills WHERE job_id = (?1)", [job_id])?;
+        for (position, skill) in skills.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO job_skills (job_id, position, name, proficiency, required) values (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    job_id,
+                    position as i64,
+                    &skill.name,
+                    skill.proficiency as u8,
+                    skill.required
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_job_skills(&self, job_id: Id) -> StoreResult<Vec<JobSkill>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, proficiency, required FROM job_skills WHERE job_id = (?1) ORDER BY position",
+        )?;
+        let skills = stmt
+            .query_map([job_id], |row| {
+                Ok(JobSkill {
+                    name: row.get(0)?,
+                    proficiency: read_enum(row, 1, "proficiency")?,
+                    required: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(skills)
+    }
+
+    fn set_questions(&self, job_id: Id, questions: &[Question]) -> StoreResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        // Without questions nothing is inserted, so the job is checked explicitly
+        let jobs: i64 = tx.query_row(
+            "SELECT count(*) FROM jobs WHERE id = (?1)",
+            [job_id],
+            |row| row.get(0),
+        )?;
+        if jobs == 0 {
+            return Err(StoreError::Constraint(
+                "FOREIGN KEY constraint failed".into(),
+            ));
+        }
+        for table in ["question_choices", "questions"].iter() {
+            tx.execute(
+                &format!("DELETE FROM {} WHERE job_id = (?1)", table),
+                [job_id],
+            )?;
+        }
+        for (position, question) in questions.iter().enumerate() {
+            let (knockout, answer, min, max, flagged): (Option<u8>, _, _, _, &[String]) =
+                match &question.knockout {
+                    None => (None, None, None, None, &[]),
+                    Some(Knockout::Requires(answer)) => (Some(0), Some(*answer), None, None, &[]),
+                    Some(Knockout::AnyOf(choices)) => (Some(1), None, None, None, choices),
+                    Some(Knockout::AllOf(choices)) => (Some(2), None, None, None, choices),
+                    Some(Knockout::Range { min, max }) => (Some(3), None, *min, *max, &[]),
+                };
+            tx.execute(
+                "INSERT INTO questions (job_id, position, text, kind, required, knockout, knockout_answer, knockout_min, knockout_max)
+                values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    job_id,
+                    position as i64,
+                    &question.text,
+                    question.kind as u8,
+                    question.required,
+                    knockout,
+                    answer,
+                    min,
+                    max
+                ],
+            )?;
+            for (i, choice) in question.choices.iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO question_choices (job_id, question, position, text, knockout) values (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        job_id,
+                        position as i64,
+                        i as i64,
+                        choice,
+                        flagged.contains(choice)
+                    ],
+                )?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_questions(&self, job_id: Id) -> StoreResult<Vec<Question>> {
+        let choices = self
+            .conn
+            .prepare(
+                "SELECT question, text, knockout FROM question_choices WHERE job_id = (?1) ORDER BY question, position",
+            )?
+            .query_map([job_id], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, bool>(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut stmt = self.conn.prepare(
+            "SELECT position, text, kind, required, knockout, knockout_answer, knockout_min, knockout_max FROM questions
+            WHERE job_id = (?1) ORDER BY position",
+        )?;
+        let questions = stmt
+            .query_map([job_id], |row| {
+                let position: i64 = row.get(0)?;
+                let of_question = choices
+                    .iter()
+                    .filter(|(question, ..)| *question == position);
+                let flagged = of_question
+                    .clone()
+                    .filter(|(.., knockout)| *knockout)
+                    .map(|(_, choice, _)| choice.clone())
+                    .collect();
+                let knockout = match row.get::<_, Option<u8>>(4)? {
+                    None => None,
+                    Some(0) => Some(Knockout::Requires(row.get(5)?)),
+                    Some(1) => Some(Knockout::AnyOf(flagged)),
+                    Some(2) => Some(Knockout::AllOf(flagged)),
+                    Some(3) => Some(Knockout::Range {
+                        min: row.get(6)?,
+                        max: row.get(7)?,
+                    }),
+                    Some(i) => {
+                        return Err(rusqlite::Error::FromSqlConversionFailure(
+                            4,
+                            rusqlite::types::Type::Integer,
+                            format!("Unknown knockout rule {}", i).into(),
+                        ))
+                    }
+                };
+                Ok(Question {
+                    text: row.get(1)?,
+                    kind: read_enum(row, 2, "question kind")?,
+                    choices: of_question.map(|(_, choice, _)| choice.clone()).collect(),
+                    required: row.get(3)?,
+                    knockout,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(questions)
+    }
+
+    fn get_answers(&self, job_id: Id, candidate_id: Id) -> StoreResult<Vec<(usize, Answer)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT question, kind, text, number FROM answers WHERE job_id = (?1) AND candidate_id = (?2) ORDER BY question",
+        )?;
+        let answers = stmt
+            .query_map([job_id, candidate_id], |row| {
+                let text: Option<String> = row.get(2)?;
+                let number: Option<f64> = row.get(3)?;
+                let answer = match read_enum(row, 1, "question kind")? {
+                    QuestionKind::FreeText => Answer::Text(text.unwrap_or_default()),
+                    QuestionKind::SingleChoice => Answer::Choice(text.unwrap_or_default()),
+                    QuestionKind::MultipleChoice => Answer::Choices(
+                        text.unwrap_or_default()
+                            .split('\n')
+                            .map(String::from)
+                            .collect(),
+                    ),
+                    QuestionKind::YesNo => Answer::YesNo(number == Some(1.0)),
+                    QuestionKind::Numeric => Answer::Number(number.unwrap_or_default()),
+                };
+                Ok((row.get::<_, i64>(0)? as usize, answer))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(answers)
+    }
+
+    // Note: Matches are ranked by FTS5's bm25, the IDs found are then joined with their applicants
+    fn search_jobs(&self, search: &JobSearch) -> StoreResult<Vec<Job>> {
+        let query = search::words(&search.query);
+        // Without words there's nothing to match, ?1 is then the empty string
+        let (from, matching, order) = if query.is_empty() {
+            ("jobs", "(?1) = ''", "jobs.id")
+        } else {
+            (
+                "jobs_search JOIN jobs ON jobs.id = jobs_search.rowid",
+                "jobs_search MATCH (?1)",
+                "jobs_search.rank, jobs.id",
+            )
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT jobs.id FROM {}
+            WHERE {}
+                AND (?2 IS NULL OR jobs.state = (?2))
+                AND (?3 IS NULL OR lower(trim(jobs.department)) = lower(trim(?3)))
+                AND (?4 IS NULL OR instr(lower(jobs.location), lower(trim(?4))) > 0)
+                AND (?5 IS NULL OR jobs.created_at >= (?5))
+                AND (?6 IS NULL OR jobs.created_at < (?6))
+            ORDER BY {}",
+            from, matching, order
+        ))?;
+        let ids = stmt
+            .query_map(
+                params![
+                    fts_query(&query),
+                    search.state.map(|state| state as u8),
+                    &search.department,
+                    &search.location,
+                    search.posted_after,
+                    search.posted_before
+                ],
+                |row| row.get::<_, Id>(0),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids_list = ids.iter().map(Id::to_string).collect::<Vec<_>>().join(", ");
+        let mut jobs = self.query_jobs(
+            &format!("(SELECT * FROM jobs WHERE id IN ({})) AS jobs", ids_list),
+            [],
+        )?;
+        jobs.sort_by_key(|job| ids.iter().position(|id| *id == job.id));
+        Ok(jobs)
+    }
+
+    fn search_candidates(&self, search: &CandidateSearch) -> StoreResult<Vec<(String, Profile)>> {
+        let query = search::words(&search.query);
+        let (from, matching, order) = if query.is_empty() {
+            ("profiles", "(?1) = ''", "profiles.candidate_id")
+        } else {
+            (
+                "candidates_search JOIN profiles ON profiles.candidate_id = candidates_search.rowid",
+                "candidates_search MATCH (?1)",
+                "candidates_search.rank, profiles.candidate_id",
+            )
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT candidates.name, profiles.candidate_id FROM {}
+            JOIN candidates ON candidates.id = profiles.candidate_id
+            WHERE {}
+                AND (?2 IS NULL OR instr(lower(profiles.location), lower(trim(?2))) > 0)
+            ORDER BY {}",
+            from, matching, order
+        ))?;
+        let found = stmt
+            .query_map(params![fts_query(&query), &search.location], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Id>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        found
+            .into_iter()
+            .map(|(user, candidate_id)| Ok((user, self.get_profile(candidate_id)?)))
+            .collect()
+    }
+
+    fn insert_session(&self, session: &Session) -> StoreResult<()> {
+        self.conn.execute(
+            "INSERT INTO sessions (id, user_id, issued_at, expires_at, revoked) values (?1, ?2, ?3, ?4, ?5)",
+            params![
+                &session.id,
+                session.user_id,
+                session.issued_at,
+                session.expires_at,
+                session.revoked
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn get_session(&self, session_id: &str) -> StoreResult<Session> {
+        Ok(self.conn.query_row(
+            "SELECT id, user_id, issued_at, expires_at, revoked FROM sessions WHERE id = (?1)",
+            [session_id],
+            |row| {
+                Ok(Session {
+                    id: row.get(0)?,
+                    user_id: row.get(1)?,
+                    issued_at: row.get(2)?,
+                    expires_at: row.get(3)?,
+                    revoked: row.get(4)?,
+                })
+            },
+        )?)
+    }
+
+    fn revoke_session(&self, session_id: &str) -> StoreResult<usize> {
+        Ok(self.conn.execute(
+            "UPDATE sessions SET revoked = 1 WHERE id = (?1)",
+            [session_id],
+        )?)
+    }
+
+    fn get_signing_key(&self) -> StoreResult<Option<Vec<u8>>> {
+        match self.conn.query_row(
+            "SELECT value FROM settings WHERE name = 'signing_key'",
+            [],
+            |row| row.get(0),
+        ) {
+            Ok(key) => Ok(Some(key)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_signing_key(&self, key: &[u8]) -> StoreResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO settings (name, value) values ('signing_key', ?1)",
+            [key],
+        )?;
+        Ok(())
+    }
+}
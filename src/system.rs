@@ -0,0 +1,1923 @@
+//! `HRSystem`, the instance based API of the system
+//! Each instance owns its store, signing key and clock so many can live in the same process.
+use crate::attachment::{self, Attachment, ContentDir, DocumentKind};
+use crate::auth::{Action, Role};
+use crate::clock::{Clock, SystemClock, Timestamp};
+use crate::config::{Backend, Config, ConfigError};
+use crate::interview::{Interview, InterviewFilter, InterviewStatus};
+use crate::matching::{self, JobSkill, MatchScore};
+use crate::offer::{self, Offer, OfferFilter, OfferStatus};
+use crate::pipeline::{self, Outcome, Pipeline};
+use crate::posting::JobDetails;
+use crate::profile::{Profile, YearMonth};
+use crate::questionnaire::{self, Answer, Question};
+use crate::report::{JobReport, MaintenanceReport};
+use crate::resume::{self, ParsedResume};
+use crate::scorecard::{Scorecard, ScorecardSummary};
+use crate::search::{CandidateSearch, JobSearch};
+use crate::session::{Session, MAX_SESSION_TTL};
+#[cfg(feature = "sqlite")]
+use crate::SqliteStore;
+use crate::{
+    Application, Candidate, ErrorVariant, Id, Job, JobState, LoggedUser, MemoryStore, PasswordHash,
+    Rejection, Store, StoreError, StoreResult,
+};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// The system logic, generic over the storage backend
+/// It's `Send + Sync` as long as the store is `Send`, the store is only accessed behind a `Mutex`
+pub struct HRSystem<S> {
+    // Note: The Mutex is needed because the SQL library I'm using is not thread safe
+    store: Mutex<S>,
+    key: ring::hmac::Key,
+    clock: Arc<dyn Clock>,
+    page_size: usize,
+    password_iterations: u32,
+    session_ttl: i64,
+    require_scorecards: bool,
+    rejection_reasons: Vec<String>,
+    attachments_dir: Option<PathBuf>,
+    max_attachment_size: u64,
+    attachment_types: Vec<String>,
+    skill_dictionary: Vec<String>,
+}
+
+/// Gives `StoreError::NotFound` its meaning for the operation, other errors are kept as `Storage`
+trait OrNotFound<T> {
+    fn or_not_found<F: FnOnce() -> ErrorVariant>(self, error: F) -> Result<T, ErrorVariant>;
+}
+
+impl<T> OrNotFound<T> for StoreResult<T> {
+    fn or_not_found<F: FnOnce() -> ErrorVariant>(self, error: F) -> Result<T, ErrorVariant> {
+        self.map_err(|e| match e {
+            StoreError::NotFound => error(),
+            e => e.into(),
+        })
+    }
+}
+
+type StoreOpener<S> = Box<dyn FnOnce() -> StoreResult<S>>;
+
+/// Builder for `HRSystem`, the store is required(either directly or through `db_path`)
+/// Everything else defaults to `Config::default()` and `SystemClock`
+pub struct HRSystemBuilder<S> {
+    store: Option<StoreOpener<S>>,
+    secret: Option<Vec<u8>>,
+    clock: Arc<dyn Clock>,
+    page_size: usize,
+    password_iterations: u32,
+    session_ttl: i64,
+    require_scorecards: bool,
+    rejection_reasons: Vec<String>,
+    attachments_dir: Option<PathBuf>,
+    max_attachment_size: u64,
+    attachment_types: Vec<String>,
+    skill_dictionary: Vec<String>,
+}
+
+impl<S: Store + 'static> HRSystemBuilder<S> {
+    pub fn new() -> Self {
+        Self {
+            store: None,
+            secret: None,
+            clock: Arc::new(SystemClock),
+            page_size: Config::default().page_size,
+            password_iterations: Config::default().password_iterations,
+            session_ttl: Config::default().session_ttl,
+            require_scorecards: Config::default().require_scorecards,
+            rejection_reasons: Config::default().rejection_reasons,
+            attachments_dir: Config::default().attachments_dir,
+            max_attachment_size: Config::default().max_attachment_size,
+            attachment_types: Config::default().attachment_types,
+            skill_dictionary: Config::default().skill_dictionary,
+        }
+    }
+
+    /// Takes every setting but the store from `config`, settings set after this override it
+    /// Note: The store isn't taken from the config, see `HRSystem::from_config` for that
+    pub fn config(mut self, config: &Config) -> Result<Self, ConfigError> {
+        self.secret = config.secret.load()?;
+        self.page_size = config.page_size;
+        self.password_iterations = config.password_iterations;
+        self.session_ttl = config.session_ttl;
+        self.require_scorecards = config.require_scorecards;
+        self.rejection_reasons = config.rejection_reasons.clone();
+        self.attachments_dir = config.attachments_dir.clone();
+        self.max_attachment_size = config.max_attachment_size;
+        self.attachment_types = config.attachment_types.clone();
+        self.skill_dictionary = config.skill_dictionary.clone();
+        Ok(self)
+    }
+
+    pub fn store(mut self, store: S) -> Self {
+        self.store = Some(Box::new(move || Ok(store)));
+        self
+    }
+
+    /// Key used to sign the tokens, tokens are only valid for the system that signed them
+    /// Without it a random key is generated on first use and kept in the store, so sessions survive restarts
+    pub fn secret(mut self, secret: &[u8]) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Number of jobs per page in `HRSystem::list_jobs_page`, should be at least 1
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// PBKDF2 iterations for new password hashes, weaker hashes are upgraded on login
+    pub fn password_iterations(mut self, iterations: u32) -> Self {
+        self.password_iterations = iterations;
+        self
+    }
+
+    /// Seconds a session lasts after login, between 1 and `MAX_SESSION_TTL`
+    pub fn session_ttl(mut self, seconds: i64) -> Self {
+        self.session_ttl = seconds;
+        self
+    }
+
+    /// Whether an application needs at least one scorecard before it can be approved or rejected
+    pub fn require_scorecards(mut self, required: bool) -> Self {
+        self.require_scorecards = required;
+        self
+    }
+
+    /// Reason codes a rejection can use, should have at least one
+    pub fn rejection_reasons(mut self, reasons: &[&str]) -> Self {
+        self.rejection_reasons = reasons.iter().map(|reason| reason.to_string()).collect();
+        self
+    }
+
+    /// Keeps the attached documents in `dir` instead of the store, it's created when needed
+    pub fn attachments_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.attachments_dir = Some(dir.into());
+        self
+    }
+
+    /// Largest document that can be attached in bytes, should be at least 1
+    pub fn max_attachment_size(mut self, bytes: u64) -> Self {
+        self.max_attachment_size = bytes;
+        self
+    }
+
+    /// MIME types of the documents that can be attached
+    pub fn attachment_types(mut self, types: &[&str]) -> Self {
+        self.attachment_types = types
+            .iter()
+            .map(|mime_type| mime_type.to_string())
+            .collect();
+        self
+    }
+
+    /// Skills the résumé parser looks for, matched ignoring case
+    pub fn skill_dictionary(mut self, skills: &[&str]) -> Self {
+        self.skill_dictionary = skills.iter().map(|skill| skill.to_string()).collect();
+        self
+    }
+
+    pub fn build(self) -> StoreResult<HRSystem<S>> {
+        let open = self
+            .store
+            .ok_or_else(|| StoreError::Backend("No store configured for HRSystem".into()))?;
+        let store = open()?;
+        let secret = match self.secret {
+            Some(secret) => secret,
+            None => match store.get_signing_key()? {
+                Some(secret) => secret,
+                None => {
+                    let secret = random_secret();
+                    store.set_signing_key(&secret)?;
+                    secret
+                }
+            },
+        };
+        Ok(HRSystem {
+            store: Mutex::new(store),
+            key: ring::hmac::Key::new(ring::hmac::HMAC_SHA256, &secret),
+            clock: self.clock,
+            page_size: self.page_size.max(1),
+            password_iterations: self.password_iterations.max(1),
+            session_ttl: self.session_ttl.clamp(1, MAX_SESSION_TTL),
+            require_scorecards: self.require_scorecards,
+            rejection_reasons: self.rejection_reasons,
+            attachments_dir: self.attachments_dir,
+            max_attachment_size: self.max_attachment_size.max(1),
+            attachment_types: self.attachment_types,
+            skill_dictionary: self.skill_dictionary,
+        })
+    }
+}
+
+impl<S: Store + 'static> Default for HRSystemBuilder<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl HRSystemBuilder<SqliteStore> {
+    /// Opens(or creates) the SQLite DB at `path` when building
+    pub fn db_path<P: AsRef<std::path::Path> + 'static>(mut self, path: P) -> Self {
+        self.store = Some(Box::new(move || SqliteStore::open(path)));
+        self
+    }
+}
+
+impl HRSystem<Box<dyn Store + Send>> {
+    /// A system with the backend and settings given by `config`
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let store: Box<dyn Store + Send> = match config.backend {
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite => Box::new(SqliteStore::open(&config.db_path)?),
+            #[cfg(not(feature = "sqlite"))]
+            Backend::Sqlite => {
+                return Err(ConfigError::Invalid(
+                    "The sqlite backend requires the sqlite feature".into(),
+                ))
+            }
+            Backend::Memory => Box::new(MemoryStore::new()),
+        };
+        Ok(Self::builder().config(config)?.store(store).build()?)
+    }
+}
+
+fn random_secret() -> Vec<u8> {
+    let secret: [u8; ring::digest::SHA256_OUTPUT_LEN] =
+        ring::rand::generate(&ring::rand::SystemRandom::new())
+            .unwrap()
+            .expose();
+    secret.into()
+}
+
+impl<S: Store> HRSystem<S> {
+    /// A system using `store` with the key kept in the store and the system clock
+    pub fn new(store: S) -> Self
+    where
+        S: 'static,
+    {
+        Self::builder()
+            .store(store)
+            .build()
+            .expect("The store is already opened")
+    }
+
+    pub fn builder() -> HRSystemBuilder<S>
+    where
+        S: 'static,
+    {
+        HRSystemBuilder::new()
+    }
+
+    /// Current time according to the system's clock
+    pub fn now(&self) -> Timestamp {
+        self.clock.now()
+    }
+
+    fn store(&self) -> MutexGuard<'_, S> {
+        self.store.lock().unwrap()
+    }
+
+    /// Checks that the token of `principal` was signed by this system and its session is still valid
+    pub fn check_token(&self, principal: &LoggedUser) -> Result<(), ErrorVariant> {
+        self.check_session(&self.store(), principal).map(|_| ())
+    }
+
+    /// Returns the stored user of a valid session
+    fn check_session(&self, store: &S, principal: &LoggedUser) -> Result<Candidate, ErrorVariant> {
+        self.verify_signature(principal)?;
+        let session = store
+            .get_session(&principal.session_id)
+            .or_not_found(|| ErrorVariant::Unauthorized)?;
+        let user = store
+            .get_candidate(&principal.user)
+            .or_not_found(|| ErrorVariant::Unauthorized)?;
+        if session.user_id != user.id || session.revoked || session.is_expired(self.now()) {
+            Err(ErrorVariant::Unauthorized)
+        } else {
+            Ok(user)
+        }
+    }
+
+    fn verify_signature(&self, principal: &LoggedUser) -> Result<(), ErrorVariant> {
+        ring::hmac::verify(
+            &self.key,
+            principal.session_id.as_bytes(),
+            principal.token.as_ref(),
+        )
+        .map_err(|_| ErrorVariant::Unauthorized)
+    }
+
+    /// Checks the session of `principal` and that its role allows `action`
+    /// The role is always read from the store, so role changes apply to already logged users
+    fn authorize(
+        &self,
+        store: &S,
+        principal: &LoggedUser,
+        action: Action,
+    ) -> Result<Candidate, ErrorVariant> {
+        let user = self.check_session(store, principal)?;
+        if user.role.can(action) {
+            Ok(user)
+        } else {
+            Err(ErrorVariant::PermissionDenied(action))
+        }
+    }
+
+    pub fn list_jobs(&self, principal: &LoggedUser) -> Result<Vec<Job>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ListJobs)?;
+        // Expired offers free their slots
+        store.expire_offers(self.now())?;
+        Ok(store.list_jobs()?)
+    }
+
+    /// Returns the `page`th(starting from 0) page of jobs, using the configured page size
+    pub fn list_jobs_page(
+        &self,
+        principal: &LoggedUser,
+        page: usize,
+    ) -> Result<Vec<Job>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ListJobs)?;
+        store.expire_offers(self.now())?;
+        Ok(store.list_jobs_page(page, self.page_size)?)
+    }
+
+    /// Returns the jobs matching the search(see `JobSearch`), most relevant first
+    pub fn search_jobs(
+        &self,
+        principal: &LoggedUser,
+        search: &JobSearch,
+    ) -> Result<Vec<Job>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ListJobs)?;
+        store.expire_offers(self.now())?;
+        Ok(store.search_jobs(search)?)
+    }
+
+    /// Returns the users and profiles of the candidates matching the search(see `CandidateSearch`), most relevant first
+    pub fn search_candidates(
+        &self,
+        principal: &LoggedUser,
+        search: &CandidateSearch,
+    ) -> Result<Vec<(String, Profile)>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ViewProfiles)?;
+        Ok(store.search_candidates(search)?)
+    }
+
+    pub fn create_job_posting(
+        &self,
+        principal: &LoggedUser,
+        name: String,
+    ) -> Result<Id, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::CreateJob)?;
+        Ok(store.add_job_posting(&Job {
+            created_at: self.now(),
+            ..Job::new(name)
+        })?)
+    }
+
+    /// Replaces the details of a job posting, they must be valid(`JobDetails::validate`)
+    pub fn update_job_posting(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+        details: &JobDetails,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::CreateJob)?;
+        details
+            .validate()
+            .map_err(ErrorVariant::InvalidJobDetails)?;
+        let mut job = store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        job.details = details.clone();
+        store.update_job_posting(&job)?;
+        Ok(())
+    }
+
+    /// Replaces the screening questions candidates answer when applying to the job(`apply_with_answers`).
+    /// They can't change once the job has applications, and knockout rules need a Rejected stage in the job's pipeline
+    pub fn set_questionnaire(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+        questions: &[Question],
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::CreateJob)?;
+        questionnaire::validate_questionnaire(questions)
+            .map_err(ErrorVariant::InvalidQuestionnaire)?;
+        let job = store
+            .get_job_with_applicants(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        if !job.applicants.is_empty() {
+            return Err(ErrorVariant::InvalidQuestionnaire(
+                "The job already has applications, their answers wouldn't match".into(),
+            ));
+        }
+        if questions.iter().any(|question| question.knockout.is_some()) {
+            let pipeline = store
+                .get_pipeline(job.pipeline_id)
+                .or_not_found(|| ErrorVariant::PipelineNotFound(job.pipeline_id))?;
+            if pipeline.rejected_stage().is_none() {
+                return Err(ErrorVariant::InvalidQuestionnaire(format!(
+                    "Pipeline {} has no Rejected stage for knockout rules",
+                    pipeline.name
+                )));
+            }
+        }
+        store.set_questions(job_id, questions)?;
+        Ok(())
+    }
+
+    /// Screening questions of the job in the order they're asked
+    pub fn questionnaire(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+    ) -> Result<Vec<Question>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ListJobs)?;
+        store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        Ok(store.get_questions(job_id)?)
+    }
+
+    /// Replaces the skills the job asks for, candidates are matched against them(`rank_applicants`, `suggest_jobs`)
+    pub fn set_job_skills(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+        skills: &[JobSkill],
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::CreateJob)?;
+        matching::validate_job_skills(skills).map_err(ErrorVariant::InvalidJobDetails)?;
+        store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        store.set_job_skills(job_id, skills)?;
+        Ok(())
+    }
+
+    /// Skills the job asks for in the order they were given
+    pub fn job_skills(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+    ) -> Result<Vec<JobSkill>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ListJobs)?;
+        store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        Ok(store.get_job_skills(job_id)?)
+    }
+
+    /// Changes how many candidates the job hires. It can't go below the slots taken by accepted and outstanding offers,
+    /// an open(or on hold) job is filled if that many offers were accepted and a filled job reopens if it has room again
+    pub fn set_headcount(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+        headcount: u32,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::CreateJob)?;
+        store.expire_offers(self.now())?;
+        let mut job = store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        let taken = job.hired + job.offered;
+        if headcount == 0 || headcount < taken {
+            return Err(ErrorVariant::InvalidHeadcount { headcount, taken });
+        }
+        job.headcount = headcount;
+        job.state = filled_state(&job);
+        store.update_job_posting(&job)?;
+        Ok(())
+    }
+
+    /// Adds a job posting as a draft, it doesn't take applications until it's published with `publish_job`.
+    /// Its applications follow the given pipeline, or the default one
+    pub fn create_draft_job_posting(
+        &self,
+        principal: &LoggedUser,
+        name: String,
+        pipeline_id: Option<Id>,
+    ) -> Result<Id, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::CreateJob)?;
+        let pipeline_id = pipeline_id.unwrap_or(pipeline::DEFAULT_PIPELINE_ID);
+        store
+            .get_pipeline(pipeline_id)
+            .or_not_found(|| ErrorVariant::PipelineNotFound(pipeline_id))?;
+        Ok(store.add_job_posting(&Job {
+            pipeline_id,
+            state: JobState::Draft,
+            created_at: self.now(),
+            ..Job::new(name)
+        })?)
+    }
+
+    /// Opens a draft job to applications
+    pub fn publish_job(&self, principal: &LoggedUser, job_id: Id) -> Result<(), ErrorVariant> {
+        self.move_job(principal, job_id, &[JobState::Draft], JobState::Open)
+    }
+
+    /// Puts an open job on hold, it stops taking applications and process changes until it's resumed
+    pub fn pause_job(&self, principal: &LoggedUser, job_id: Id) -> Result<(), ErrorVariant> {
+        self.move_job(principal, job_id, &[JobState::Open], JobState::OnHold)
+    }
+
+    /// Opens a job on hold again
+    pub fn resume_job(&self, principal: &LoggedUser, job_id: Id) -> Result<(), ErrorVariant> {
+        self.move_job(principal, job_id, &[JobState::OnHold], JobState::Open)
+    }
+
+    /// Opens a cancelled job again, filled jobs reopen by raising their headcount(`set_headcount`)
+    pub fn reopen_job(&self, principal: &LoggedUser, job_id: Id) -> Result<(), ErrorVariant> {
+        self.move_job(principal, job_id, &[JobState::Cancelled], JobState::Open)
+    }
+
+    /// Cancels a job that isn't filled yet, offers already sent can only be declined
+    pub fn cancel_job(&self, principal: &LoggedUser, job_id: Id) -> Result<(), ErrorVariant> {
+        self.move_job(
+            principal,
+            job_id,
+            &[
+                JobState::Draft,
+                JobState::Open,
+                JobState::OnHold,
+                JobState::Closed,
+            ],
+            JobState::Cancelled,
+        )
+    }
+
+    /// Sets when the job takes applications, `None` leaves that end open. An open job whose new window
+    /// already ended is closed, and a closed job whose window was extended opens again
+    pub fn set_application_window(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+        opens_at: Option<Timestamp>,
+        closes_at: Option<Timestamp>,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::CreateJob)?;
+        if let (Some(opens_at), Some(closes_at)) = (opens_at, closes_at) {
+            if closes_at <= opens_at {
+                return Err(ErrorVariant::InvalidApplicationWindow {
+                    opens_at,
+                    closes_at,
+                });
+            }
+        }
+        let mut job = store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        job.opens_at = opens_at;
+        job.closes_at = closes_at;
+        let ended = job.window_ended(self.now());
+        job.state = match job.state {
+            JobState::Open | JobState::OnHold if ended => JobState::Closed,
+            JobState::Closed if !ended => JobState::Open,
+            state => state,
+        };
+        store.update_job_posting(&job)?;
+        Ok(())
+    }
+
+    /// Closes the open and on hold jobs whose application window ended and expires the outstanding offers
+    /// past their expiry time. Both happen anyway when needed, running it periodically keeps the stored states current
+    pub fn run_maintenance(
+        &self,
+        principal: &LoggedUser,
+    ) -> Result<MaintenanceReport, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::RunMaintenance)?;
+        let now = self.now();
+        let mut report = MaintenanceReport {
+            expired_offers: store.expire_offers(now)?,
+            ..MaintenanceReport::default()
+        };
+        for mut job in store.list_jobs()? {
+            if matches!(job.state, JobState::Open | JobState::OnHold) && job.window_ended(now) {
+                job.state = JobState::Closed;
+                store.update_job_posting(&job)?;
+                report.closed_jobs.push((job.id, job.name));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Moves the job to `to` if it's in one of the `from` states
+    fn move_job(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+        from: &[JobState],
+        to: JobState,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::CreateJob)?;
+        let mut job = store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        if !from.contains(&job.state) || !job.state.can_move(to) {
+            return Err(ErrorVariant::InvalidJobTransition {
+                job_id,
+                from: job.state,
+                to,
+            });
+        }
+        job.state = to;
+        store.update_job_posting(&job)?;
+        Ok(())
+    }
+
+    /// Same as `create_job_posting` but the applications follow the given pipeline instead of the default one
+    pub fn create_job_posting_with_pipeline(
+        &self,
+        principal: &LoggedUser,
+        name: String,
+        pipeline_id: Id,
+    ) -> Result<Id, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::CreateJob)?;
+        store
+            .get_pipeline(pipeline_id)
+            .or_not_found(|| ErrorVariant::PipelineNotFound(pipeline_id))?;
+        Ok(store.add_job_posting(&Job {
+            pipeline_id,
+            created_at: self.now(),
+            ..Job::new(name)
+        })?)
+    }
+
+    /// Registers a new user with the Candidate role
+    pub fn register_candidate(
+        &self,
+        user: String,
+        password: String,
+    ) -> Result<usize, ErrorVariant> {
+        self.add_user(&self.store(), user, &password, Role::Candidate)
+    }
+
+    fn add_user(
+        &self,
+        store: &S,
+        user: String,
+        password: &str,
+        role: Role,
+    ) -> Result<usize, ErrorVariant> {
+        store
+            .add_candidate(&Candidate {
+                id: Default::default(),
+                password: PasswordHash::new(password, self.password_iterations),
+                user: user.clone(),
+                role,
+            })
+            .map_err(|e| match e {
+                StoreError::Constraint(_) => ErrorVariant::UsernameTaken(user),
+                e => e.into(),
+            })
+    }
+
+    /// Registers the first admin, fails if there's already one.
+    /// Further admins must be promoted by an admin through `set_role`
+    pub fn bootstrap_admin(&self, user: String, password: String) -> Result<usize, ErrorVariant> {
+        let store = self.store();
+        if store.count_by_role(Role::Admin)? > 0 {
+            return Err(ErrorVariant::PermissionDenied(Action::ManageUsers));
+        }
+        self.add_user(&store, user, &password, Role::Admin)
+    }
+
+    /// Changes the role of `user`, only admins can do it
+    pub fn set_role(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+        role: Role,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ManageUsers)?;
+        let mut user = store
+            .get_candidate(user)
+            .or_not_found(|| ErrorVariant::CandidateNotFound(user.to_string()))?;
+        user.role = role;
+        store.update_candidate(&user)?;
+        Ok(())
+    }
+
+    /// Starts a new session for the user that lasts the configured session TTL.
+    /// Passwords hashed with an outdated algorithm are rehashed on a successful login
+    pub fn login(&self, user: &str, password: &str) -> Result<LoggedUser, ErrorVariant> {
+        let store = self.store();
+        let mut candidate = match store.get_candidate(user) {
+            Ok(candidate) => candidate,
+            Err(StoreError::NotFound) => {
+                // Pays for a hash like known users do, so the time taken doesn't reveal who exists
+                PasswordHash::dummy(self.password_iterations).verify(password);
+                return Err(ErrorVariant::Unauthorized);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if !candidate.password.verify(password) {
+            return Err(ErrorVariant::Unauthorized);
+        }
+        if candidate.password.needs_rehash(self.password_iterations) {
+            candidate.password = PasswordHash::new(password, self.password_iterations);
+            // Not being able to upgrade the hash shouldn't stop the user from logging in
+            let _ = store.update_candidate(&candidate);
+        }
+        let session = Session::new(candidate.id, self.now(), self.session_ttl);
+        store.insert_session(&session)?;
+        Ok(LoggedUser {
+            user_id: candidate.id,
+            user: candidate.user,
+            role: candidate.role,
+            token: ring::hmac::sign(&self.key, session.id.as_bytes()),
+            session_id: session.id,
+            expires_at: session.expires_at,
+        })
+    }
+
+    /// Revokes the session of `principal`, its token can't be used anymore
+    pub fn logout(&self, principal: &LoggedUser) -> Result<(), ErrorVariant> {
+        self.verify_signature(principal)?;
+        self.store().revoke_session(&principal.session_id)?;
+        Ok(())
+    }
+
+    /// Applies `principal` to the job, which must be open and within its application window. Returns the application's ID.
+    /// Jobs with required screening questions need `apply_with_answers`
+    pub fn apply(&self, principal: &LoggedUser, job_id: Id) -> Result<Id, ErrorVariant> {
+        self.apply_with_answers(principal, job_id, &[])
+    }
+
+    /// Same as `apply` answering the job's screening questions, each answer paired with the position of its question.
+    /// The answers must fit the questions(`questionnaire::check_answers`), and an application failing a knockout rule
+    /// is moved to the pipeline's Rejected stage right away, with the `knockout` reason and the failed questions as the note
+    pub fn apply_with_answers(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+        answers: &[(usize, Answer)],
+    ) -> Result<Id, ErrorVariant> {
+        let store = self.store();
+        let candidate = self.authorize(&store, principal, Action::Apply)?;
+        // TODO: This would need to hold all candidates in memory
+        // change this
+        let job = store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+
+        if job.state != JobState::Open {
+            return Err(ErrorVariant::JobNotOpen {
+                job_id,
+                state: job.state,
+            });
+        }
+        if !job.in_application_window(self.now()) {
+            return Err(ErrorVariant::OutsideApplicationWindow {
+                job_id,
+                opens_at: job.opens_at,
+                closes_at: job.closes_at,
+            });
+        }
+        let questions = store.get_questions(job_id)?;
+        questionnaire::check_answers(&questions, answers).map_err(ErrorVariant::InvalidAnswers)?;
+
+        // Knocked out applications are stored straight in the Rejected stage, along with their answers
+        let mut application = Application {
+            job_id: job.id,
+            candidate_id: candidate.id,
+            stage: 0,
+            rejection: None,
+        };
+        let failed = questionnaire::knocked_out(&questions, answers);
+        if !failed.is_empty() {
+            let pipeline = store
+                .get_pipeline(job.pipeline_id)
+                .or_not_found(|| ErrorVariant::PipelineNotFound(job.pipeline_id))?;
+            // `set_questionnaire` only takes knockout rules for pipelines with a Rejected stage
+            if let Some(stage) = pipeline.rejected_stage() {
+                application.stage = stage;
+                application.rejection = Some(Rejection {
+                    reason: questionnaire::KNOCKOUT_REASON.into(),
+                    note: failed
+                        .iter()
+                        .map(|question| question.text.as_str())
+                        .collect::<Vec<_>>()
+                        .join("; "),
+                });
+            }
+        }
+
+        // Both the job and the candidate exist, so the only constraint left is the unique application
+        let id = store
+            .insert_application(&application, answers)
+            .map_err(|e| match e {
+                StoreError::Constraint(_) => ErrorVariant::AlreadyApplied,
+                e => e.into(),
+            })?;
+        Ok(id)
+    }
+
+    /// The answers `user` gave to the job's screening questions, paired with their question.
+    /// Candidates can only see their own
+    pub fn application_answers(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+    ) -> Result<Vec<(Question, Answer)>, ErrorVariant> {
+        let store = self.store();
+        let viewer = self.check_session(&store, principal)?;
+        if viewer.user != user && !viewer.role.can(Action::ViewProfiles) {
+            return Err(ErrorVariant::PermissionDenied(Action::ViewProfiles));
+        }
+        let application = find_application(&*store, user, job_id)?;
+        let questions = store.get_questions(job_id)?;
+        Ok(store
+            .get_answers(job_id, application.candidate_id)?
+            .into_iter()
+            .filter_map(|(position, answer)| {
+                questions
+                    .get(position)
+                    .map(|question| (question.clone(), answer))
+            })
+            .collect())
+    }
+
+    /// Moves the application of `user` to the stage chosen by `pick` in the job's pipeline.
+    /// `pick` gets the current stage and returns the next one, or a description of the one it wanted.
+    /// The role of `actor` must allow what the new stage implies: Approve to hire, Reject to reject and Interview otherwise.
+    /// Reaching a Rejected stage needs a `rejection`, reaching a Hired one lets the application get an offer
+    fn advance_process<F>(
+        &self,
+        store: &S,
+        actor: &Candidate,
+        user: &str,
+        job_id: Id,
+        rejection: Option<Rejection>,
+        pick: F,
+    ) -> Result<(), ErrorVariant>
+    where
+        F: FnOnce(&Pipeline, usize) -> Result<usize, String>,
+    {
+        let job = store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        if !job.state.is_hiring() {
+            return Err(ErrorVariant::JobNotOpen {
+                job_id,
+                state: job.state,
+            });
+        }
+        let mut application = find_application(store, user, job_id)?;
+        let pipeline = store
+            .get_pipeline(job.pipeline_id)
+            .or_not_found(|| ErrorVariant::PipelineNotFound(job.pipeline_id))?;
+
+        let from = application.stage;
+        let to = pick(&pipeline, from)
+            .and_then(|to| {
+                if pipeline.can_move(from, to) {
+                    Ok(to)
+                } else {
+                    Err(pipeline.stage_name(to).to_string())
+                }
+            })
+            .map_err(|to| ErrorVariant::InvalidTransition {
+                from: pipeline.stage_name(from).to_string(),
+                to,
+            })?;
+        let outcome = pipeline.stages[to].outcome;
+        let action = match outcome {
+            Some(Outcome::Hired) => Action::Approve,
+            Some(Outcome::Rejected) => Action::Reject,
+            Some(Outcome::Withdrawn) => Action::Withdraw,
+            None => Action::Interview,
+        };
+        if !actor.role.can(action) {
+            return Err(ErrorVariant::PermissionDenied(action));
+        }
+        if outcome == Some(Outcome::Rejected) && rejection.is_none() {
+            return Err(ErrorVariant::InvalidRejection(
+                "A rejection needs a reason, use reject".into(),
+            ));
+        }
+        if outcome.is_some()
+            && self.require_scorecards
+            && store
+                .list_scorecards(job_id, application.candidate_id)?
+                .is_empty()
+        {
+            return Err(ErrorVariant::ScorecardRequired {
+                job_id,
+                user: user.to_string(),
+            });
+        }
+
+        application.stage = to;
+        application.rejection = rejection;
+        store.update_application(&application)?;
+        Ok(())
+    }
+
+    /// Moves the application of `user` to the named stage of the job's pipeline
+    pub fn advance(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+        stage: &str,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        let actor = self.authorize(&store, principal, Action::Interview)?;
+        self.advance_process(&store, &actor, user, job_id, None, |pipeline, _| {
+            pipeline.position(stage).ok_or_else(|| stage.to_string())
+        })
+    }
+
+    /// Moves the application to the first non terminal stage that follows the current one
+    pub fn interview(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        let actor = self.authorize(&store, principal, Action::Interview)?;
+        self.advance_process(&store, &actor, user, job_id, None, |pipeline, from| {
+            pipeline
+                .next_stages(from)
+                .find(|to| pipeline.stages[*to].outcome.is_none())
+                .ok_or_else(|| "a next stage".to_string())
+        })
+    }
+
+    /// Moves the application to a Hired stage that follows the current one, the job is filled once enough offers are accepted
+    pub fn approve(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        let actor = self.authorize(&store, principal, Action::Approve)?;
+        self.advance_process(&store, &actor, user, job_id, None, |pipeline, from| {
+            next_with_outcome(pipeline, from, Outcome::Hired)
+        })
+    }
+
+    /// Moves the application to a Rejected stage that follows the current one,
+    /// recording the reason(one of `rejection_reasons`) and a note
+    pub fn reject(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+        reason: &str,
+        note: &str,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        let actor = self.authorize(&store, principal, Action::Reject)?;
+        if !self.rejection_reasons.iter().any(|known| known == reason) {
+            return Err(ErrorVariant::InvalidRejection(format!(
+                "Unknown reason {}",
+                reason
+            )));
+        }
+        let rejection = Rejection {
+            reason: reason.into(),
+            note: note.into(),
+        };
+        self.advance_process(
+            &store,
+            &actor,
+            user,
+            job_id,
+            Some(rejection),
+            |pipeline, from| next_with_outcome(pipeline, from, Outcome::Rejected),
+        )
+    }
+
+    /// The reason codes a rejection can use
+    pub fn rejection_reasons(&self) -> &[String] {
+        &self.rejection_reasons
+    }
+
+    /// Moves the application of `principal` to the Withdrawn stage of the job's pipeline
+    /// and cancels its scheduled interviews. Applications in a terminal stage can't be withdrawn
+    pub fn withdraw(&self, principal: &LoggedUser, job_id: Id) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        let candidate = self.authorize(&store, principal, Action::Withdraw)?;
+        let job = store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        let mut application = store
+            .get_application(job_id, candidate.id)
+            .or_not_found(|| ErrorVariant::ApplicationNotFound {
+                job_id,
+                user: candidate.user.clone(),
+            })?;
+        let pipeline = store
+            .get_pipeline(job.pipeline_id)
+            .or_not_found(|| ErrorVariant::PipelineNotFound(job.pipeline_id))?;
+
+        let from = application.stage;
+        let active = pipeline
+            .stages
+            .get(from)
+            .is_some_and(|stage| stage.outcome.is_none());
+        application.stage = match pipeline.withdrawn_stage() {
+            Some(to) if active => to,
+            _ => {
+                return Err(ErrorVariant::InvalidTransition {
+                    from: pipeline.stage_name(from).to_string(),
+                    to: "Withdrawn".into(),
+                })
+            }
+        };
+        store.update_application(&application)?;
+
+        let scheduled = store.list_interviews(&InterviewFilter {
+            job_id: Some(job_id),
+            candidate_id: Some(candidate.id),
+            ..Default::default()
+        })?;
+        for mut interview in scheduled
+            .into_iter()
+            .filter(|interview| interview.status == InterviewStatus::Scheduled)
+        {
+            interview.status = InterviewStatus::Cancelled;
+            store.update_interview(&interview)?;
+        }
+        Ok(())
+    }
+
+    /// Drafts an offer for the application of `user` to the job, returns its ID.
+    /// The application must be in a Hired stage without another outstanding offer, and the job needs an open slot
+    pub fn create_offer(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+        salary: u64,
+        start_date: Timestamp,
+        expires_at: Timestamp,
+    ) -> Result<Id, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ManageOffers)?;
+        let now = self.now();
+        store.expire_offers(now)?;
+        let job = store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        if !job.state.is_hiring() {
+            return Err(ErrorVariant::JobNotOpen {
+                job_id,
+                state: job.state,
+            });
+        }
+        let application = find_application(&*store, user, job_id)?;
+        let pipeline = store
+            .get_pipeline(job.pipeline_id)
+            .or_not_found(|| ErrorVariant::PipelineNotFound(job.pipeline_id))?;
+        let outcome = pipeline
+            .stages
+            .get(application.stage)
+            .and_then(|stage| stage.outcome);
+        if outcome != Some(Outcome::Hired) {
+            return Err(ErrorVariant::InvalidOffer(format!(
+                "{} hasn't been approved",
+                user
+            )));
+        }
+        if expires_at <= now {
+            return Err(ErrorVariant::InvalidOffer(
+                "It must expire in the future".into(),
+            ));
+        }
+        if salary > offer::MAX_SALARY {
+            return Err(ErrorVariant::InvalidOffer(format!(
+                "The salary can't be more than {}",
+                offer::MAX_SALARY
+            )));
+        }
+        let offers = store.list_offers(&OfferFilter {
+            job_id: Some(job_id),
+            candidate_id: Some(application.candidate_id),
+        })?;
+        if offers.iter().any(|offer| offer.status.is_outstanding()) {
+            return Err(ErrorVariant::InvalidOffer(format!(
+                "{} already has an outstanding offer",
+                user
+            )));
+        }
+        if job.open_slots() == 0 {
+            return Err(ErrorVariant::InvalidOffer(
+                "The job has no open slots".into(),
+            ));
+        }
+        Ok(store.add_offer(&Offer {
+            id: Default::default(),
+            job_id,
+            candidate_id: application.candidate_id,
+            salary,
+            start_date,
+            expires_at,
+            status: OfferStatus::Drafted,
+            created_at: now,
+        })?)
+    }
+
+    /// Sends a drafted offer to the candidate, who can then accept or decline it until it expires
+    pub fn send_offer(&self, principal: &LoggedUser, offer_id: Id) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ManageOffers)?;
+        store.expire_offers(self.now())?;
+        let mut offer = store
+            .get_offer(offer_id)
+            .or_not_found(|| ErrorVariant::OfferNotFound(offer_id))?;
+        move_offer(&mut offer, OfferStatus::Drafted, OfferStatus::Sent)?;
+        store.update_offer(&offer)?;
+        Ok(())
+    }
+
+    /// Accepts an offer sent to `principal`, the job is filled if it fills its headcount
+    pub fn accept_offer(&self, principal: &LoggedUser, offer_id: Id) -> Result<(), ErrorVariant> {
+        self.answer_offer(principal, offer_id, OfferStatus::Accepted)
+    }
+
+    /// Declines an offer sent to `principal`, its slot is open again
+    pub fn decline_offer(&self, principal: &LoggedUser, offer_id: Id) -> Result<(), ErrorVariant> {
+        self.answer_offer(principal, offer_id, OfferStatus::Declined)
+    }
+
+    fn answer_offer(
+        &self,
+        principal: &LoggedUser,
+        offer_id: Id,
+        answer: OfferStatus,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        let candidate = self.authorize(&store, principal, Action::AnswerOffers)?;
+        store.expire_offers(self.now())?;
+        let mut offer = store
+            .get_offer(offer_id)
+            .or_not_found(|| ErrorVariant::OfferNotFound(offer_id))?;
+        // Offers of other candidates are as good as missing
+        if offer.candidate_id != candidate.id {
+            return Err(ErrorVariant::OfferNotFound(offer_id));
+        }
+        move_offer(&mut offer, OfferStatus::Sent, answer)?;
+        if answer == OfferStatus::Declined {
+            store.update_offer(&offer)?;
+            return Ok(());
+        }
+
+        // Cancelled jobs don't hire, the offer can only be declined
+        let job = store
+            .get_job_by_id(offer.job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(offer.job_id))?;
+        if job.state == JobState::Cancelled {
+            return Err(ErrorVariant::JobNotOpen {
+                job_id: job.id,
+                state: job.state,
+            });
+        }
+        store.update_offer(&offer)?;
+        let mut job = store.get_job_by_id(offer.job_id)?;
+        let state = filled_state(&job);
+        if state != job.state {
+            job.state = state;
+            store.update_job_posting(&job)?;
+        }
+        Ok(())
+    }
+
+    /// Offers made for the job ordered by ID
+    pub fn offers_for_job(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+    ) -> Result<Vec<Offer>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ManageOffers)?;
+        store.expire_offers(self.now())?;
+        Ok(store.list_offers(&OfferFilter {
+            job_id: Some(job_id),
+            ..Default::default()
+        })?)
+    }
+
+    /// Offers made to `principal` ordered by ID
+    pub fn my_offers(&self, principal: &LoggedUser) -> Result<Vec<Offer>, ErrorVariant> {
+        let store = self.store();
+        let candidate = self.authorize(&store, principal, Action::AnswerOffers)?;
+        store.expire_offers(self.now())?;
+        Ok(store.list_offers(&OfferFilter {
+            candidate_id: Some(candidate.id),
+            ..Default::default()
+        })?)
+    }
+
+    /// Counts of the applications to the job per stage and outcome, and of the rejections per reason
+    pub fn job_report(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+    ) -> Result<JobReport, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::Interview)?;
+        let job = store
+            .get_job_with_applicants(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        let pipeline = store
+            .get_pipeline(job.pipeline_id)
+            .or_not_found(|| ErrorVariant::PipelineNotFound(job.pipeline_id))?;
+        Ok(JobReport::new(&job, &pipeline))
+    }
+
+    /// Replaces the profile of `principal`, it must be valid(`Profile::validate`)
+    pub fn update_profile(
+        &self,
+        principal: &LoggedUser,
+        profile: &Profile,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        let candidate = self.authorize(&store, principal, Action::EditProfile)?;
+        profile.validate().map_err(ErrorVariant::InvalidProfile)?;
+        store.set_profile(&Profile {
+            candidate_id: candidate.id,
+            ..profile.clone()
+        })?;
+        Ok(())
+    }
+
+    /// Profile of `principal`, empty if they didn't fill it yet
+    pub fn my_profile(&self, principal: &LoggedUser) -> Result<Profile, ErrorVariant> {
+        let store = self.store();
+        let candidate = self.authorize(&store, principal, Action::EditProfile)?;
+        Ok(profile_of(&*store, candidate.id)?)
+    }
+
+    /// Parses a plain text or Markdown résumé into a draft of the profile of `principal`.
+    /// The draft is their current profile with what the résumé gives merged in(`ParsedResume::merged_with`),
+    /// nothing is saved until they confirm it with `update_profile`
+    pub fn draft_profile(
+        &self,
+        principal: &LoggedUser,
+        resume: &str,
+    ) -> Result<ParsedResume, ErrorVariant> {
+        let store = self.store();
+        let candidate = self.authorize(&store, principal, Action::EditProfile)?;
+        let current = profile_of(&*store, candidate.id)?;
+        let parsed = resume::parse_resume(resume, &self.skill_dictionary);
+        Ok(ParsedResume {
+            profile: parsed.merged_with(&current),
+            report: parsed.report,
+        })
+    }
+
+    /// Same as `draft_profile` with a plain text or Markdown document `principal` attached to an application
+    pub fn draft_profile_from_attachment(
+        &self,
+        principal: &LoggedUser,
+        attachment_id: Id,
+    ) -> Result<ParsedResume, ErrorVariant> {
+        // Candidates can only download their own attachments
+        let (attachment, content) = self.download_attachment(principal, attachment_id)?;
+        if !attachment.mime_type.starts_with("text/") {
+            return Err(ErrorVariant::InvalidAttachment(format!(
+                "Only plain text and Markdown résumés can be parsed, not {}",
+                attachment.mime_type
+            )));
+        }
+        // Text is checked to be UTF-8 when it's attached, this refuses anything stored otherwise
+        let text = String::from_utf8(content)
+            .map_err(|_| ErrorVariant::InvalidAttachment("The résumé isn't UTF-8 text".into()))?;
+        self.draft_profile(principal, &text)
+    }
+
+    /// Profile of `user`, empty if they didn't fill it yet
+    pub fn candidate_profile(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+    ) -> Result<Profile, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ViewProfiles)?;
+        let candidate = store
+            .get_candidate(user)
+            .or_not_found(|| ErrorVariant::CandidateNotFound(user.to_string()))?;
+        Ok(profile_of(&*store, candidate.id)?)
+    }
+
+    /// Profiles of the candidates that applied to the job ordered by username, empty for those who didn't fill it
+    pub fn applicant_profiles(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+    ) -> Result<Vec<(String, Profile)>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ViewProfiles)?;
+        let job = store
+            .get_job_with_applicants(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        let mut users: Vec<_> = job.applicants.into_keys().collect();
+        users.sort();
+        users
+            .into_iter()
+            .map(|user| {
+                let candidate = store.get_candidate(&user)?;
+                Ok((user, profile_of(&*store, candidate.id)?))
+            })
+            .collect()
+    }
+
+    /// Applicants of the job still in the process(not in a terminal stage) scored against the skills
+    /// and seniority it asks for, best first(`matching::best_first`) with ties by name.
+    /// Jobs asking for neither can't rank them and fail with `ErrorVariant::InvalidJobDetails`
+    pub fn rank_applicants(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+    ) -> Result<Vec<(String, MatchScore)>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ViewProfiles)?;
+        let job = store
+            .get_job_with_applicants(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        let skills = store.get_job_skills(job_id)?;
+        if skills.is_empty() && job.details.seniority.is_none() {
+            return Err(ErrorVariant::InvalidJobDetails(
+                "The job doesn't give the skills or seniority it asks for".into(),
+            ));
+        }
+        let pipeline = store
+            .get_pipeline(job.pipeline_id)
+            .or_not_found(|| ErrorVariant::PipelineNotFound(job.pipeline_id))?;
+        let now = YearMonth::from_timestamp(self.now());
+        let mut ranking = Vec::new();
+        for (user, stage) in job.applicants {
+            let finished = pipeline
+                .position(&stage)
+                .is_some_and(|position| pipeline.stages[position].outcome.is_some());
+            if finished {
+                continue;
+            }
+            let candidate = store.get_candidate(&user)?;
+            let profile = profile_of(&*store, candidate.id)?;
+            if let Some(score) = MatchScore::new(&skills, job.details.seniority, &profile, now) {
+                ranking.push((user, score));
+            }
+        }
+        ranking.sort_by(|(a_user, a), (b_user, b)| {
+            matching::best_first(a, b).then_with(|| a_user.cmp(b_user))
+        });
+        Ok(ranking)
+    }
+
+    /// Open jobs `principal` can apply to scored against their profile, best first(`matching::best_first`).
+    /// Jobs that don't give the skills or seniority they ask for, or that the candidate matches nothing of,
+    /// are left out, and at most a page of them is returned
+    pub fn suggest_jobs(
+        &self,
+        principal: &LoggedUser,
+    ) -> Result<Vec<(Job, MatchScore)>, ErrorVariant> {
+        let store = self.store();
+        let candidate = self.authorize(&store, principal, Action::Apply)?;
+        let profile = profile_of(&*store, candidate.id)?;
+        let now = self.now();
+        let month = YearMonth::from_timestamp(now);
+        let mut suggestions = Vec::new();
+        for job in store.list_jobs()? {
+            if job.state != JobState::Open
+                || !job.in_application_window(now)
+                || job.applicants.contains_key(&candidate.user)
+            {
+                continue;
+            }
+            let skills = store.get_job_skills(job.id)?;
+            if let Some(score) = MatchScore::new(&skills, job.details.seniority, &profile, month) {
+                if score.score > 0.0 {
+                    suggestions.push((job, score));
+                }
+            }
+        }
+        suggestions.sort_by(|(a_job, a), (b_job, b)| {
+            matching::best_first(a, b).then(a_job.id.cmp(&b_job.id))
+        });
+        suggestions.truncate(self.page_size);
+        Ok(suggestions)
+    }
+
+    /// Attaches a document to the application of `principal` to the job, returns its ID.
+    /// It must be within the size limit, of an accepted MIME type and look like one(`attachment::check_document`)
+    pub fn attach_document(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+        kind: DocumentKind,
+        file_name: &str,
+        mime_type: &str,
+        content: &[u8],
+    ) -> Result<Id, ErrorVariant> {
+        let store = self.store();
+        let candidate = self.authorize(&store, principal, Action::AttachDocuments)?;
+        store
+            .get_application(job_id, candidate.id)
+            .or_not_found(|| ErrorVariant::ApplicationNotFound {
+                job_id,
+                user: candidate.user.clone(),
+            })?;
+        if content.len() as u64 > self.max_attachment_size {
+            return Err(ErrorVariant::InvalidAttachment(format!(
+                "The file can't be larger than {} bytes",
+                self.max_attachment_size
+            )));
+        }
+        if !self
+            .attachment_types
+            .iter()
+            .any(|accepted| accepted == mime_type)
+        {
+            return Err(ErrorVariant::InvalidAttachment(format!(
+                "{} files can't be attached",
+                mime_type
+            )));
+        }
+        attachment::check_document(file_name, mime_type, content)
+            .map_err(ErrorVariant::InvalidAttachment)?;
+
+        let sha256 = attachment::content_hash(content);
+        match self.content_dir() {
+            Some(dir) => dir.put(&sha256, content)?,
+            None => store.put_content(&sha256, content)?,
+        }
+        Ok(store.add_attachment(&Attachment {
+            id: 0,
+            job_id,
+            candidate_id: candidate.id,
+            kind,
+            file_name: file_name.into(),
+            mime_type: mime_type.into(),
+            size: content.len() as u64,
+            sha256,
+            uploaded_at: self.now(),
+        })?)
+    }
+
+    /// Attachments of the application of `user` to the job ordered by ID.
+    /// Candidates can only list their own, roles that view profiles can list anyone's
+    pub fn list_attachments(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+    ) -> Result<Vec<Attachment>, ErrorVariant> {
+        let store = self.store();
+        let viewer = self.check_session(&store, principal)?;
+        if viewer.user != user && !viewer.role.can(Action::ViewProfiles) {
+            return Err(ErrorVariant::PermissionDenied(Action::ViewProfiles));
+        }
+        let application = find_application(&*store, user, job_id)?;
+        Ok(store.list_attachments(job_id, application.candidate_id)?)
+    }
+
+    /// The attachment with its content, checked against its hash
+    pub fn download_attachment(
+        &self,
+        principal: &LoggedUser,
+        attachment_id: Id,
+    ) -> Result<(Attachment, Vec<u8>), ErrorVariant> {
+        let store = self.store();
+        let viewer = self.check_session(&store, principal)?;
+        let attachment = store
+            .get_attachment(attachment_id)
+            .or_not_found(|| ErrorVariant::AttachmentNotFound(attachment_id))?;
+        // Attachments the user can't see are as good as missing
+        if !can_view_attachments(&viewer, attachment.candidate_id) {
+            return Err(ErrorVariant::AttachmentNotFound(attachment_id));
+        }
+        let content = match self.content_dir() {
+            Some(dir) => dir.get(&attachment.sha256)?,
+            None => store.get_content(&attachment.sha256)?,
+        };
+        if attachment::content_hash(&content) != attachment.sha256 {
+            return Err(StoreError::Backend(
+                format!("The content of attachment {} is corrupted", attachment_id).into(),
+            )
+            .into());
+        }
+        Ok((attachment, content))
+    }
+
+    /// Deletes an attachment of `principal`(or anyone's for roles that manage documents).
+    /// Its content is deleted too when no other attachment has it
+    pub fn delete_attachment(
+        &self,
+        principal: &LoggedUser,
+        attachment_id: Id,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        let user = self.check_session(&store, principal)?;
+        let attachment = store
+            .get_attachment(attachment_id)
+            .or_not_found(|| ErrorVariant::AttachmentNotFound(attachment_id))?;
+        if !can_view_attachments(&user, attachment.candidate_id) {
+            return Err(ErrorVariant::AttachmentNotFound(attachment_id));
+        }
+        let owner = attachment.candidate_id == user.id && user.role.can(Action::AttachDocuments);
+        if !owner && !user.role.can(Action::ManageDocuments) {
+            return Err(ErrorVariant::PermissionDenied(Action::ManageDocuments));
+        }
+        store.delete_attachment(attachment_id)?;
+        if store.count_content_references(&attachment.sha256)? == 0 {
+            match self.content_dir() {
+                Some(dir) => dir.delete(&attachment.sha256)?,
+                None => store.delete_content(&attachment.sha256)?,
+            };
+        }
+        Ok(())
+    }
+
+    /// Where the attached documents are kept, `None` if they're kept in the store
+    fn content_dir(&self) -> Option<ContentDir<'_>> {
+        self.attachments_dir
+            .as_deref()
+            .map(|root| ContentDir { root })
+    }
+
+    /// Schedules a new interview round for the application of `user` to the job, returns its ID.
+    /// Every interviewer must be allowed to interview and be free during the whole slot
+    pub fn schedule_interview(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+        starts_at: Timestamp,
+        duration: i64,
+        interviewers: &[&str],
+    ) -> Result<Id, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::Interview)?;
+        if duration <= 0 {
+            return Err(ErrorVariant::InvalidInterview(
+                "The duration must be positive".into(),
+            ));
+        }
+        if starts_at.checked_add(duration).is_none() {
+            return Err(ErrorVariant::InvalidInterview(
+                "The interview would end after the last representable time".into(),
+            ));
+        }
+        if interviewers.is_empty() {
+            return Err(ErrorVariant::InvalidInterview(
+                "An interview needs at least one interviewer".into(),
+            ));
+        }
+        let job = store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        if !job.state.is_hiring() {
+            return Err(ErrorVariant::JobNotOpen {
+                job_id,
+                state: job.state,
+            });
+        }
+        let candidate_id = find_application(&*store, user, job_id)?.candidate_id;
+
+        let mut interviewer_ids = Vec::new();
+        for name in interviewers {
+            let interviewer = store
+                .get_candidate(name)
+                .or_not_found(|| ErrorVariant::CandidateNotFound(name.to_string()))?;
+            if !interviewer.role.can(Action::Interview) {
+                return Err(ErrorVariant::InvalidInterview(format!(
+                    "{} can't interview",
+                    name
+                )));
+            }
+            if !interviewer_ids.contains(&interviewer.id) {
+                interviewer_ids.push(interviewer.id);
+            }
+        }
+
+        // Cancelled rounds don't count
+        let round = store
+            .list_interviews(&InterviewFilter {
+                job_id: Some(job_id),
+                candidate_id: Some(candidate_id),
+                ..Default::default()
+            })?
+            .iter()
+            .filter(|interview| interview.status != InterviewStatus::Cancelled)
+            .map(|interview| interview.round)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let interview = Interview {
+            id: Default::default(),
+            job_id,
+            candidate_id,
+            round,
+            starts_at,
+            duration,
+            interviewers: interviewer_ids,
+            status: InterviewStatus::Scheduled,
+        };
+        check_double_booking(&*store, &interview)?;
+        Ok(store.add_interview(&interview)?)
+    }
+
+    /// Marks a scheduled interview as completed, no-show or cancelled, after that it can't change
+    pub fn mark_interview(
+        &self,
+        principal: &LoggedUser,
+        interview_id: Id,
+        status: InterviewStatus,
+    ) -> Result<(), ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::Interview)?;
+        let mut interview = store
+            .get_interview(interview_id)
+            .or_not_found(|| ErrorVariant::InterviewNotFound(interview_id))?;
+        if interview.status != InterviewStatus::Scheduled || status == InterviewStatus::Scheduled {
+            return Err(ErrorVariant::InvalidTransition {
+                from: interview.status.to_string(),
+                to: status.to_string(),
+            });
+        }
+        interview.status = status;
+        store.update_interview(&interview)?;
+        Ok(())
+    }
+
+    /// Scheduled interviews of `interviewer` that haven't ended yet, ordered by start time
+    pub fn upcoming_interviews_for(
+        &self,
+        principal: &LoggedUser,
+        interviewer: &str,
+    ) -> Result<Vec<Interview>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::Interview)?;
+        let interviewer_id = store
+            .get_candidate(interviewer)
+            .or_not_found(|| ErrorVariant::CandidateNotFound(interviewer.to_string()))?
+            .id;
+        self.upcoming_interviews(
+            &store,
+            &InterviewFilter {
+                interviewer_id: Some(interviewer_id),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Scheduled interviews for the job that haven't ended yet, ordered by start time
+    pub fn upcoming_interviews_for_job(
+        &self,
+        principal: &LoggedUser,
+        job_id: Id,
+    ) -> Result<Vec<Interview>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::Interview)?;
+        store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        self.upcoming_interviews(
+            &store,
+            &InterviewFilter {
+                job_id: Some(job_id),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn upcoming_interviews(
+        &self,
+        store: &S,
+        filter: &InterviewFilter,
+    ) -> Result<Vec<Interview>, ErrorVariant> {
+        let now = self.now();
+        Ok(store
+            .list_interviews(filter)?
+            .into_iter()
+            .filter(|interview| {
+                interview.status == InterviewStatus::Scheduled && interview.ends_at() > now
+            })
+            .collect())
+    }
+
+    /// Stores the scorecard of `principal` for the application of `user` to the job, returns its ID.
+    /// If the scorecard is about an interview, `principal` must be one of its interviewers.
+    /// Each interviewer submits one scorecard per interview(or one without interview)
+    pub fn submit_scorecard(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+        scorecard: Scorecard,
+    ) -> Result<Id, ErrorVariant> {
+        let store = self.store();
+        let interviewer = self.authorize(&store, principal, Action::Interview)?;
+        scorecard
+            .validate()
+            .map_err(ErrorVariant::InvalidScorecard)?;
+        store
+            .get_job_by_id(job_id)
+            .or_not_found(|| ErrorVariant::JobNotFound(job_id))?;
+        let application = find_application(&*store, user, job_id)?;
+        if let Some(interview_id) = scorecard.interview_id {
+            let interview = store
+                .get_interview(interview_id)
+                .or_not_found(|| ErrorVariant::InterviewNotFound(interview_id))?;
+            if interview.job_id != job_id || interview.candidate_id != application.candidate_id {
+                return Err(ErrorVariant::InvalidScorecard(format!(
+                    "Interview {} isn't about this application",
+                    interview_id
+                )));
+            }
+            if !interview.interviewers.contains(&interviewer.id) {
+                return Err(ErrorVariant::InvalidScorecard(format!(
+                    "Not an interviewer of interview {}",
+                    interview_id
+                )));
+            }
+        }
+        if store
+            .list_scorecards(job_id, application.candidate_id)?
+            .iter()
+            .any(|other| {
+                other.interviewer_id == interviewer.id
+                    && other.interview_id == scorecard.interview_id
+            })
+        {
+            return Err(ErrorVariant::InvalidScorecard(
+                "A scorecard was already submitted".into(),
+            ));
+        }
+        Ok(store.add_scorecard(&Scorecard {
+            job_id,
+            candidate_id: application.candidate_id,
+            interviewer_id: interviewer.id,
+            submitted_at: self.now(),
+            ..scorecard
+        })?)
+    }
+
+    /// Scorecards of the application of `user` to the job
+    pub fn scorecards(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+    ) -> Result<Vec<Scorecard>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::Interview)?;
+        let application = find_application(&*store, user, job_id)?;
+        Ok(store.list_scorecards(job_id, application.candidate_id)?)
+    }
+
+    /// Aggregation of the scorecards of the application of `user` to the job
+    pub fn scorecard_summary(
+        &self,
+        principal: &LoggedUser,
+        user: &str,
+        job_id: Id,
+    ) -> Result<ScorecardSummary, ErrorVariant> {
+        Ok(ScorecardSummary::new(
+            &self.scorecards(principal, user, job_id)?,
+        ))
+    }
+
+    /// Stores a new pipeline after checking it's valid, returns its ID.
+    /// A Withdrawn stage is added at the end if the pipeline doesn't have one
+    pub fn create_pipeline(
+        &self,
+        principal: &LoggedUser,
+        pipeline: &Pipeline,
+    ) -> Result<Id, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::ManagePipelines)?;
+        let pipeline = pipeline.clone().with_withdrawal();
+        pipeline.validate().map_err(ErrorVariant::InvalidPipeline)?;
+        Ok(store.add_pipeline(&pipeline)?)
+    }
+
+    /// Pipelines that can be used for new jobs
+    pub fn list_pipelines(&self, principal: &LoggedUser) -> Result<Vec<Pipeline>, ErrorVariant> {
+        let store = self.store();
+        self.authorize(&store, principal, Action::CreateJob)?;
+        Ok(store.list_pipelines()?)
+    }
+}
+
+/// The application of `user` to the job
+fn find_application<S: Store>(
+    store: &S,
+    user: &str,
+    job_id: Id,
+) -> Result<Application, ErrorVariant> {
+    let candidate = store
+        .get_candidate(user)
+        .or_not_found(|| ErrorVariant::CandidateNotFound(user.to_string()))?;
+    store
+        .get_application(job_id, candidate.id)
+        .or_not_found(|| ErrorVariant::ApplicationNotFound {
+            job_id,
+            user: user.to_string(),
+        })
+}
+
+/// The state of an open, on hold or closed job once accepted offers fill its headcount, or of a filled job with room again
+fn filled_state(job: &Job) -> JobState {
+    match job.state {
+        JobState::Open | JobState::OnHold | JobState::Closed if job.hired >= job.headcount => {
+            JobState::Filled
+        }
+        JobState::Filled if job.hired < job.headcount => JobState::Open,
+        state => state,
+    }
+}
+
+/// The stored profile of the candidate, or an empty one
+fn profile_of<S: Store>(store: &S, candidate_id: Id) -> StoreResult<Profile> {
+    match store.get_profile(candidate_id) {
+        Err(StoreError::NotFound) => Ok(Profile {
+            candidate_id,
+            ..Profile::default()
+        }),
+        profile => profile,
+    }
+}
+
+/// Whether `user` can see the attachments of the candidate with `owner_id`: their own, or anyone's if they view profiles
+fn can_view_attachments(user: &Candidate, owner_id: Id) -> bool {
+    user.id == owner_id || user.role.can(Action::ViewProfiles)
+}
+
+/// Moves the offer from `from` to `to`, failing if it's in another status
+fn move_offer(offer: &mut Offer, from: OfferStatus, to: OfferStatus) -> Result<(), ErrorVariant> {
+    if offer.status != from {
+        return Err(ErrorVariant::InvalidTransition {
+            from: offer.status.to_string(),
+            to: to.to_string(),
+        });
+    }
+    offer.status = to;
+    Ok(())
+}
+
+/// Fails if any interviewer has another scheduled interview overlapping `interview`
+fn check_double_booking<S: Store>(store: &S, interview: &Interview) -> Result<(), ErrorVariant> {
+    for interviewer in interview.interviewers.iter() {
+        let clash = store
+            .list_interviews(&InterviewFilter {
+                interviewer_id: Some(*interviewer),
+                ..Default::default()
+            })?
+            .into_iter()
+            .find(|other| {
+                other.id != interview.id
+                    && other.status == InterviewStatus::Scheduled
+                    && other.overlaps(interview)
+            });
+        if let Some(clash) = clash {
+            return Err(ErrorVariant::DoubleBooked {
+                interviewer: *interviewer,
+                interview_id: clash.id,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn next_with_outcome(pipeline: &Pipeline, from: usize, outcome: Outcome) -> Result<usize, String> {
+    pipeline
+        .next_stages(from)
+        .find(|to| pipeline.stages[*to].outcome == Some(outcome))
+        .ok_or_else(|| format!("a {:?} stage", outcome))
+}
@@ -1,180 +1,3488 @@
-#[cfg(test)]
-mod tests {
-    use crate::*;
-    // Note: In test we don't use System because we want each test to initialize the DB on their own
-    #[test]
-    fn create_job_posting() {
-        let system = HRSystem::new();
-        let job_posting_id = system.create_job_posting("Engineer".to_string()).unwrap();
-        assert_eq!(
-            system.list_jobs().unwrap().first().unwrap().name,
-            "Engineer".to_string()
-        );
-        assert_eq!(
-            system.list_jobs().unwrap().first().unwrap().id,
-            job_posting_id
-        );
+use crate::*;
+// Note: In test we don't use System because we want each test to initialize the DB on their own
+// Tests use `MemoryStore`, `store_constraints` checks both backends behave the same
+
+/// A builder with cheap password hashing, the default iterations are too slow for tests
+fn test_builder<S: Store + 'static>(store: S) -> HRSystemBuilder<S> {
+    HRSystem::builder().store(store).password_iterations(1_000)
+}
+
+/// A system built by `test_builder` without changing anything else
+fn new_system<S: Store + 'static>(store: S) -> HRSystem<S> {
+    test_builder(store).build().unwrap()
+}
+
+/// Registers `user`(with `user` as password) and gives it `role` through the first admin
+fn logged_in_with_role<S: Store>(system: &HRSystem<S>, user: &str, role: Role) -> LoggedUser {
+    let _ = system.bootstrap_admin("admin".to_string(), "admin".to_string());
+    let admin = system.login("admin", "admin").unwrap();
+    system
+        .register_candidate(user.to_string(), user.to_string())
+        .unwrap();
+    system.set_role(&admin, user, role).unwrap();
+    system.login(user, user).unwrap()
+}
+
+#[test]
+fn create_job_posting() {
+    let system = new_system(MemoryStore::new());
+    let manager = logged_in_with_role(&system, "manager", Role::HiringManager);
+    let job_posting_id = system
+        .create_job_posting(&manager, "Engineer".to_string())
+        .unwrap();
+    assert_eq!(
+        system.list_jobs(&manager).unwrap().first().unwrap().name,
+        "Engineer".to_string()
+    );
+    assert_eq!(
+        system.list_jobs(&manager).unwrap().first().unwrap().id,
+        job_posting_id
+    );
+}
+
+#[test]
+fn register_candidate() {
+    let system = new_system(MemoryStore::new());
+    let _ = system.register_candidate("test".to_string(), "test".to_string());
+    // Can login with correct password
+    let token = system.login("test", "test");
+    assert!(token.is_ok());
+    // Can't login with incorrect password
+    let token = system.login("test", "incorrect");
+    assert!(matches!(token, Err(ErrorVariant::Unauthorized)));
+    // Usernames are unique
+    assert!(matches!(
+        system.register_candidate("test".to_string(), "other".to_string()),
+        Err(ErrorVariant::UsernameTaken(_))
+    ));
+}
+
+#[test]
+fn candidate_process() {
+    let system = new_system(MemoryStore::new());
+    let manager = logged_in_with_role(&system, "manager", Role::HiringManager);
+    let job_posting_id = system
+        .create_job_posting(&manager, "Engineer".to_string())
+        .unwrap();
+    let _ = system.register_candidate("test".to_string(), "test".to_string());
+    let _ = system.register_candidate("test1".to_string(), "test".to_string());
+    let logged_in_user = system.login("test1", "test").unwrap();
+    assert!(system.apply(&logged_in_user, job_posting_id).is_ok());
+    assert_eq!(
+        system
+            .list_jobs(&manager)
+            .unwrap()
+            .first()
+            .unwrap()
+            .applicants
+            .get("test1")
+            .unwrap(),
+        "Applied"
+    );
+
+    assert!(matches!(
+        system.apply(&logged_in_user, job_posting_id),
+        Err(ErrorVariant::AlreadyApplied)
+    ));
+    assert!(matches!(
+        system.apply(&logged_in_user, job_posting_id + 1),
+        Err(ErrorVariant::JobNotFound(_))
+    ));
+    assert!(matches!(
+        system.interview(&manager, "test", job_posting_id),
+        Err(ErrorVariant::ApplicationNotFound { .. })
+    ));
+    assert!(matches!(
+        system.interview(&manager, "nobody", job_posting_id),
+        Err(ErrorVariant::CandidateNotFound(_))
+    ));
+
+    // Must follow flow
+    assert!(matches!(
+        system.approve(&manager, "test1", job_posting_id),
+        Err(ErrorVariant::InvalidTransition { from, to })
+            if from == "Applied" && to == "a Hired stage"
+    ));
+    assert_eq!(
+        system
+            .list_jobs(&manager)
+            .unwrap()
+            .first()
+            .unwrap()
+            .applicants
+            .get("test1")
+            .unwrap(),
+        "Applied"
+    );
+
+    // Must follow flow
+    assert!(matches!(
+        system.reject(&manager, "test1", job_posting_id, "experience", ""),
+        Err(ErrorVariant::InvalidTransition { from, to })
+            if from == "Applied" && to == "a Rejected stage"
+    ));
+    assert_eq!(
+        system
+            .list_jobs(&manager)
+            .unwrap()
+            .first()
+            .unwrap()
+            .applicants
+            .get("test1")
+            .unwrap(),
+        "Applied"
+    );
+    // Reject Flow
+    assert!(system.interview(&manager, "test1", job_posting_id).is_ok());
+    assert_eq!(
+        system
+            .list_jobs(&manager)
+            .unwrap()
+            .first()
+            .unwrap()
+            .applicants
+            .get("test1")
+            .unwrap(),
+        "Interviewed"
+    );
+
+    assert!(system
+        .reject(
+            &manager,
+            "test1",
+            job_posting_id,
+            "experience",
+            "Too junior"
+        )
+        .is_ok());
+    let job = system.list_jobs(&manager).unwrap().remove(0);
+    assert_eq!(job.applicants.get("test1").unwrap(), "Rejected");
+    assert_eq!(
+        job.rejections["test1"],
+        Rejection {
+            reason: "experience".into(),
+            note: "Too junior".into(),
+        }
+    );
+
+    // Aprove Flow
+
+    let logged_in_user = system.login("test", "test").unwrap();
+    assert!(system.apply(&logged_in_user, job_posting_id).is_ok());
+    assert_eq!(
+        system
+            .list_jobs(&manager)
+            .unwrap()
+            .first()
+            .unwrap()
+            .applicants
+            .get("test")
+            .unwrap(),
+        "Applied"
+    );
+    assert!(system.interview(&manager, "test", job_posting_id).is_ok());
+    assert_eq!(
+        system
+            .list_jobs(&manager)
+            .unwrap()
+            .first()
+            .unwrap()
+            .applicants
+            .get("test")
+            .unwrap(),
+        "Interviewed"
+    );
+
+    assert!(system.approve(&manager, "test", job_posting_id).is_ok());
+    assert_eq!(
+        system
+            .list_jobs(&manager)
+            .unwrap()
+            .first()
+            .unwrap()
+            .applicants
+            .get("test")
+            .unwrap(),
+        "Approved"
+    );
+
+    // Gets closed once the offer is accepted
+    let offer_id = system
+        .create_offer(
+            &manager,
+            "test",
+            job_posting_id,
+            50_000,
+            i64::MAX - 1,
+            i64::MAX,
+        )
+        .unwrap();
+    system.send_offer(&manager, offer_id).unwrap();
+    assert!(system.list_jobs(&manager).unwrap()[0].state == JobState::Open);
+    system.accept_offer(&logged_in_user, offer_id).unwrap();
+    let _ = system.register_candidate("test2".to_string(), "test".to_string());
+    let logged_in_user = system.login("test2", "test").unwrap();
+    assert!(matches!(
+        system.apply(&logged_in_user, job_posting_id),
+        Err(ErrorVariant::JobNotOpen {
+            state: JobState::Filled,
+            ..
+        })
+    ));
+}
+
+fn check_pipelines<S: Store + 'static>(store: S) {
+    let system = new_system(store);
+    let manager = logged_in_with_role(&system, "manager", Role::HiringManager);
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+
+    let pipeline = Pipeline::new("Engineering")
+        .stage("Applied", &["Phone screen", "Rejected"])
+        .stage("Phone screen", &["Technical", "Rejected"])
+        .stage("Technical", &["Onsite", "Rejected"])
+        .stage("Onsite", &["Offer", "Rejected"])
+        .terminal("Offer", Outcome::Hired)
+        .terminal("Rejected", Outcome::Rejected);
+    assert!(matches!(
+        system.create_pipeline(&recruiter, &pipeline),
+        Err(ErrorVariant::PermissionDenied(Action::ManagePipelines))
+    ));
+    assert!(matches!(
+        system.create_pipeline(
+            &manager,
+            &Pipeline::new("Broken").stage("Applied", &["Missing"])
+        ),
+        Err(ErrorVariant::InvalidPipeline(_))
+    ));
+    let pipeline_id = system.create_pipeline(&manager, &pipeline).unwrap();
+    let pipelines = system.list_pipelines(&recruiter).unwrap();
+    assert_eq!(pipelines.len(), 2);
+    // Every pipeline gets a Withdrawn stage
+    assert_eq!(
+        pipelines[1].stages,
+        pipeline.clone().with_withdrawal().stages
+    );
+    assert_eq!(pipelines[1].withdrawn_stage(), Some(6));
+    // Which only candidates reach by withdrawing
+    assert!(matches!(
+        system.create_pipeline(
+            &manager,
+            &Pipeline::new("Leaky")
+                .stage("Applied", &["Left"])
+                .terminal("Left", Outcome::Withdrawn)
+        ),
+        Err(ErrorVariant::InvalidPipeline(_))
+    ));
+
+    assert!(matches!(
+        system.create_job_posting_with_pipeline(&recruiter, "Engineer".into(), pipeline_id + 1),
+        Err(ErrorVariant::PipelineNotFound(_))
+    ));
+    let job_id = system
+        .create_job_posting_with_pipeline(&recruiter, "Engineer".into(), pipeline_id)
+        .unwrap();
+    system
+        .register_candidate("test".to_string(), "test".to_string())
+        .unwrap();
+    let candidate = system.login("test", "test").unwrap();
+    system.apply(&candidate, job_id).unwrap();
+    let stage = || system.list_jobs(&manager).unwrap()[0].applicants["test"].clone();
+    assert_eq!(stage(), "Applied");
+
+    // The job's pipeline decides which moves are allowed
+    assert!(matches!(
+        system.advance(&recruiter, "test", job_id, "Technical"),
+        Err(ErrorVariant::InvalidTransition { from, to }) if from == "Applied" && to == "Technical"
+    ));
+    system.interview(&recruiter, "test", job_id).unwrap();
+    assert_eq!(stage(), "Phone screen");
+    system
+        .advance(&recruiter, "test", job_id, "Technical")
+        .unwrap();
+    system
+        .advance(&recruiter, "test", job_id, "Onsite")
+        .unwrap();
+    assert_eq!(stage(), "Onsite");
+
+    // Reaching a terminal stage needs the matching permission
+    assert!(matches!(
+        system.advance(&recruiter, "test", job_id, "Offer"),
+        Err(ErrorVariant::PermissionDenied(Action::Approve))
+    ));
+    system.approve(&manager, "test", job_id).unwrap();
+    assert_eq!(stage(), "Offer");
+    // The job stays open until an offer is accepted
+    assert!(system.list_jobs(&manager).unwrap()[0].state == JobState::Open);
+}
+
+/// Approves `user` for the job and has them accept an offer
+fn hire<S: Store>(system: &HRSystem<S>, manager: &LoggedUser, user: &str, job_id: Id) {
+    system.approve(manager, user, job_id).unwrap();
+    let offer_id = system
+        .create_offer(manager, user, job_id, 50_000, 1_000, 10_000)
+        .unwrap();
+    system.send_offer(manager, offer_id).unwrap();
+    system
+        .accept_offer(&system.login(user, user).unwrap(), offer_id)
+        .unwrap();
+}
+
+fn check_headcount<S: Store + 'static>(store: S) {
+    let system = test_builder(store).clock(|| 0).build().unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let manager = logged_in_with_role(&system, "manager", Role::HiringManager);
+    let job_id = system
+        .create_job_posting(&recruiter, "Support engineer".to_string())
+        .unwrap();
+    for user in ["test", "test2", "test3"].iter() {
+        system
+            .register_candidate(user.to_string(), user.to_string())
+            .unwrap();
+        let candidate = system.login(user, user).unwrap();
+        system.apply(&candidate, job_id).unwrap();
+        system.interview(&recruiter, user, job_id).unwrap();
     }
+    let job = || system.list_jobs(&recruiter).unwrap().remove(0);
+    assert_eq!((job().headcount, job().open_slots()), (1, 1));
 
-    #[test]
-    fn register_candidate() {
-        let system = HRSystem::new();
-        let _ = system.register_candidate("test".to_string(), "test".to_string());
-        // Can login with correct password
-        let token = system.login(&"test".to_string(), &"test".to_string());
-        assert!(token.is_some());
-        // Can't login with incorrect password
-        let token = system.login(&"test".to_string(), &"incorrect".to_string());
-        assert!(token.is_none());
-    }
-
-    #[test]
-    fn candidate_process() {
-        let system = HRSystem::new();
-        let job_posting_id = system.create_job_posting("Engineer".to_string()).unwrap();
-        let _ = system.register_candidate("test".to_string(), "test".to_string());
-        let _ = system.register_candidate("test1".to_string(), "test".to_string());
-        let logged_in_user = system
-            .login(&"test1".to_string(), &"test".to_string())
+    let candidate = system.login("test", "test").unwrap();
+    assert!(matches!(
+        system.set_headcount(&candidate, job_id, 2),
+        Err(ErrorVariant::PermissionDenied(Action::CreateJob))
+    ));
+    assert!(matches!(
+        system.set_headcount(&recruiter, job_id, 0),
+        Err(ErrorVariant::InvalidHeadcount { .. })
+    ));
+    system.set_headcount(&recruiter, job_id, 2).unwrap();
+
+    // The job stays open until the headcount is filled
+    hire(&system, &manager, "test", job_id);
+    assert!(job().state == JobState::Open);
+    assert_eq!((job().hired, job().open_slots()), (1, 1));
+    assert!(matches!(
+        system.set_headcount(&recruiter, job_id, 0),
+        Err(ErrorVariant::InvalidHeadcount {
+            headcount: 0,
+            taken: 1
+        })
+    ));
+    hire(&system, &manager, "test2", job_id);
+    assert!(job().state == JobState::Filled);
+    assert_eq!(job().open_slots(), 0);
+    assert!(matches!(
+        system.approve(&manager, "test3", job_id),
+        Err(ErrorVariant::JobNotOpen {
+            state: JobState::Filled,
+            ..
+        })
+    ));
+    assert!(matches!(
+        system.set_headcount(&recruiter, job_id, 1),
+        Err(ErrorVariant::InvalidHeadcount {
+            headcount: 1,
+            taken: 2
+        })
+    ));
+
+    // More openings reopen it
+    system.set_headcount(&recruiter, job_id, 3).unwrap();
+    assert!(job().state == JobState::Open);
+    assert_eq!(job().open_slots(), 1);
+    hire(&system, &manager, "test3", job_id);
+    assert!(job().state == JobState::Filled);
+}
+
+fn check_job_details<S: Store + 'static>(store: S) {
+    let system = new_system(store);
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    let job = || system.list_jobs(&recruiter).unwrap().remove(0);
+    // Postings start without details
+    assert_eq!(job().details, JobDetails::default());
+
+    let details = JobDetails::new("Builds the HR system")
+        .department("Engineering")
+        .location("Buenos Aires", Workplace::Hybrid)
+        .employment_type(EmploymentType::Contract)
+        .seniority(Seniority::Senior)
+        .salary(50_000, 70_000, "USD");
+    system
+        .update_job_posting(&recruiter, job_id, &details)
+        .unwrap();
+    assert_eq!(job().details, details);
+    assert_eq!(job().name, "Engineer");
+    // Updating replaces every detail
+    system
+        .update_job_posting(&recruiter, job_id, &JobDetails::new("Maintains it"))
+        .unwrap();
+    assert_eq!(job().details, JobDetails::new("Maintains it"));
+
+    for invalid in [
+        details.clone().salary(70_000, 50_000, "USD"),
+        details.clone().salary(50_000, 70_000, "usd"),
+        details.clone().salary(50_000, 70_000, "Dollars"),
+        details.clone().salary(50_000, u64::MAX, "USD"),
+        JobDetails::new(&"a".repeat(posting::MAX_DESCRIPTION_LEN + 1)),
+    ]
+    .iter()
+    {
+        assert!(matches!(
+            system.update_job_posting(&recruiter, job_id, invalid),
+            Err(ErrorVariant::InvalidJobDetails(_))
+        ));
+    }
+    assert!(matches!(
+        system.update_job_posting(&recruiter, job_id + 1, &details),
+        Err(ErrorVariant::JobNotFound(_))
+    ));
+    system
+        .register_candidate("test".to_string(), "test".to_string())
+        .unwrap();
+    assert!(matches!(
+        system.update_job_posting(&system.login("test", "test").unwrap(), job_id, &details),
+        Err(ErrorVariant::PermissionDenied(Action::CreateJob))
+    ));
+    assert_eq!(job().details, JobDetails::new("Maintains it"));
+}
+
+#[test]
+fn job_details() {
+    check_job_details(MemoryStore::new());
+    #[cfg(feature = "sqlite")]
+    check_job_details(SqliteStore::open_in_memory().unwrap());
+}
+
+fn check_job_lifecycle<S: Store + 'static>(store: S) {
+    let system = test_builder(store).clock(|| 0).build().unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let manager = logged_in_with_role(&system, "manager", Role::HiringManager);
+    for user in ["test", "test2", "test3"].iter() {
+        system
+            .register_candidate(user.to_string(), user.to_string())
             .unwrap();
-        assert!(system
-            .apply(
-                &logged_in_user.user,
-                logged_in_user.token,
-                logged_in_user.user_id,
-                job_posting_id
-            )
-            .is_ok());
-        assert_eq!(
-            system
-                .list_jobs()
-                .unwrap()
-                .first()
-                .unwrap()
-                .applicants
-                .get("test1")
-                .unwrap(),
-            &Candidacy::Applied(AppliedApplication)
-        );
+    }
+    let candidate = system.login("test", "test").unwrap();
+    let job_id = system
+        .create_draft_job_posting(&recruiter, "Engineer".to_string(), None)
+        .unwrap();
+    let state = || system.list_jobs(&recruiter).unwrap()[0].state;
+    assert_eq!(state(), JobState::Draft);
 
-        // Must follow flow
-        assert!(system.approve("test1".to_string(), job_posting_id).is_err());
-        assert_eq!(
-            system
-                .list_jobs()
-                .unwrap()
-                .first()
-                .unwrap()
-                .applicants
-                .get("test1")
-                .unwrap(),
-            &Candidacy::Applied(AppliedApplication)
-        );
+    // Drafts don't take applications until published
+    assert!(matches!(
+        system.apply(&candidate, job_id),
+        Err(ErrorVariant::JobNotOpen { job_id: id, state: JobState::Draft }) if id == job_id
+    ));
+    assert!(matches!(
+        system.publish_job(&candidate, job_id),
+        Err(ErrorVariant::PermissionDenied(Action::CreateJob))
+    ));
+    assert!(matches!(
+        system.pause_job(&recruiter, job_id),
+        Err(ErrorVariant::InvalidJobTransition {
+            from: JobState::Draft,
+            to: JobState::OnHold,
+            ..
+        })
+    ));
+    system.publish_job(&recruiter, job_id).unwrap();
+    assert_eq!(state(), JobState::Open);
+    assert!(matches!(
+        system.publish_job(&recruiter, job_id),
+        Err(ErrorVariant::InvalidJobTransition { .. })
+    ));
+    system.apply(&candidate, job_id).unwrap();
+    system.interview(&recruiter, "test", job_id).unwrap();
 
-        // Must follow flow
-        assert!(system.reject("test1".to_string(), job_posting_id).is_ok());
-        assert_eq!(
-            system
-                .list_jobs()
-                .unwrap()
-                .first()
-                .unwrap()
-                .applicants
-                .get("test1")
-                .unwrap(),
-            &Candidacy::Applied(AppliedApplication)
-        );
-        // Reject Flow
-        assert!(system
-            .interview("test1".to_string(), job_posting_id)
-            .is_ok());
-        assert_eq!(
-            system
-                .list_jobs()
-                .unwrap()
-                .first()
-                .unwrap()
-                .applicants
-                .get("test1")
-                .unwrap(),
-            &Candidacy::Interviewed(InterviewedApplication)
-        );
+    // Jobs on hold don't take applications or process changes
+    system.pause_job(&recruiter, job_id).unwrap();
+    assert!(matches!(
+        system.apply(&system.login("test2", "test2").unwrap(), job_id),
+        Err(ErrorVariant::JobNotOpen {
+            state: JobState::OnHold,
+            ..
+        })
+    ));
+    assert!(matches!(
+        system.approve(&manager, "test", job_id),
+        Err(ErrorVariant::JobNotOpen {
+            state: JobState::OnHold,
+            ..
+        })
+    ));
+    system.resume_job(&recruiter, job_id).unwrap();
+    system
+        .apply(&system.login("test2", "test2").unwrap(), job_id)
+        .unwrap();
+    system.interview(&recruiter, "test2", job_id).unwrap();
 
-        assert!(system.reject("test1".to_string(), job_posting_id).is_ok());
-        assert_eq!(
-            system
-                .list_jobs()
-                .unwrap()
-                .first()
-                .unwrap()
-                .applicants
-                .get("test1")
-                .unwrap(),
-            &Candidacy::Rejected(RejectedApplication)
-        );
+    // But offers sent before pausing can be accepted, filling the job
+    system.approve(&manager, "test", job_id).unwrap();
+    let offer_id = system
+        .create_offer(&manager, "test", job_id, 50_000, 1_000, 10_000)
+        .unwrap();
+    system.send_offer(&manager, offer_id).unwrap();
+    system.pause_job(&recruiter, job_id).unwrap();
+    system.accept_offer(&candidate, offer_id).unwrap();
+    assert_eq!(state(), JobState::Filled);
+    for moved in [
+        system.resume_job(&recruiter, job_id),
+        system.reopen_job(&recruiter, job_id),
+        system.cancel_job(&recruiter, job_id),
+    ]
+    .iter()
+    {
+        assert!(matches!(
+            moved,
+            Err(ErrorVariant::InvalidJobTransition {
+                from: JobState::Filled,
+                ..
+            })
+        ));
+    }
+    // Filled jobs reopen by raising their headcount
+    system.set_headcount(&recruiter, job_id, 2).unwrap();
+    assert_eq!(state(), JobState::Open);
+
+    // Offers of cancelled jobs can only be declined
+    system.approve(&manager, "test2", job_id).unwrap();
+    let offer_id = system
+        .create_offer(&manager, "test2", job_id, 50_000, 1_000, 10_000)
+        .unwrap();
+    system.send_offer(&manager, offer_id).unwrap();
+    system.cancel_job(&manager, job_id).unwrap();
+    assert_eq!(state(), JobState::Cancelled);
+    let other = system.login("test2", "test2").unwrap();
+    assert!(matches!(
+        system.accept_offer(&other, offer_id),
+        Err(ErrorVariant::JobNotOpen {
+            state: JobState::Cancelled,
+            ..
+        })
+    ));
+    system.decline_offer(&other, offer_id).unwrap();
+    assert!(matches!(
+        system.cancel_job(&recruiter, job_id),
+        Err(ErrorVariant::InvalidJobTransition { .. })
+    ));
+    system.reopen_job(&recruiter, job_id).unwrap();
+    assert_eq!(state(), JobState::Open);
+    system
+        .apply(&system.login("test3", "test3").unwrap(), job_id)
+        .unwrap();
+
+    assert!(matches!(
+        system.create_draft_job_posting(&recruiter, "Designer".to_string(), Some(42)),
+        Err(ErrorVariant::PipelineNotFound(42))
+    ));
+    assert!(matches!(
+        system.resume_job(&recruiter, job_id + 1),
+        Err(ErrorVariant::JobNotFound(_))
+    ));
+}
 
-        // Aprove Flow
+#[test]
+fn job_lifecycle() {
+    check_job_lifecycle(MemoryStore::new());
+    #[cfg(feature = "sqlite")]
+    check_job_lifecycle(SqliteStore::open_in_memory().unwrap());
+}
 
-        let logged_in_user = system
-            .login(&"test".to_string(), &"test".to_string())
+fn check_application_window<S: Store + 'static>(store: S) {
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+    let now = Arc::new(AtomicI64::new(100));
+    let clock = now.clone();
+    let system = test_builder(store)
+        .clock(move || clock.load(Ordering::SeqCst))
+        .build()
+        .unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let manager = logged_in_with_role(&system, "manager", Role::HiringManager);
+    for user in ["test", "test2"].iter() {
+        system
+            .register_candidate(user.to_string(), user.to_string())
             .unwrap();
-        assert!(system
-            .apply(
-                &logged_in_user.user,
-                logged_in_user.token,
-                logged_in_user.user_id,
-                job_posting_id
-            )
-            .is_ok());
-        assert_eq!(
-            system
-                .list_jobs()
-                .unwrap()
-                .first()
-                .unwrap()
-                .applicants
-                .get("test")
-                .unwrap(),
-            &Candidacy::Applied(AppliedApplication)
-        );
-        assert!(system.interview("test".to_string(), job_posting_id).is_ok());
-        assert_eq!(
-            system
-                .list_jobs()
-                .unwrap()
-                .first()
-                .unwrap()
-                .applicants
-                .get("test")
-                .unwrap(),
-            &Candidacy::Interviewed(InterviewedApplication)
-        );
+    }
+    let candidate = system.login("test", "test").unwrap();
+    let other = system.login("test2", "test2").unwrap();
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    let paused_id = system
+        .create_job_posting(&recruiter, "Designer".to_string())
+        .unwrap();
+    let draft_id = system
+        .create_draft_job_posting(&recruiter, "Writer".to_string(), None)
+        .unwrap();
+    let forever_id = system
+        .create_job_posting(&recruiter, "Tester".to_string())
+        .unwrap();
+    let job = |id: Id| {
+        system
+            .list_jobs(&recruiter)
+            .unwrap()
+            .into_iter()
+            .find(|job| job.id == id)
+            .unwrap()
+    };
+
+    assert!(matches!(
+        system.set_application_window(&recruiter, job_id, Some(300), Some(300)),
+        Err(ErrorVariant::InvalidApplicationWindow {
+            opens_at: 300,
+            closes_at: 300
+        })
+    ));
+    assert!(matches!(
+        system.set_application_window(&candidate, job_id, None, Some(300)),
+        Err(ErrorVariant::PermissionDenied(Action::CreateJob))
+    ));
+    for id in [job_id, paused_id, draft_id].iter() {
+        system
+            .set_application_window(&recruiter, *id, Some(200), Some(300))
+            .unwrap();
+    }
+    assert_eq!(
+        (job(job_id).opens_at, job(job_id).closes_at),
+        (Some(200), Some(300))
+    );
 
-        assert!(system.approve("test".to_string(), job_posting_id).is_ok());
-        assert_eq!(
-            system
-                .list_jobs()
-                .unwrap()
-                .first()
-                .unwrap()
-                .applicants
-                .get("test")
-                .unwrap(),
-            &Candidacy::Approved(ApprovedApplication)
+    // Applications are only taken within the window
+    assert!(matches!(
+        system.apply(&candidate, job_id),
+        Err(ErrorVariant::OutsideApplicationWindow {
+            opens_at: Some(200),
+            closes_at: Some(300),
+            ..
+        })
+    ));
+    now.store(200, Ordering::SeqCst);
+    system.apply(&candidate, job_id).unwrap();
+    system.interview(&recruiter, "test", job_id).unwrap();
+    system.approve(&manager, "test", job_id).unwrap();
+    let offer_id = system
+        .create_offer(&manager, "test", job_id, 50_000, 1_000, 350)
+        .unwrap();
+    system.pause_job(&recruiter, paused_id).unwrap();
+    // Only admins run the maintenance, with a valid session
+    let admin = logged_in_with_role(&system, "ops", Role::Admin);
+    assert!(matches!(
+        system.run_maintenance(&candidate),
+        Err(ErrorVariant::PermissionDenied(Action::RunMaintenance))
+    ));
+    let logged_out = system.login("ops", "ops").unwrap();
+    system.logout(&logged_out).unwrap();
+    assert!(matches!(
+        system.run_maintenance(&logged_out),
+        Err(ErrorVariant::Unauthorized)
+    ));
+    assert_eq!(
+        system.run_maintenance(&admin).unwrap(),
+        MaintenanceReport::default()
+    );
+    now.store(300, Ordering::SeqCst);
+    assert!(matches!(
+        system.apply(&other, job_id),
+        Err(ErrorVariant::OutsideApplicationWindow { .. })
+    ));
+
+    // Maintenance closes the open and on hold jobs whose window ended
+    now.store(400, Ordering::SeqCst);
+    assert_eq!(
+        system.run_maintenance(&admin).unwrap(),
+        MaintenanceReport {
+            closed_jobs: vec![(job_id, "Engineer".into()), (paused_id, "Designer".into())],
+            expired_offers: 1,
+        }
+    );
+    assert_eq!(job(job_id).state, JobState::Closed);
+    assert_eq!(job(paused_id).state, JobState::Closed);
+    assert_eq!(job(draft_id).state, JobState::Draft);
+    assert_eq!(job(forever_id).state, JobState::Open);
+    assert_eq!(
+        system.run_maintenance(&admin).unwrap(),
+        MaintenanceReport::default()
+    );
+    assert!(matches!(
+        system.apply(&other, job_id),
+        Err(ErrorVariant::JobNotOpen {
+            state: JobState::Closed,
+            ..
+        })
+    ));
+    // The applications of closed jobs keep going
+    assert_eq!(
+        system.my_offers(&candidate).unwrap()[0],
+        Offer {
+            id: offer_id,
+            job_id,
+            candidate_id: candidate.user_id,
+            salary: 50_000,
+            start_date: 1_000,
+            expires_at: 350,
+            status: OfferStatus::Expired,
+            created_at: 200,
+        }
+    );
+    let offer_id = system
+        .create_offer(&manager, "test", job_id, 50_000, 1_000, 500)
+        .unwrap();
+    system.send_offer(&manager, offer_id).unwrap();
+
+    // Extending the window opens the job again, ending it closes the job right away
+    system
+        .set_application_window(&recruiter, job_id, None, Some(500))
+        .unwrap();
+    assert_eq!(job(job_id).state, JobState::Open);
+    system.apply(&other, job_id).unwrap();
+    system
+        .set_application_window(&recruiter, forever_id, None, Some(400))
+        .unwrap();
+    assert_eq!(job(forever_id).state, JobState::Closed);
+
+    // And accepting offers fills closed jobs too
+    system
+        .set_application_window(&recruiter, job_id, None, Some(400))
+        .unwrap();
+    assert_eq!(job(job_id).state, JobState::Closed);
+    system.accept_offer(&candidate, offer_id).unwrap();
+    assert_eq!(job(job_id).state, JobState::Filled);
+}
+
+#[test]
+fn application_window() {
+    check_application_window(MemoryStore::new());
+    #[cfg(feature = "sqlite")]
+    check_application_window(SqliteStore::open_in_memory().unwrap());
+}
+
+#[test]
+fn job_states() {
+    use std::convert::TryFrom;
+    assert!(JobState::Draft.can_move(JobState::Open));
+    assert!(JobState::OnHold.can_move(JobState::Filled));
+    assert!(!JobState::Draft.can_move(JobState::Filled));
+    assert!(!JobState::Filled.can_move(JobState::Cancelled));
+    assert!(!JobState::Cancelled.can_move(JobState::OnHold));
+    for state in [
+        JobState::Open,
+        JobState::Filled,
+        JobState::Draft,
+        JobState::OnHold,
+        JobState::Cancelled,
+        JobState::Closed,
+    ]
+    .iter()
+    {
+        assert!(!state.can_move(*state));
+        assert_eq!(JobState::try_from(*state as u8), Ok(*state));
+    }
+    // Unknown states aren't taken as open
+    assert_eq!(JobState::try_from(6), Err(6));
+}
+
+fn check_profiles<S: Store + 'static>(store: S) {
+    let system = new_system(store);
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    for user in ["test", "test2"].iter() {
+        system
+            .register_candidate(user.to_string(), user.to_string())
+            .unwrap();
+    }
+    let candidate = system.login("test", "test").unwrap();
+    let other = system.login("test2", "test2").unwrap();
+    assert_eq!(
+        system.my_profile(&candidate).unwrap(),
+        Profile {
+            candidate_id: candidate.user_id,
+            ..Profile::default()
+        }
+    );
+
+    let profile = Profile::new("Ada Lovelace")
+        .email("ada@example.com")
+        .phone("+44 (20) 1234-5678")
+        .location("London")
+        .skill("Rust", Proficiency::Expert)
+        .skill("SQL", Proficiency::Intermediate)
+        .experience(
+            "Analytical Engines",
+            "Engineer",
+            YearMonth::new(2018, 3),
+            Some(YearMonth::new(2021, 12)),
+        )
+        .experience("Difference Co", "Lead", YearMonth::new(2022, 1), None)
+        .education(
+            "University of London",
+            "Mathematics",
+            YearMonth::new(2014, 9),
+            Some(YearMonth::new(2018, 6)),
         );
+    // Candidates can only save their own profile
+    system
+        .update_profile(
+            &candidate,
+            &Profile {
+                candidate_id: other.user_id,
+                ..profile.clone()
+            },
+        )
+        .unwrap();
+    let saved = Profile {
+        candidate_id: candidate.user_id,
+        ..profile.clone()
+    };
+    assert_eq!(system.my_profile(&candidate).unwrap(), saved);
+    assert_eq!(
+        system.my_profile(&other).unwrap(),
+        Profile {
+            candidate_id: other.user_id,
+            ..Profile::default()
+        }
+    );
+
+    for invalid in [
+        profile.clone().email("ada.example.com"),
+        profile.clone().email("ada@example"),
+        profile.clone().phone("call me"),
+        profile.clone().skill("rust", Proficiency::Beginner),
+        profile.clone().skill(" ", Proficiency::Beginner),
+        profile.clone().experience(
+            "Later Co",
+            "Engineer",
+            YearMonth::new(2020, 5),
+            Some(YearMonth::new(2020, 4)),
+        ),
+        profile
+            .clone()
+            .education("School", "Degree", YearMonth::new(2020, 13), None),
+    ]
+    .iter()
+    {
+        assert!(matches!(
+            system.update_profile(&candidate, invalid),
+            Err(ErrorVariant::InvalidProfile(_))
+        ));
+    }
+    assert!(matches!(
+        system.update_profile(&recruiter, &profile),
+        Err(ErrorVariant::PermissionDenied(Action::EditProfile))
+    ));
+
+    // Recruiters see the profiles, of the applicants to a job too
+    assert!(matches!(
+        system.candidate_profile(&other, "test"),
+        Err(ErrorVariant::PermissionDenied(Action::ViewProfiles))
+    ));
+    assert_eq!(system.candidate_profile(&recruiter, "test").unwrap(), saved);
+    assert!(matches!(
+        system.candidate_profile(&recruiter, "missing"),
+        Err(ErrorVariant::CandidateNotFound(_))
+    ));
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    assert!(system
+        .applicant_profiles(&recruiter, job_id)
+        .unwrap()
+        .is_empty());
+    system.apply(&other, job_id).unwrap();
+    system.apply(&candidate, job_id).unwrap();
+    assert_eq!(
+        system.applicant_profiles(&recruiter, job_id).unwrap(),
+        vec![
+            ("test".to_string(), saved),
+            (
+                "test2".to_string(),
+                Profile {
+                    candidate_id: other.user_id,
+                    ..Profile::default()
+                }
+            )
+        ]
+    );
+    assert!(matches!(
+        system.applicant_profiles(&recruiter, job_id + 1),
+        Err(ErrorVariant::JobNotFound(_))
+    ));
+
+    // Saving replaces the whole profile
+    system
+        .update_profile(
+            &candidate,
+            &Profile::new("Ada").skill("Go", Proficiency::Beginner),
+        )
+        .unwrap();
+    assert_eq!(
+        system.my_profile(&candidate).unwrap(),
+        Profile {
+            candidate_id: candidate.user_id,
+            ..Profile::new("Ada").skill("Go", Proficiency::Beginner)
+        }
+    );
+}
+
+#[test]
+fn profiles() {
+    check_profiles(MemoryStore::new());
+    #[cfg(feature = "sqlite")]
+    check_profiles(SqliteStore::open_in_memory().unwrap());
+}
 
-        // Gets closed
-        let _ = system.register_candidate("test2".to_string(), "test".to_string());
-        let logged_in_user = system
-            .login(&"test2".to_string(), &"test".to_string())
+/// `dir` keeps the documents on the filesystem instead of the store
+fn check_attachments<S: Store + 'static>(store: S, dir: Option<std::path::PathBuf>) {
+    let mut builder = test_builder(store).max_attachment_size(64);
+    if let Some(dir) = &dir {
+        builder = builder.attachments_dir(dir.clone());
+    }
+    let system = builder.build().unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let admin = system.login("admin", "admin").unwrap();
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    for user in ["test", "test2"].iter() {
+        system
+            .register_candidate(user.to_string(), user.to_string())
             .unwrap();
-        assert!(system
-            .apply(
-                &"test2".to_string(),
-                logged_in_user.token,
-                logged_in_user.user_id,
-                job_posting_id
+    }
+    let candidate = system.login("test", "test").unwrap();
+    let other = system.login("test2", "test2").unwrap();
+    let resume = b"%PDF-1.7 resume";
+
+    // Only candidates attach, and only to their applications
+    assert!(matches!(
+        system.attach_document(
+            &candidate,
+            job_id,
+            DocumentKind::Resume,
+            "cv.pdf",
+            "application/pdf",
+            resume
+        ),
+        Err(ErrorVariant::ApplicationNotFound { .. })
+    ));
+    assert!(matches!(
+        system.attach_document(
+            &recruiter,
+            job_id,
+            DocumentKind::Resume,
+            "cv.pdf",
+            "application/pdf",
+            resume
+        ),
+        Err(ErrorVariant::PermissionDenied(Action::AttachDocuments))
+    ));
+    system.apply(&candidate, job_id).unwrap();
+    system.apply(&other, job_id).unwrap();
+
+    // Size, type and content are checked
+    for (file_name, mime_type, content) in [
+        ("cv.pdf", "application/pdf", &[b'a'; 65][..]),
+        ("cv.exe", "application/x-msdownload", b"MZ"),
+        ("cv.pdf", "application/pdf", b"not a pdf"),
+        ("cv.txt", "text/plain", b"\xff\xfe"),
+        ("../cv.pdf", "application/pdf", resume),
+        ("cv.pdf", "application/pdf", b""),
+    ]
+    .iter()
+    {
+        assert!(matches!(
+            system.attach_document(
+                &candidate,
+                job_id,
+                DocumentKind::Resume,
+                file_name,
+                mime_type,
+                content
+            ),
+            Err(ErrorVariant::InvalidAttachment(_))
+        ));
+    }
+
+    let first = system
+        .attach_document(
+            &candidate,
+            job_id,
+            DocumentKind::Resume,
+            "cv.pdf",
+            "application/pdf",
+            resume,
+        )
+        .unwrap();
+    let letter = system
+        .attach_document(
+            &candidate,
+            job_id,
+            DocumentKind::CoverLetter,
+            "letter.md",
+            "text/markdown",
+            "# Hi".as_bytes(),
+        )
+        .unwrap();
+    // The same content is stored once
+    let copy = system
+        .attach_document(
+            &other,
+            job_id,
+            DocumentKind::Resume,
+            "mine.pdf",
+            "application/pdf",
+            resume,
+        )
+        .unwrap();
+    let sha256 = attachment::content_hash(resume);
+    if let Some(dir) = &dir {
+        let stored = attachment::ContentDir { root: dir }.path(&sha256);
+        assert_eq!(std::fs::read(stored).unwrap(), resume);
+    }
+
+    let attachments = system.list_attachments(&candidate, "test", job_id).unwrap();
+    assert_eq!(
+        attachments
+            .iter()
+            .map(|attachment| (attachment.id, attachment.kind))
+            .collect::<Vec<_>>(),
+        vec![
+            (first, DocumentKind::Resume),
+            (letter, DocumentKind::CoverLetter)
+        ]
+    );
+    assert_eq!(
+        (attachments[0].size, &attachments[0].sha256),
+        (resume.len() as u64, &sha256)
+    );
+    assert_eq!(
+        system.list_attachments(&recruiter, "test", job_id).unwrap(),
+        attachments
+    );
+
+    // Candidates only see their own documents
+    assert!(matches!(
+        system.list_attachments(&other, "test", job_id),
+        Err(ErrorVariant::PermissionDenied(Action::ViewProfiles))
+    ));
+    assert!(matches!(
+        system.download_attachment(&other, first),
+        Err(ErrorVariant::AttachmentNotFound(_))
+    ));
+    assert_eq!(
+        system.download_attachment(&recruiter, first).unwrap(),
+        (attachments[0].clone(), resume.to_vec())
+    );
+    assert_eq!(
+        system.download_attachment(&candidate, letter).unwrap().1,
+        b"# Hi"
+    );
+
+    // Owners and admins delete, the content goes with the last attachment having it
+    assert!(matches!(
+        system.delete_attachment(&recruiter, first),
+        Err(ErrorVariant::PermissionDenied(Action::ManageDocuments))
+    ));
+    assert!(matches!(
+        system.delete_attachment(&other, first),
+        Err(ErrorVariant::AttachmentNotFound(_))
+    ));
+    system.delete_attachment(&candidate, first).unwrap();
+    assert!(matches!(
+        system.download_attachment(&candidate, first),
+        Err(ErrorVariant::AttachmentNotFound(_))
+    ));
+    assert_eq!(system.download_attachment(&other, copy).unwrap().1, resume);
+    system.delete_attachment(&admin, copy).unwrap();
+    if let Some(dir) = &dir {
+        assert!(!attachment::ContentDir { root: dir }.path(&sha256).exists());
+    }
+    assert_eq!(
+        system
+            .list_attachments(&candidate, "test", job_id)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn attachments() {
+    check_attachments(MemoryStore::new(), None);
+    #[cfg(feature = "sqlite")]
+    check_attachments(SqliteStore::open_in_memory().unwrap(), None);
+    let dir = std::env::temp_dir().join(format!("hr_attachments_{}", std::process::id()));
+    check_attachments(MemoryStore::new(), Some(dir.clone()));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn resume_parsing() {
+    let resume = "# Ada Lovelace
+
+ada@example.com | Phone: +44 20 7946 0958
+
+## Skills
+Rust (expert), Python, machine learning, Go
+
+## Experience
+### Senior Engineer, Analytical Engines
+2019-03 - Present
+Built the engine in Rust and C++.
+
+Engineer at Babbage & Co (Mar 2016 to Feb 2019)
+Intern, Somewhere 2015 - 2014
+
+## Education
+BSc Mathematics, University of London, 2012 - 2015
+";
+    let skills: Vec<String> = resume::DEFAULT_SKILL_DICTIONARY
+        .iter()
+        .map(|skill| skill.to_string())
+        .collect();
+    let parsed = resume::parse_resume(resume, &skills);
+    assert_eq!(
+        parsed.profile,
+        Profile::new("Ada Lovelace")
+            .email("ada@example.com")
+            .phone("+44 20 7946 0958")
+            .skill("Rust", Proficiency::Expert)
+            .skill("Python", Proficiency::Intermediate)
+            .skill("Machine Learning", Proficiency::Intermediate)
+            .skill("C++", Proficiency::Intermediate)
+            .experience(
+                "Analytical Engines",
+                "Senior Engineer",
+                YearMonth::new(2019, 3),
+                None
+            )
+            .experience(
+                "Babbage & Co",
+                "Engineer",
+                YearMonth::new(2016, 3),
+                Some(YearMonth::new(2019, 2))
             )
-            .is_err());
+            .education(
+                "University of London",
+                "BSc Mathematics",
+                YearMonth::new(2012, 1),
+                Some(YearMonth::new(2015, 12))
+            )
+    );
+    assert!(parsed.profile.validate().is_ok());
+
+    let confidence = |field, line| {
+        parsed
+            .report
+            .findings
+            .iter()
+            .find(|finding| finding.field == field && finding.line == line)
+            .map(|finding| finding.confidence)
+    };
+    assert_eq!(confidence(ProfileField::Email, 3), Some(Confidence::High));
+    assert_eq!(confidence(ProfileField::Phone, 3), Some(Confidence::High));
+    // Skills are surer in their own section
+    assert_eq!(confidence(ProfileField::Skill, 6), Some(Confidence::High));
+    assert_eq!(
+        confidence(ProfileField::Skill, 11),
+        Some(Confidence::Medium)
+    );
+    // A period named by the line before it, or only with years, needs a check
+    assert_eq!(
+        confidence(ProfileField::Experience, 10),
+        Some(Confidence::Medium)
+    );
+    assert_eq!(
+        confidence(ProfileField::Experience, 13),
+        Some(Confidence::High)
+    );
+    assert_eq!(
+        confidence(ProfileField::Education, 17),
+        Some(Confidence::Medium)
+    );
+    assert_eq!(parsed.report.to_review().count(), 4);
+    // The reversed period was left out
+    assert_eq!(parsed.report.warnings.len(), 1);
+    assert!(parsed.report.warnings[0].contains("line 14"));
+
+    let empty = resume::parse_resume("nothing to see here", &skills);
+    assert_eq!(empty.profile, Profile::default());
+    assert_eq!(empty.report.warnings.len(), 3);
+
+    // The draft keeps the current profile, it's only saved when confirmed
+    let system = test_builder(MemoryStore::new())
+        .skill_dictionary(&["Rust", "Haskell"])
+        .build()
+        .unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    system
+        .register_candidate("test".to_string(), "test".to_string())
+        .unwrap();
+    let candidate = system.login("test", "test").unwrap();
+    let current = Profile::new("Ada King")
+        .location("London")
+        .skill("Haskell", Proficiency::Advanced);
+    system.update_profile(&candidate, &current).unwrap();
+    let draft = system.draft_profile(&candidate, resume).unwrap();
+    assert_eq!(draft.profile.full_name, "Ada Lovelace");
+    assert_eq!(draft.profile.location, "London");
+    assert_eq!(
+        draft
+            .profile
+            .skills
+            .iter()
+            .map(|skill| skill.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Haskell", "Rust"]
+    );
+    assert_eq!(system.my_profile(&candidate).unwrap().full_name, "Ada King");
+    system.update_profile(&candidate, &draft.profile).unwrap();
+    assert_eq!(
+        system.my_profile(&candidate).unwrap(),
+        Profile {
+            candidate_id: candidate.user_id,
+            ..draft.profile.clone()
+        }
+    );
+    assert!(matches!(
+        system.draft_profile(&recruiter, resume),
+        Err(ErrorVariant::PermissionDenied(Action::EditProfile))
+    ));
+
+    // Attached text résumés can be parsed too
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    system.apply(&candidate, job_id).unwrap();
+    let attached = system
+        .attach_document(
+            &candidate,
+            job_id,
+            DocumentKind::Resume,
+            "cv.md",
+            "text/markdown",
+            resume.as_bytes(),
+        )
+        .unwrap();
+    let pdf = system
+        .attach_document(
+            &candidate,
+            job_id,
+            DocumentKind::Resume,
+            "cv.pdf",
+            "application/pdf",
+            b"%PDF-1.7",
+        )
+        .unwrap();
+    assert_eq!(
+        system
+            .draft_profile_from_attachment(&candidate, attached)
+            .unwrap()
+            .report,
+        draft.report
+    );
+    assert!(matches!(
+        system.draft_profile_from_attachment(&candidate, pdf),
+        Err(ErrorVariant::InvalidAttachment(_))
+    ));
+}
+
+fn check_questionnaires<S: Store + 'static>(store: S) {
+    let system = new_system(store);
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let manager = logged_in_with_role(&system, "manager", Role::HiringManager);
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    let questions = vec![
+        Question::yes_no("Can you work in the EU?").knockout(Knockout::Requires(true)),
+        Question::numeric("Years of experience").knockout(Knockout::Range {
+            min: Some(2.0),
+            max: None,
+        }),
+        Question::multiple_choice("Languages", &["Rust", "C++", "Go"])
+            .knockout(Knockout::AnyOf(vec!["Rust".into(), "C++".into()])),
+        Question::single_choice("Notice period", &["None", "1 month", "3 months"]),
+        Question::free_text("Why us?").required(),
+    ];
+
+    // Only job creators set questions, which must be valid
+    let candidate = logged_in_with_role(&system, "test", Role::Candidate);
+    assert!(matches!(
+        system.set_questionnaire(&candidate, job_id, &questions),
+        Err(ErrorVariant::PermissionDenied(Action::CreateJob))
+    ));
+    for invalid in [
+        vec![Question::free_text(" ")],
+        vec![Question::single_choice("Pick", &["Only one"])],
+        vec![Question::yes_no("Twice?"), Question::yes_no("Twice?")],
+        vec![Question::free_text("Why us?").knockout(Knockout::Requires(true))],
+        vec![Question::single_choice("Pick", &["A", "B"])
+            .knockout(Knockout::AnyOf(vec!["C".into()]))],
+        vec![Question::numeric("Years").knockout(Knockout::Range {
+            min: Some(5.0),
+            max: Some(1.0),
+        })],
+        vec![Question {
+            required: false,
+            ..Question::yes_no("Optional knockout?").knockout(Knockout::Requires(true))
+        }],
+    ]
+    .iter()
+    {
+        assert!(matches!(
+            system.set_questionnaire(&recruiter, job_id, invalid),
+            Err(ErrorVariant::InvalidQuestionnaire(_))
+        ));
+    }
+    assert!(matches!(
+        system.set_questionnaire(&recruiter, job_id + 1, &questions),
+        Err(ErrorVariant::JobNotFound(_))
+    ));
+    system
+        .set_questionnaire(&recruiter, job_id, &questions)
+        .unwrap();
+    assert_eq!(system.questionnaire(&candidate, job_id).unwrap(), questions);
+
+    // Answers must fit the questions, required ones included
+    for invalid in [
+        vec![],
+        vec![(0, Answer::YesNo(true)), (1, Answer::Number(3.0))],
+        vec![
+            (0, Answer::Text("Yes".into())),
+            (1, Answer::Number(3.0)),
+            (2, Answer::Choices(vec!["Rust".into()])),
+            (4, Answer::Text("Rust".into())),
+        ],
+        vec![
+            (0, Answer::YesNo(true)),
+            (1, Answer::Number(3.0)),
+            (2, Answer::Choices(vec!["Rust".into(), "Rust".into()])),
+            (4, Answer::Text("Rust".into())),
+        ],
+        vec![
+            (0, Answer::YesNo(true)),
+            (1, Answer::Number(3.0)),
+            (2, Answer::Choices(vec!["Rust".into()])),
+            (3, Answer::Choice("Tomorrow".into())),
+            (4, Answer::Text("Rust".into())),
+        ],
+        vec![
+            (0, Answer::YesNo(true)),
+            (0, Answer::YesNo(false)),
+            (1, Answer::Number(3.0)),
+            (2, Answer::Choices(vec!["Rust".into()])),
+            (4, Answer::Text("Rust".into())),
+        ],
+        vec![
+            (0, Answer::YesNo(true)),
+            (1, Answer::Number(f64::NAN)),
+            (2, Answer::Choices(vec!["Rust".into()])),
+            (4, Answer::Text("Rust".into())),
+            (5, Answer::Text("Extra".into())),
+        ],
+    ]
+    .iter()
+    {
+        assert!(matches!(
+            system.apply_with_answers(&candidate, job_id, invalid),
+            Err(ErrorVariant::InvalidAnswers(_))
+        ));
+    }
+    assert!(matches!(
+        system.apply(&candidate, job_id),
+        Err(ErrorVariant::InvalidAnswers(_))
+    ));
+    let answers = vec![
+        (0, Answer::YesNo(true)),
+        (1, Answer::Number(2.0)),
+        (2, Answer::Choices(vec!["Go".into(), "C++".into()])),
+        (4, Answer::Text("Rust".into())),
+    ];
+    system
+        .apply_with_answers(&candidate, job_id, &answers)
+        .unwrap();
+
+    // Failing knockout rules rejects the application right away
+    let knocked_out = logged_in_with_role(&system, "test2", Role::Candidate);
+    system
+        .apply_with_answers(
+            &knocked_out,
+            job_id,
+            &[
+                (0, Answer::YesNo(false)),
+                (1, Answer::Number(1.5)),
+                (2, Answer::Choices(vec!["Rust".into()])),
+                (3, Answer::Choice("None".into())),
+                (4, Answer::Text("Rust".into())),
+            ],
+        )
+        .unwrap();
+    let job = system.list_jobs(&recruiter).unwrap().remove(0);
+    assert_eq!(job.applicants["test"], "Applied");
+    assert_eq!(job.applicants["test2"], "Rejected");
+    assert_eq!(
+        job.rejections["test2"],
+        Rejection {
+            reason: "knockout".into(),
+            note: "Can you work in the EU?; Years of experience".into(),
+        }
+    );
+    assert!(matches!(
+        system.interview(&recruiter, "test2", job_id),
+        Err(ErrorVariant::InvalidTransition { .. })
+    ));
+    system.interview(&recruiter, "test", job_id).unwrap();
+
+    // Answers are seen by their candidate and by who can view profiles
+    let seen = system
+        .application_answers(&candidate, "test", job_id)
+        .unwrap();
+    assert_eq!(seen.len(), 4);
+    assert_eq!(seen[1], (questions[1].clone(), Answer::Number(2.0)));
+    assert_eq!(
+        system
+            .application_answers(&manager, "test", job_id)
+            .unwrap(),
+        seen
+    );
+    assert!(matches!(
+        system.application_answers(&knocked_out, "test", job_id),
+        Err(ErrorVariant::PermissionDenied(Action::ViewProfiles))
+    ));
+    assert!(matches!(
+        system.application_answers(&manager, "recruiter", job_id),
+        Err(ErrorVariant::ApplicationNotFound { .. })
+    ));
+
+    // Questions can't change under existing answers
+    assert!(matches!(
+        system.set_questionnaire(&recruiter, job_id, &[]),
+        Err(ErrorVariant::InvalidQuestionnaire(_))
+    ));
+
+    // Knockout rules need somewhere to reject applications to
+    let pipeline_id = system
+        .create_pipeline(
+            &manager,
+            &Pipeline::new("No rejections")
+                .stage("Applied", &["Hired"])
+                .terminal("Hired", Outcome::Hired),
+        )
+        .unwrap();
+    let other_job = system
+        .create_job_posting_with_pipeline(&recruiter, "Designer".to_string(), pipeline_id)
+        .unwrap();
+    assert!(matches!(
+        system.set_questionnaire(&recruiter, other_job, &questions),
+        Err(ErrorVariant::InvalidQuestionnaire(_))
+    ));
+    system
+        .set_questionnaire(&recruiter, other_job, &questions[3..])
+        .unwrap();
+    system
+        .apply_with_answers(&candidate, other_job, &[(1, Answer::Text("Design".into()))])
+        .unwrap();
+}
+
+#[test]
+fn questionnaires() {
+    check_questionnaires(MemoryStore::new());
+    #[cfg(feature = "sqlite")]
+    check_questionnaires(SqliteStore::open_in_memory().unwrap());
+}
+
+fn check_matching<S: Store + 'static>(store: S) {
+    // 2024-06-15, current jobs last until June 2024
+    let system = test_builder(store).clock(|| 1_718_409_600).build().unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    system
+        .update_job_posting(
+            &recruiter,
+            job_id,
+            &JobDetails::new("Builds the HR system").seniority(Seniority::Senior),
+        )
+        .unwrap();
+    let skills = vec![
+        JobSkill::required("Rust", Proficiency::Advanced),
+        JobSkill::required("SQL", Proficiency::Intermediate),
+        JobSkill::nice_to_have("Docker", Proficiency::Beginner),
+    ];
+
+    // Only job creators set the skills, each one once
+    let ada = logged_in_with_role(&system, "ada", Role::Candidate);
+    assert!(matches!(
+        system.set_job_skills(&ada, job_id, &skills),
+        Err(ErrorVariant::PermissionDenied(Action::CreateJob))
+    ));
+    assert!(matches!(
+        system.set_job_skills(
+            &recruiter,
+            job_id,
+            &[
+                JobSkill::required("Rust", Proficiency::Advanced),
+                JobSkill::nice_to_have("rust", Proficiency::Expert),
+            ]
+        ),
+        Err(ErrorVariant::InvalidJobDetails(_))
+    ));
+    assert!(matches!(
+        system.set_job_skills(&recruiter, job_id + 1, &skills),
+        Err(ErrorVariant::JobNotFound(_))
+    ));
+    system.set_job_skills(&recruiter, job_id, &skills).unwrap();
+    assert_eq!(system.job_skills(&ada, job_id).unwrap(), skills);
+
+    // Meets everything, overlapping jobs count once: 2018-01 to 2024-06 is 6.5 years
+    system
+        .update_profile(
+            &ada,
+            &Profile::new("Ada")
+                .skill("Rust", Proficiency::Expert)
+                .skill("SQL", Proficiency::Advanced)
+                .skill("Docker", Proficiency::Beginner)
+                .experience(
+                    "Acme",
+                    "Engineer",
+                    YearMonth::new(2018, 1),
+                    Some(YearMonth::new(2021, 12)),
+                )
+                .experience("Initech", "Engineer", YearMonth::new(2021, 6), None),
+        )
+        .unwrap();
+    system.apply(&ada, job_id).unwrap();
+    // Below the wanted Rust, no Docker and 25 months of experience
+    let bob = logged_in_with_role(&system, "bob", Role::Candidate);
+    system
+        .update_profile(
+            &bob,
+            &Profile::new("Bob")
+                .skill("Rust", Proficiency::Intermediate)
+                .skill("sql", Proficiency::Intermediate)
+                .experience("Acme", "Engineer", YearMonth::new(2022, 6), None),
+        )
+        .unwrap();
+    system.apply(&bob, job_id).unwrap();
+    // No profile at all
+    let cy = logged_in_with_role(&system, "cy", Role::Candidate);
+    system.apply(&cy, job_id).unwrap();
+    // Withdrawn applications aren't ranked
+    let dee = logged_in_with_role(&system, "dee", Role::Candidate);
+    system.apply(&dee, job_id).unwrap();
+    system.withdraw(&dee, job_id).unwrap();
+
+    let ranking = system.rank_applicants(&recruiter, job_id).unwrap();
+    let users: Vec<_> = ranking.iter().map(|(user, _)| user.as_str()).collect();
+    assert_eq!(users, ["ada", "bob", "cy"]);
+    let (_, ada_score) = &ranking[0];
+    assert!(ada_score.meets_requirements());
+    assert!((ada_score.score - 100.0).abs() < 1e-9);
+    assert!((ada_score.experience.as_ref().unwrap().years - 6.5).abs() < 1e-9);
+    let (_, bob_score) = &ranking[1];
+    assert!(!bob_score.meets_requirements());
+    assert_eq!(bob_score.required[0].has, Some(Proficiency::Intermediate));
+    assert_eq!(bob_score.required[1].credit, 1.0);
+    assert_eq!(bob_score.nice_to_have[0].has, None);
+    // (2/3 + 1) / 2 of 60 for the required skills, 0 of 20 for the nice to have and 25/60 of 20 for the experience
+    assert!((bob_score.score - (50.0 + 25.0 / 3.0)).abs() < 1e-9);
+    let explained = bob_score.to_string();
+    assert!(explained.starts_with("58/100, doesn't meet the requirements"));
+    assert!(explained.contains("Rust: Advanced wanted, has Intermediate(67%)"));
+    assert!(explained.contains("Docker: Beginner wanted, missing(0%)"));
+    assert!(explained.contains("2.1 years of the 5 expected for Senior(42%)"));
+    assert_eq!(ranking[2].1.score, 0.0);
+    assert!(matches!(
+        system.rank_applicants(&ada, job_id),
+        Err(ErrorVariant::PermissionDenied(Action::ViewProfiles))
+    ));
+    let unranked = system
+        .create_job_posting(&recruiter, "Anything".to_string())
+        .unwrap();
+    assert!(matches!(
+        system.rank_applicants(&recruiter, unranked),
+        Err(ErrorVariant::InvalidJobDetails(_))
+    ));
+
+    // Suggestions are open jobs the candidate didn't apply to and matches something of
+    let designer = system
+        .create_job_posting(&recruiter, "Designer".to_string())
+        .unwrap();
+    system
+        .set_job_skills(
+            &recruiter,
+            designer,
+            &[JobSkill::required("Figma", Proficiency::Beginner)],
+        )
+        .unwrap();
+    let draft = system
+        .create_draft_job_posting(&recruiter, "Draft".to_string(), None)
+        .unwrap();
+    system.set_job_skills(&recruiter, draft, &skills).unwrap();
+    let backend = system
+        .create_job_posting(&recruiter, "Backend".to_string())
+        .unwrap();
+    system
+        .set_job_skills(
+            &recruiter,
+            backend,
+            &[JobSkill::required("rust", Proficiency::Beginner)],
+        )
+        .unwrap();
+    let suggestions = system.suggest_jobs(&bob).unwrap();
+    let names: Vec<_> = suggestions
+        .iter()
+        .map(|(job, _)| job.name.as_str())
+        .collect();
+    assert_eq!(names, ["Backend"]);
+    let eve = logged_in_with_role(&system, "eve", Role::Candidate);
+    system
+        .update_profile(
+            &eve,
+            &Profile::new("Eve").skill("Rust", Proficiency::Expert),
+        )
+        .unwrap();
+    let suggestions = system.suggest_jobs(&eve).unwrap();
+    let names: Vec<_> = suggestions
+        .iter()
+        .map(|(job, _)| job.name.as_str())
+        .collect();
+    assert_eq!(names, ["Backend", "Engineer"]);
+    // Rust but no SQL nor experience
+    assert!((suggestions[1].1.score - 30.0).abs() < 1e-9);
+    assert!(matches!(
+        system.suggest_jobs(&recruiter),
+        Err(ErrorVariant::PermissionDenied(Action::Apply))
+    ));
+}
+
+#[test]
+fn matching() {
+    check_matching(MemoryStore::new());
+    #[cfg(feature = "sqlite")]
+    check_matching(SqliteStore::open_in_memory().unwrap());
+    assert_eq!(YearMonth::from_timestamp(0), YearMonth::new(1970, 1));
+    assert_eq!(
+        YearMonth::from_timestamp(951_782_400),
+        YearMonth::new(2000, 2)
+    );
+    assert_eq!(
+        YearMonth::from_timestamp(1_718_409_600),
+        YearMonth::new(2024, 6)
+    );
+}
+
+fn check_search<S: Store + 'static>(store: S) {
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+    let now = Arc::new(AtomicI64::new(100));
+    let clock = now.clone();
+    let system = test_builder(store)
+        .clock(move || clock.load(Ordering::SeqCst))
+        .build()
+        .unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let post = |name: &str, details: JobDetails, posted_at: Timestamp| {
+        now.store(posted_at, Ordering::SeqCst);
+        let job_id = system
+            .create_job_posting(&recruiter, name.to_string())
+            .unwrap();
+        system
+            .update_job_posting(&recruiter, job_id, &details)
+            .unwrap();
+        job_id
+    };
+    post(
+        "Rust Engineer",
+        JobDetails::new("Rust services, more Rust")
+            .department("Engineering")
+            .location("Berlin", Workplace::OnSite),
+        100,
+    );
+    post(
+        "Backend Developer",
+        JobDetails::new("APIs in Rust and SQL")
+            .department("Engineering")
+            .location("Remote, Germany", Workplace::Remote),
+        200,
+    );
+    let designer = post(
+        "Designer",
+        JobDetails::new("Figma").location("Berlin", Workplace::Hybrid),
+        300,
+    );
+    now.store(400, Ordering::SeqCst);
+    system
+        .create_draft_job_posting(&recruiter, "Data Engineer".to_string(), None)
+        .unwrap();
+
+    let ada = logged_in_with_role(&system, "ada", Role::Candidate);
+    let names = |search: JobSearch| -> Vec<String> {
+        system
+            .search_jobs(&ada, &search)
+            .unwrap()
+            .into_iter()
+            .map(|job| job.name)
+            .collect()
+    };
+    // More mentions rank higher, words are prefixes and ignore case and punctuation
+    assert_eq!(
+        names(JobSearch::new("rust")),
+        ["Rust Engineer", "Backend Developer"]
+    );
+    assert_eq!(
+        names(JobSearch::new("RUST!")),
+        names(JobSearch::new("rust"))
+    );
+    assert_eq!(names(JobSearch::new("rust sql")), ["Backend Developer"]);
+    let mut engineering = names(JobSearch::new("eng"));
+    engineering.sort();
+    assert_eq!(
+        engineering,
+        ["Backend Developer", "Data Engineer", "Rust Engineer"]
+    );
+    assert!(names(JobSearch::new("golang")).is_empty());
+    // Without words every job matching the filters, by ID
+    assert_eq!(
+        names(JobSearch::new("")),
+        [
+            "Rust Engineer",
+            "Backend Developer",
+            "Designer",
+            "Data Engineer"
+        ]
+    );
+
+    assert_eq!(
+        names(JobSearch {
+            state: Some(JobState::Draft),
+            ..JobSearch::new("eng")
+        }),
+        ["Data Engineer"]
+    );
+    assert_eq!(
+        names(JobSearch {
+            department: Some("engineering".into()),
+            ..JobSearch::default()
+        }),
+        ["Rust Engineer", "Backend Developer"]
+    );
+    assert_eq!(
+        names(JobSearch {
+            location: Some("BERLIN".into()),
+            ..JobSearch::default()
+        }),
+        ["Rust Engineer", "Designer"]
+    );
+    assert_eq!(
+        names(JobSearch {
+            posted_after: Some(200),
+            posted_before: Some(400),
+            ..JobSearch::default()
+        }),
+        ["Backend Developer", "Designer"]
+    );
+    let found = system.search_jobs(&ada, &JobSearch::new("rust")).unwrap();
+    assert_eq!(found[0].created_at, 100);
+
+    // Edited jobs are found by their new details
+    system
+        .update_job_posting(
+            &recruiter,
+            designer,
+            &JobDetails::new("Designs for a Rust team"),
+        )
+        .unwrap();
+    assert!(names(JobSearch::new("figma")).is_empty());
+    assert_eq!(names(JobSearch::new("rust")).len(), 3);
+    assert!(names(JobSearch {
+        location: Some("berlin".into()),
+        ..JobSearch::new("design")
+    })
+    .is_empty());
+
+    // Candidates are searched by their profile, by those who can view profiles
+    system
+        .update_profile(
+            &ada,
+            &Profile::new("Ada Lovelace")
+                .location("Berlin")
+                .skill("Rust", Proficiency::Expert)
+                .experience("Acme", "Engineer", YearMonth::new(2018, 1), None),
+        )
+        .unwrap();
+    let bob = logged_in_with_role(&system, "bob", Role::Candidate);
+    system
+        .update_profile(
+            &bob,
+            &Profile::new("Bob")
+                .location("Lisbon")
+                .skill("Figma", Proficiency::Advanced)
+                .education(
+                    "Design School",
+                    "BA",
+                    YearMonth::new(2015, 9),
+                    Some(YearMonth::new(2018, 6)),
+                ),
+        )
+        .unwrap();
+    // Without a profile nobody finds them
+    logged_in_with_role(&system, "carol", Role::Candidate);
+    assert!(matches!(
+        system.search_candidates(&ada, &CandidateSearch::new("rust")),
+        Err(ErrorVariant::PermissionDenied(Action::ViewProfiles))
+    ));
+    let users = |search: CandidateSearch| -> Vec<String> {
+        system
+            .search_candidates(&recruiter, &search)
+            .unwrap()
+            .into_iter()
+            .map(|(user, _)| user)
+            .collect()
+    };
+    assert_eq!(users(CandidateSearch::new("rust")), ["ada"]);
+    assert_eq!(users(CandidateSearch::new("acme engineer")), ["ada"]);
+    assert_eq!(users(CandidateSearch::new("design")), ["bob"]);
+    assert_eq!(users(CandidateSearch::new("")), ["ada", "bob"]);
+    assert!(users(CandidateSearch::new("carol")).is_empty());
+    assert_eq!(
+        users(CandidateSearch {
+            location: Some("lisbon".into()),
+            ..CandidateSearch::default()
+        }),
+        ["bob"]
+    );
+    let found = system
+        .search_candidates(&recruiter, &CandidateSearch::new("lovelace"))
+        .unwrap();
+    assert_eq!(found[0].1.skills[0].name, "Rust");
+
+    // Updated profiles are found by their new details
+    system
+        .update_profile(&ada, &Profile::new("Ada Lovelace").location("Berlin"))
+        .unwrap();
+    assert!(users(CandidateSearch::new("rust")).is_empty());
+    assert_eq!(users(CandidateSearch::new("ada")), ["ada"]);
+}
+
+#[test]
+fn search() {
+    check_search(MemoryStore::new());
+    #[cfg(feature = "sqlite")]
+    check_search(SqliteStore::open_in_memory().unwrap());
+    assert_eq!(search::words("C++ & Rust-lang"), ["c", "rust", "lang"]);
+    let query = search::words("rust eng");
+    assert_eq!(
+        search::relevance(&query, &["Rust Engineer", "Rust"]),
+        Some(3)
+    );
+    assert_eq!(search::relevance(&query, &["Rust"]), None);
+    assert_eq!(search::relevance(&[], &["Rust"]), Some(0));
+}
+
+fn check_offers<S: Store + 'static>(store: S) {
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+    let now = Arc::new(AtomicI64::new(0));
+    let clock = now.clone();
+    let system = test_builder(store)
+        .clock(move || clock.load(Ordering::SeqCst))
+        .build()
+        .unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let manager = logged_in_with_role(&system, "manager", Role::HiringManager);
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    for user in ["test", "test2"].iter() {
+        system
+            .register_candidate(user.to_string(), user.to_string())
+            .unwrap();
+        let candidate = system.login(user, user).unwrap();
+        system.apply(&candidate, job_id).unwrap();
+        system.interview(&recruiter, user, job_id).unwrap();
+    }
+    let candidate = system.login("test", "test").unwrap();
+    let other = system.login("test2", "test2").unwrap();
+    let job = || system.list_jobs(&recruiter).unwrap().remove(0);
+
+    // Only approved applications get offers
+    assert!(matches!(
+        system.create_offer(&recruiter, "test", job_id, 50_000, 1_000, 100),
+        Err(ErrorVariant::PermissionDenied(Action::ManageOffers))
+    ));
+    assert!(matches!(
+        system.create_offer(&manager, "test", job_id, 50_000, 1_000, 100),
+        Err(ErrorVariant::InvalidOffer(_))
+    ));
+    system.approve(&manager, "test", job_id).unwrap();
+    system.approve(&manager, "test2", job_id).unwrap();
+    assert!(matches!(
+        system.create_offer(&manager, "test", job_id, 50_000, 1_000, 0),
+        Err(ErrorVariant::InvalidOffer(_))
+    ));
+    assert!(matches!(
+        system.create_offer(&manager, "test", job_id, u64::MAX, 1_000, 100),
+        Err(ErrorVariant::InvalidOffer(_))
+    ));
+    let first = system
+        .create_offer(&manager, "test", job_id, 50_000, 1_000, 100)
+        .unwrap();
+
+    // The outstanding offer holds the only slot
+    assert_eq!((job().offered, job().open_slots()), (1, 0));
+    assert!(matches!(
+        system.create_offer(&manager, "test", job_id, 60_000, 1_000, 100),
+        Err(ErrorVariant::InvalidOffer(_))
+    ));
+    assert!(matches!(
+        system.create_offer(&manager, "test2", job_id, 50_000, 1_000, 100),
+        Err(ErrorVariant::InvalidOffer(_))
+    ));
+
+    // Candidates only answer sent offers of theirs
+    assert!(matches!(
+        system.accept_offer(&candidate, first),
+        Err(ErrorVariant::InvalidTransition { from, to }) if from == "Drafted" && to == "Accepted"
+    ));
+    system.send_offer(&manager, first).unwrap();
+    assert!(matches!(
+        system.send_offer(&manager, first),
+        Err(ErrorVariant::InvalidTransition { .. })
+    ));
+    assert!(matches!(
+        system.accept_offer(&other, first),
+        Err(ErrorVariant::OfferNotFound(_))
+    ));
+    assert!(matches!(
+        system.accept_offer(&manager, first),
+        Err(ErrorVariant::PermissionDenied(Action::AnswerOffers))
+    ));
+    assert!(system.my_offers(&other).unwrap().is_empty());
+
+    // Declining frees the slot
+    system.decline_offer(&candidate, first).unwrap();
+    assert_eq!(job().open_slots(), 1);
+    assert!(job().state == JobState::Open);
+
+    // And so does expiring
+    let second = system
+        .create_offer(&manager, "test2", job_id, 55_000, 1_000, 200)
+        .unwrap();
+    system.send_offer(&manager, second).unwrap();
+    assert_eq!(job().open_slots(), 0);
+    now.store(200, Ordering::SeqCst);
+    assert_eq!(job().open_slots(), 1);
+    assert!(matches!(
+        system.accept_offer(&other, second),
+        Err(ErrorVariant::InvalidTransition { from, .. }) if from == "Expired"
+    ));
+
+    // Accepting fills it
+    let third = system
+        .create_offer(&manager, "test2", job_id, 60_000, 1_000, 300)
+        .unwrap();
+    system.send_offer(&manager, third).unwrap();
+    system.accept_offer(&other, third).unwrap();
+    assert!(job().state == JobState::Filled);
+    assert_eq!((job().hired, job().offered), (1, 0));
+    assert_eq!(
+        system
+            .offers_for_job(&manager, job_id)
+            .unwrap()
+            .iter()
+            .map(|offer| offer.status)
+            .collect::<Vec<_>>(),
+        vec![
+            OfferStatus::Declined,
+            OfferStatus::Expired,
+            OfferStatus::Accepted
+        ]
+    );
+    assert_eq!(
+        system.my_offers(&other).unwrap()[1],
+        Offer {
+            id: third,
+            job_id,
+            candidate_id: other.user_id,
+            salary: 60_000,
+            start_date: 1_000,
+            expires_at: 300,
+            status: OfferStatus::Accepted,
+            created_at: 200,
+        }
+    );
+}
+
+#[test]
+fn offers() {
+    check_offers(MemoryStore::new());
+    #[cfg(feature = "sqlite")]
+    check_offers(SqliteStore::open_in_memory().unwrap());
+}
+
+#[test]
+fn headcount() {
+    check_headcount(MemoryStore::new());
+    #[cfg(feature = "sqlite")]
+    check_headcount(SqliteStore::open_in_memory().unwrap());
+}
+
+#[test]
+fn pipelines() {
+    check_pipelines(MemoryStore::new());
+    #[cfg(feature = "sqlite")]
+    check_pipelines(SqliteStore::open_in_memory().unwrap());
+}
+
+#[test]
+fn interviews() {
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+    let now = Arc::new(AtomicI64::new(0));
+    let clock = now.clone();
+    let system = test_builder(MemoryStore::new())
+        .clock(move || clock.load(Ordering::SeqCst))
+        .build()
+        .unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let manager = logged_in_with_role(&system, "manager", Role::HiringManager);
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    for user in ["test", "test2"].iter() {
+        system
+            .register_candidate(user.to_string(), user.to_string())
+            .unwrap();
+        let candidate = system.login(user, user).unwrap();
+        system.apply(&candidate, job_id).unwrap();
+    }
+    let candidate = system.login("test", "test").unwrap();
+
+    assert!(matches!(
+        system.schedule_interview(&candidate, "test", job_id, 1_000, 3_600, &["recruiter"]),
+        Err(ErrorVariant::PermissionDenied(Action::Interview))
+    ));
+    assert!(matches!(
+        system.schedule_interview(&recruiter, "test", job_id, 1_000, 3_600, &["test2"]),
+        Err(ErrorVariant::InvalidInterview(_))
+    ));
+    assert!(matches!(
+        system.schedule_interview(&recruiter, "test", job_id, 1_000, 0, &["recruiter"]),
+        Err(ErrorVariant::InvalidInterview(_))
+    ));
+    assert!(matches!(
+        system.schedule_interview(
+            &recruiter,
+            "test",
+            job_id,
+            i64::MAX - 10,
+            3_600,
+            &["recruiter"]
+        ),
+        Err(ErrorVariant::InvalidInterview(_))
+    ));
+    let first = system
+        .schedule_interview(&recruiter, "test", job_id, 1_000, 3_600, &["recruiter"])
+        .unwrap();
+    let second = system
+        .schedule_interview(
+            &recruiter,
+            "test",
+            job_id,
+            10_000,
+            3_600,
+            &["recruiter", "manager"],
+        )
+        .unwrap();
+
+    // Interviewers can't be in two interviews at once
+    assert!(matches!(
+        system.schedule_interview(&recruiter, "test2", job_id, 4_000, 3_600, &["manager", "recruiter"]),
+        Err(ErrorVariant::DoubleBooked { interview_id, .. }) if interview_id == first
+    ));
+    let third = system
+        .schedule_interview(&recruiter, "test2", job_id, 4_600, 3_600, &["manager"])
+        .unwrap();
+
+    let upcoming = system
+        .upcoming_interviews_for(&recruiter, "recruiter")
+        .unwrap();
+    assert_eq!(
+        upcoming
+            .iter()
+            .map(|interview| interview.id)
+            .collect::<Vec<_>>(),
+        vec![first, second]
+    );
+    assert_eq!(upcoming[0].round, 1);
+    assert_eq!(upcoming[1].round, 2);
+    assert_eq!(
+        system
+            .upcoming_interviews_for_job(&manager, job_id)
+            .unwrap()
+            .iter()
+            .map(|interview| interview.id)
+            .collect::<Vec<_>>(),
+        vec![first, third, second]
+    );
+
+    // Only scheduled interviews change, and only those that haven't ended are upcoming
+    system
+        .mark_interview(&recruiter, first, InterviewStatus::Completed)
+        .unwrap();
+    assert!(matches!(
+        system.mark_interview(&recruiter, first, InterviewStatus::Cancelled),
+        Err(ErrorVariant::InvalidTransition { .. })
+    ));
+    system
+        .mark_interview(&manager, third, InterviewStatus::NoShow)
+        .unwrap();
+    now.store(11_000, Ordering::SeqCst);
+    assert_eq!(
+        system.upcoming_interviews_for(&manager, "manager").unwrap()[0].id,
+        second
+    );
+    now.store(13_600, Ordering::SeqCst);
+    assert!(system
+        .upcoming_interviews_for_job(&manager, job_id)
+        .unwrap()
+        .is_empty());
+
+    // Cancelled rounds are reused
+    system
+        .mark_interview(&recruiter, second, InterviewStatus::Cancelled)
+        .unwrap();
+    let rescheduled = system
+        .schedule_interview(&recruiter, "test", job_id, 20_000, 3_600, &["recruiter"])
+        .unwrap();
+    assert_eq!(
+        system
+            .upcoming_interviews_for(&recruiter, "recruiter")
+            .unwrap()[0],
+        Interview {
+            id: rescheduled,
+            job_id,
+            candidate_id: candidate.user_id,
+            round: 2,
+            starts_at: 20_000,
+            duration: 3_600,
+            interviewers: vec![recruiter.user_id],
+            status: InterviewStatus::Scheduled,
+        }
+    );
+}
+
+#[test]
+fn scorecards() {
+    let system = test_builder(MemoryStore::new())
+        .require_scorecards(true)
+        .build()
+        .unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let manager = logged_in_with_role(&system, "manager", Role::HiringManager);
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    system
+        .register_candidate("test".to_string(), "test".to_string())
+        .unwrap();
+    let candidate = system.login("test", "test").unwrap();
+    system.apply(&candidate, job_id).unwrap();
+    system.interview(&recruiter, "test", job_id).unwrap();
+    let interview_id = system
+        .schedule_interview(&recruiter, "test", job_id, 1_000, 3_600, &["recruiter"])
+        .unwrap();
+
+    // Approving or rejecting needs a scorecard
+    assert!(matches!(
+        system.approve(&manager, "test", job_id),
+        Err(ErrorVariant::ScorecardRequired { job_id: id, user }) if id == job_id && user == "test"
+    ));
+    assert!(matches!(
+        system.reject(&manager, "test", job_id, "other", ""),
+        Err(ErrorVariant::ScorecardRequired { .. })
+    ));
+
+    assert!(matches!(
+        system.submit_scorecard(
+            &candidate,
+            "test",
+            job_id,
+            Scorecard::new(Recommendation::Yes)
+        ),
+        Err(ErrorVariant::PermissionDenied(Action::Interview))
+    ));
+    for invalid in [
+        Scorecard::new(Recommendation::Yes).rating("Rust", 6),
+        Scorecard::new(Recommendation::Yes).rating("", 3),
+        Scorecard::new(Recommendation::Yes)
+            .rating("Rust", 3)
+            .rating("Rust", 4),
+        // Only interviewers of the interview can submit for it
+        Scorecard::new(Recommendation::Yes).interview(interview_id),
+    ]
+    .iter()
+    {
+        assert!(matches!(
+            system.submit_scorecard(&manager, "test", job_id, invalid.clone()),
+            Err(ErrorVariant::InvalidScorecard(_))
+        ));
+    }
+    assert!(matches!(
+        system.submit_scorecard(
+            &manager,
+            "test",
+            job_id + 1,
+            Scorecard::new(Recommendation::Yes)
+        ),
+        Err(ErrorVariant::JobNotFound(_))
+    ));
+
+    system
+        .submit_scorecard(
+            &recruiter,
+            "test",
+            job_id,
+            Scorecard::new(Recommendation::StrongYes)
+                .rating("Rust", 5)
+                .rating("Communication", 3)
+                .interview(interview_id),
+        )
+        .unwrap();
+    assert!(matches!(
+        system.submit_scorecard(
+            &recruiter,
+            "test",
+            job_id,
+            Scorecard::new(Recommendation::No).interview(interview_id)
+        ),
+        Err(ErrorVariant::InvalidScorecard(_))
+    ));
+    system
+        .submit_scorecard(
+            &manager,
+            "test",
+            job_id,
+            Scorecard::new(Recommendation::No)
+                .rating("Rust", 4)
+                .notes("Needs more experience"),
+        )
+        .unwrap();
+
+    let scorecards = system.scorecards(&manager, "test", job_id).unwrap();
+    assert_eq!(scorecards.len(), 2);
+    assert_eq!(scorecards[0].interviewer_id, recruiter.user_id);
+    assert_eq!(scorecards[1].notes, "Needs more experience");
+    let summary = system.scorecard_summary(&manager, "test", job_id).unwrap();
+    assert_eq!(summary.scorecards, 2);
+    assert_eq!(summary.average_recommendation, Some(0.5));
+    assert_eq!(
+        summary.recommendations.get(&Recommendation::StrongYes),
+        Some(&1)
+    );
+    assert_eq!(
+        summary
+            .competencies
+            .iter()
+            .map(|c| (c.competency.as_str(), c.average, c.ratings))
+            .collect::<Vec<_>>(),
+        vec![("Communication", 3.0, 1), ("Rust", 4.5, 2)]
+    );
+
+    system.approve(&manager, "test", job_id).unwrap();
+}
+
+fn check_rejections_and_withdrawals<S: Store + 'static>(store: S) {
+    let system = test_builder(store)
+        .rejection_reasons(&["skills", "other"])
+        .build()
+        .unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let manager = logged_in_with_role(&system, "manager", Role::HiringManager);
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    for user in ["test", "test2", "test3"].iter() {
+        system
+            .register_candidate(user.to_string(), user.to_string())
+            .unwrap();
+        let candidate = system.login(user, user).unwrap();
+        system.apply(&candidate, job_id).unwrap();
+        system.interview(&recruiter, user, job_id).unwrap();
+    }
+    assert_eq!(system.rejection_reasons(), ["skills", "other"]);
+
+    // Rejections need one of the configured reasons
+    assert!(matches!(
+        system.reject(&manager, "test", job_id, "experience", ""),
+        Err(ErrorVariant::InvalidRejection(_))
+    ));
+    assert!(matches!(
+        system.advance(&manager, "test", job_id, "Rejected"),
+        Err(ErrorVariant::InvalidRejection(_))
+    ));
+    system
+        .reject(&manager, "test", job_id, "skills", "Needs more Rust")
+        .unwrap();
+
+    // Candidates withdraw their own applications, which cancels their scheduled interviews
+    let candidate = system.login("test2", "test2").unwrap();
+    let interview_id = system
+        .schedule_interview(&recruiter, "test2", job_id, 1_000, 3_600, &["recruiter"])
+        .unwrap();
+    assert!(matches!(
+        system.withdraw(&recruiter, job_id),
+        Err(ErrorVariant::PermissionDenied(Action::Withdraw))
+    ));
+    assert!(matches!(
+        system.withdraw(&candidate, job_id + 1),
+        Err(ErrorVariant::JobNotFound(_))
+    ));
+    system.withdraw(&candidate, job_id).unwrap();
+    assert!(matches!(
+        system.withdraw(&candidate, job_id),
+        Err(ErrorVariant::InvalidTransition { from, to }) if from == "Withdrawn" && to == "Withdrawn"
+    ));
+    assert!(system
+        .upcoming_interviews_for(&recruiter, "recruiter")
+        .unwrap()
+        .is_empty());
+    assert!(matches!(
+        system.mark_interview(&recruiter, interview_id, InterviewStatus::Completed),
+        Err(ErrorVariant::InvalidTransition { .. })
+    ));
+    // Nor can they go back to the process
+    assert!(matches!(
+        system.approve(&manager, "test2", job_id),
+        Err(ErrorVariant::InvalidTransition { .. })
+    ));
+    let rejected = system.login("test", "test").unwrap();
+    assert!(matches!(
+        system.withdraw(&rejected, job_id),
+        Err(ErrorVariant::InvalidTransition { from, .. }) if from == "Rejected"
+    ));
+
+    let job = system.list_jobs(&manager).unwrap().remove(0);
+    assert_eq!(job.applicants["test2"], "Withdrawn");
+    assert_eq!(job.rejections.len(), 1);
+    assert_eq!(job.rejections["test"].note, "Needs more Rust");
+
+    let report = system.job_report(&recruiter, job_id).unwrap();
+    assert_eq!(
+        (
+            report.applications,
+            report.active,
+            report.hired,
+            report.rejected,
+            report.withdrawn
+        ),
+        (3, 1, 0, 1, 1)
+    );
+    assert_eq!(
+        report.stages,
+        vec![
+            ("Applied".to_string(), 0),
+            ("Interviewed".to_string(), 1),
+            ("Rejected".to_string(), 1),
+            ("Approved".to_string(), 0),
+            ("Withdrawn".to_string(), 1),
+        ]
+    );
+    assert_eq!(report.rejection_reasons.get("skills"), Some(&1));
+    assert!(matches!(
+        system.job_report(&candidate, job_id),
+        Err(ErrorVariant::PermissionDenied(Action::Interview))
+    ));
+}
+
+#[test]
+fn rejections_and_withdrawals() {
+    check_rejections_and_withdrawals(MemoryStore::new());
+    #[cfg(feature = "sqlite")]
+    check_rejections_and_withdrawals(SqliteStore::open_in_memory().unwrap());
+}
+
+#[test]
+fn transitions() {
+    // The typed states only allow legal flows
+    assert_eq!(
+        AppliedApplication.interview().approve(),
+        ApprovedApplication
+    );
+    assert_eq!(AppliedApplication.interview().reject(), RejectedApplication);
+
+    // `Candidacy` checks them at runtime reporting both states
+    let interviewed = Candidacy::default().interview().unwrap();
+    assert_eq!(
+        interviewed.approve().unwrap(),
+        Candidacy::Approved(ApprovedApplication)
+    );
+    assert!(matches!(
+        interviewed.interview(),
+        Err(ErrorVariant::InvalidTransition { from, to })
+            if from == "Interviewed" && to == "Interviewed"
+    ));
+    let rejected = interviewed.reject().unwrap();
+    assert!(matches!(
+        rejected.approve(),
+        Err(ErrorVariant::InvalidTransition { from, to })
+            if from == "Rejected" && to == "Approved"
+    ));
+
+    // Candidates can withdraw until the process ends
+    assert_eq!(
+        AppliedApplication.interview().withdraw(),
+        WithdrawnApplication
+    );
+    assert_eq!(
+        Candidacy::default().withdraw().unwrap(),
+        Candidacy::Withdrawn(WithdrawnApplication)
+    );
+    assert!(matches!(
+        rejected.withdraw(),
+        Err(ErrorVariant::InvalidTransition { from, to })
+            if from == "Rejected" && to == "Withdrawn"
+    ));
+    assert_eq!(
+        Candidacy::from(4).stage(),
+        Pipeline::default_pipeline().withdrawn_stage().unwrap()
+    );
+}
+
+fn check_store_constraints<S: Store>(store: S) {
+    let job_id = store.add_job_posting(&Job::new("Engineer".into())).unwrap();
+    assert!(matches!(
+        store.add_job_posting(&Job::new("Engineer".into())),
+        Err(StoreError::Constraint(_))
+    ));
+    assert!(matches!(
+        store.get_job_by_id(job_id + 1),
+        Err(StoreError::NotFound)
+    ));
+
+    let candidate = Candidate {
+        user: "test".into(),
+        ..Candidate::default()
+    };
+    store.add_candidate(&candidate).unwrap();
+    assert!(matches!(
+        store.add_candidate(&candidate),
+        Err(StoreError::Constraint(_))
+    ));
+    let candidate_id = store.get_candidate("test").unwrap().id;
+
+    let mut application = Application {
+        job_id,
+        candidate_id,
+        stage: 0,
+        rejection: None,
+    };
+    assert!(store.insert_application(&application, &[]).is_ok());
+    // Only one application per job and candidate
+    assert!(matches!(
+        store.insert_application(&application, &[]),
+        Err(StoreError::Constraint(_))
+    ));
+    // Applications must reference existing jobs and candidates
+    assert!(matches!(
+        store.insert_application(
+            &Application {
+                job_id: job_id + 1,
+                rejection: None,
+                ..application
+            },
+            &[]
+        ),
+        Err(StoreError::Constraint(_))
+    ));
+    assert!(matches!(
+        store.insert_application(
+            &Application {
+                candidate_id: candidate_id + 1,
+                rejection: None,
+                ..application
+            },
+            &[]
+        ),
+        Err(StoreError::Constraint(_))
+    ));
+
+    application.stage = Candidacy::Interviewed(InterviewedApplication).stage();
+    assert_eq!(store.update_application(&application).unwrap(), 1);
+    assert_eq!(
+        store
+            .list_jobs()
+            .unwrap()
+            .first()
+            .unwrap()
+            .applicants
+            .get("test"),
+        Some(&"Interviewed".to_string())
+    );
+
+    // Rejections are kept with the application
+    let rejection = Rejection {
+        reason: "skills".into(),
+        note: String::new(),
+    };
+    application.rejection = Some(rejection.clone());
+    store.update_application(&application).unwrap();
+    assert_eq!(
+        store
+            .get_application(job_id, candidate_id)
+            .unwrap()
+            .rejection,
+        Some(rejection.clone())
+    );
+    assert_eq!(store.list_jobs().unwrap()[0].rejections["test"], rejection);
+    assert_eq!(
+        store.get_job_with_applicants(job_id).unwrap().rejections["test"],
+        rejection
+    );
+    assert!(matches!(
+        store.get_job_with_applicants(job_id + 42),
+        Err(StoreError::NotFound)
+    ));
+    application.rejection = None;
+    store.update_application(&application).unwrap();
+    assert!(store.list_jobs().unwrap()[0].rejections.is_empty());
+
+    let designer_id = store.add_job_posting(&Job::new("Designer".into())).unwrap();
+    let first_page = store.list_jobs_page(0, 1).unwrap();
+    assert_eq!(first_page.len(), 1);
+    assert_eq!(first_page[0].applicants.len(), 1);
+    let second_page = store.list_jobs_page(1, 1).unwrap();
+    assert_eq!(second_page[0].name, "Designer");
+    assert!(second_page[0].applicants.is_empty());
+    assert!(store.list_jobs_page(usize::MAX, 2).unwrap().is_empty());
+
+    // Interviews must be for an existing application with existing interviewers
+    let mut interview = Interview {
+        id: 0,
+        job_id,
+        candidate_id,
+        round: 1,
+        starts_at: 100,
+        duration: 60,
+        interviewers: vec![candidate_id],
+        status: InterviewStatus::Scheduled,
+    };
+    assert!(matches!(
+        store.add_interview(&Interview {
+            interviewers: vec![candidate_id + 1],
+            ..interview.clone()
+        }),
+        Err(StoreError::Constraint(_))
+    ));
+    assert!(matches!(
+        store.add_interview(&Interview {
+            job_id: job_id + 1,
+            ..interview.clone()
+        }),
+        Err(StoreError::Constraint(_))
+    ));
+    interview.id = store.add_interview(&interview).unwrap();
+    interview.status = InterviewStatus::Completed;
+    assert_eq!(store.update_interview(&interview).unwrap(), 1);
+    assert_eq!(store.get_interview(interview.id).unwrap(), interview);
+    assert_eq!(
+        store
+            .list_interviews(&InterviewFilter {
+                interviewer_id: Some(candidate_id),
+                ..Default::default()
+            })
+            .unwrap(),
+        vec![interview.clone()]
+    );
+    assert!(store
+        .list_interviews(&InterviewFilter {
+            job_id: Some(job_id + 1),
+            ..Default::default()
+        })
+        .unwrap()
+        .is_empty());
+
+    // Scorecards must be for an existing application, interviewer and interview
+    let scorecard = Scorecard {
+        job_id,
+        candidate_id,
+        interviewer_id: candidate_id,
+        submitted_at: 200,
+        ..Scorecard::new(Recommendation::Yes)
+            .rating("Rust", 4)
+            .rating("Communication", 3)
+            .notes("Good")
+            .interview(interview.id)
+    };
+    for invalid in [
+        Scorecard {
+            interviewer_id: candidate_id + 1,
+            ..scorecard.clone()
+        },
+        Scorecard {
+            job_id: job_id + 1,
+            ..scorecard.clone()
+        },
+        Scorecard {
+            interview_id: Some(interview.id + 1),
+            ..scorecard.clone()
+        },
+    ]
+    .iter()
+    {
+        assert!(matches!(
+            store.add_scorecard(invalid),
+            Err(StoreError::Constraint(_))
+        ));
+    }
+    let scorecard_id = store.add_scorecard(&scorecard).unwrap();
+    assert_eq!(
+        store.list_scorecards(job_id, candidate_id).unwrap(),
+        vec![Scorecard {
+            id: scorecard_id,
+            ..scorecard
+        }]
+    );
+    assert!(store
+        .list_scorecards(job_id + 1, candidate_id)
+        .unwrap()
+        .is_empty());
+
+    // Offers must be for an existing application
+    let offer = Offer {
+        id: 0,
+        job_id,
+        candidate_id,
+        salary: 50_000,
+        start_date: 1_000,
+        expires_at: 300,
+        status: OfferStatus::Sent,
+        created_at: 200,
+    };
+    assert!(matches!(
+        store.add_offer(&Offer {
+            job_id: job_id + 1,
+            ..offer.clone()
+        }),
+        Err(StoreError::Constraint(_))
+    ));
+    let offer_id = store.add_offer(&offer).unwrap();
+    let offer = Offer {
+        id: offer_id,
+        ..offer
+    };
+    assert_eq!(store.get_offer(offer_id).unwrap(), offer);
+    assert!(matches!(
+        store.get_offer(offer_id + 1),
+        Err(StoreError::NotFound)
+    ));
+    assert_eq!(
+        store
+            .list_offers(&OfferFilter {
+                candidate_id: Some(candidate_id),
+                ..OfferFilter::default()
+            })
+            .unwrap(),
+        vec![offer.clone()]
+    );
+    assert!(store
+        .list_offers(&OfferFilter {
+            job_id: Some(job_id + 1),
+            ..OfferFilter::default()
+        })
+        .unwrap()
+        .is_empty());
+    // Outstanding offers count against the job until they expire
+    assert_eq!(store.get_job_by_id(job_id).unwrap().offered, 1);
+    assert_eq!(store.expire_offers(299).unwrap(), 0);
+    assert_eq!(store.expire_offers(300).unwrap(), 1);
+    assert_eq!(
+        store.get_offer(offer_id).unwrap().status,
+        OfferStatus::Expired
+    );
+    assert_eq!(store.get_job_by_id(job_id).unwrap().offered, 0);
+    // Only the status changes
+    assert_eq!(
+        store
+            .update_offer(&Offer {
+                status: OfferStatus::Accepted,
+                salary: 1,
+                ..offer.clone()
+            })
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        store.get_offer(offer_id).unwrap(),
+        Offer {
+            status: OfferStatus::Accepted,
+            ..offer
+        }
+    );
+    assert_eq!(store.get_job_by_id(job_id).unwrap().hired, 1);
+
+    // Profiles must be of an existing candidate
+    assert!(matches!(
+        store.get_profile(candidate_id),
+        Err(StoreError::NotFound)
+    ));
+    assert!(matches!(
+        store.set_profile(&Profile {
+            candidate_id: candidate_id + 1,
+            ..Profile::new("Nobody")
+        }),
+        Err(StoreError::Constraint(_))
+    ));
+
+    // Attachments must be of an existing application, contents are stored once per hash
+    let attachment = Attachment {
+        id: 0,
+        job_id,
+        candidate_id,
+        kind: DocumentKind::Resume,
+        file_name: "cv.pdf".into(),
+        mime_type: "application/pdf".into(),
+        size: 3,
+        sha256: "abc".into(),
+        uploaded_at: 0,
+    };
+    assert!(matches!(
+        store.add_attachment(&Attachment {
+            candidate_id: candidate_id + 1,
+            ..attachment.clone()
+        }),
+        Err(StoreError::Constraint(_))
+    ));
+    let attachment_id = store.add_attachment(&attachment).unwrap();
+    store.add_attachment(&attachment).unwrap();
+    assert_eq!(
+        store.get_attachment(attachment_id).unwrap(),
+        Attachment {
+            id: attachment_id,
+            ..attachment.clone()
+        }
+    );
+    assert_eq!(
+        store.list_attachments(job_id, candidate_id).unwrap().len(),
+        2
+    );
+    assert_eq!(store.count_content_references("abc").unwrap(), 2);
+    store.put_content("abc", b"pdf").unwrap();
+    store.put_content("abc", b"other").unwrap();
+    assert_eq!(store.get_content("abc").unwrap(), b"pdf");
+    assert_eq!(store.delete_attachment(attachment_id).unwrap(), 1);
+    assert_eq!(store.delete_attachment(attachment_id).unwrap(), 0);
+    assert_eq!(store.count_content_references("abc").unwrap(), 1);
+    assert_eq!(store.delete_content("abc").unwrap(), 1);
+    assert!(matches!(
+        store.get_content("abc"),
+        Err(StoreError::NotFound)
+    ));
+    assert!(matches!(
+        store.get_attachment(attachment_id),
+        Err(StoreError::NotFound)
+    ));
+
+    // Job skills belong to an existing job
+    let skills = vec![
+        JobSkill::required("Rust", Proficiency::Advanced),
+        JobSkill::nice_to_have("SQL", Proficiency::Beginner),
+    ];
+    assert!(matches!(
+        store.set_job_skills(job_id + 42, &skills),
+        Err(StoreError::Constraint(_))
+    ));
+    assert!(store.get_job_skills(job_id).unwrap().is_empty());
+    store.set_job_skills(job_id, &skills[..1]).unwrap();
+    store.set_job_skills(job_id, &skills).unwrap();
+    assert_eq!(store.get_job_skills(job_id).unwrap(), skills);
+
+    // Questions belong to an existing job and answers to an existing application
+    let questions = vec![
+        Question::yes_no("Can you work in the EU?").knockout(Knockout::Requires(true)),
+        Question::multiple_choice("Languages", &["Rust", "C++", "Go"])
+            .knockout(Knockout::AllOf(vec!["Rust".into()])),
+        Question::numeric("Years of experience").knockout(Knockout::Range {
+            min: Some(2.0),
+            max: None,
+        }),
+        Question::single_choice("Notice period", &["None", "1 month"]).required(),
+        Question::free_text("Anything else?"),
+    ];
+    assert!(matches!(
+        store.set_questions(job_id + 42, &questions),
+        Err(StoreError::Constraint(_))
+    ));
+    assert!(store.get_questions(job_id).unwrap().is_empty());
+    store.set_questions(job_id, &questions[..1]).unwrap();
+    store.set_questions(job_id, &questions).unwrap();
+    assert_eq!(store.get_questions(job_id).unwrap(), questions);
+    let answers = vec![
+        (4, Answer::Text("Line\nbreaks".into())),
+        (0, Answer::YesNo(true)),
+        (1, Answer::Choices(vec!["Go".into(), "Rust".into()])),
+        (2, Answer::Number(3.5)),
+        (3, Answer::Choice("1 month".into())),
+    ];
+    let answered = Application {
+        job_id: designer_id,
+        candidate_id,
+        stage: 0,
+        rejection: None,
+    };
+    // A refused application doesn't store its answers either
+    assert!(matches!(
+        store.insert_application(
+            &Application {
+                job_id: designer_id,
+                candidate_id: candidate_id + 1,
+                stage: 0,
+                rejection: None,
+            },
+            &answers
+        ),
+        Err(StoreError::Constraint(_))
+    ));
+    assert!(store
+        .get_answers(designer_id, candidate_id + 1)
+        .unwrap()
+        .is_empty());
+    store.insert_application(&answered, &answers).unwrap();
+    let mut sorted = answers.clone();
+    sorted.sort_by_key(|(position, _)| *position);
+    assert_eq!(
+        store.get_answers(designer_id, candidate_id).unwrap(),
+        sorted
+    );
+    assert!(matches!(
+        store.insert_application(&answered, &answers[1..2]),
+        Err(StoreError::Constraint(_))
+    ));
+    assert_eq!(
+        store.get_answers(designer_id, candidate_id).unwrap(),
+        sorted
+    );
+
+    // Sessions must belong to an existing user and have unique IDs
+    let session = Session::new(candidate_id, 0, 10);
+    store.insert_session(&session).unwrap();
+    assert!(matches!(
+        store.insert_session(&session),
+        Err(StoreError::Constraint(_))
+    ));
+    assert!(matches!(
+        store.insert_session(&Session::new(candidate_id + 1, 0, 10)),
+        Err(StoreError::Constraint(_))
+    ));
+    assert_eq!(store.revoke_session(&session.id).unwrap(), 1);
+    assert!(store.get_session(&session.id).unwrap().revoked);
+    assert_eq!(store.revoke_session("missing").unwrap(), 0);
+    assert!(matches!(
+        store.get_session("missing"),
+        Err(StoreError::NotFound)
+    ));
+    assert_eq!(store.get_signing_key().unwrap(), None);
+    store.set_signing_key(b"key").unwrap();
+    store.set_signing_key(b"new key").unwrap();
+    assert_eq!(store.get_signing_key().unwrap(), Some(b"new key".to_vec()));
+
+    // Stores start with the default pipeline and jobs must use an existing one
+    assert_eq!(
+        store.list_pipelines().unwrap(),
+        vec![Pipeline::default_pipeline()]
+    );
+    assert!(matches!(
+        store.add_job_posting(&Job {
+            pipeline_id: 42,
+            ..Job::new("Other".into())
+        }),
+        Err(StoreError::Constraint(_))
+    ));
+    let pipeline = Pipeline::new("Short")
+        .stage("Applied", &["Hired", "Rejected"])
+        .terminal("Hired", Outcome::Hired)
+        .terminal("Rejected", Outcome::Rejected);
+    let pipeline_id = store.add_pipeline(&pipeline).unwrap();
+    assert_eq!(
+        store.get_pipeline(pipeline_id).unwrap(),
+        Pipeline {
+            id: pipeline_id,
+            ..pipeline.clone()
+        }
+    );
+    assert!(matches!(
+        store.add_pipeline(&pipeline),
+        Err(StoreError::Constraint(_))
+    ));
+    assert!(matches!(
+        store.get_pipeline(pipeline_id + 1),
+        Err(StoreError::NotFound)
+    ));
+}
+
+#[test]
+fn store_constraints() {
+    check_store_constraints(MemoryStore::new());
+    #[cfg(feature = "sqlite")]
+    check_store_constraints(SqliteStore::open_in_memory().unwrap());
+    check_store_constraints::<Box<dyn Store>>(Box::new(MemoryStore::new()));
+}
+
+#[test]
+fn independent_systems() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<HRSystem<MemoryStore>>();
+    #[cfg(feature = "sqlite")]
+    assert_send_sync::<HRSystem<SqliteStore>>();
+
+    let first = test_builder(MemoryStore::new())
+        .secret(b"first secret")
+        .clock(|| 42)
+        .build()
+        .unwrap();
+    let second = test_builder(MemoryStore::new())
+        .secret(b"second secret")
+        .build()
+        .unwrap();
+    assert_eq!(first.now(), 42);
+
+    let first_recruiter = logged_in_with_role(&first, "recruiter", Role::Recruiter);
+    let second_recruiter = logged_in_with_role(&second, "recruiter", Role::Recruiter);
+    let first_job = first
+        .create_job_posting(&first_recruiter, "Engineer".to_string())
+        .unwrap();
+    second
+        .create_job_posting(&second_recruiter, "Engineer".to_string())
+        .unwrap();
+    assert_eq!(second.list_jobs(&second_recruiter).unwrap().len(), 1);
+    for system in [&first, &second].iter() {
+        let _ = system.register_candidate("test".to_string(), "test".to_string());
+    }
+
+    // Tokens are only valid for the system that issued them
+    let logged_in_user = second.login("test", "test").unwrap();
+    assert!(first.apply(&logged_in_user, first_job).is_err());
+    assert!(first.list_jobs(&first_recruiter).unwrap()[0]
+        .applicants
+        .is_empty());
+}
+
+#[test]
+fn config_precedence() {
+    let mut config = Config::from_toml(
+        r#"
+        backend = "memory"
+        db_path = "from_file.db"
+        page_size = 5
+        password_iterations = 1000
+        require_scorecards = true
+        maintenance_user = "admin"
+        attachments_dir = "documents"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.backend, config::Backend::Memory);
+    assert_eq!(config.db_path, std::path::PathBuf::from("from_file.db"));
+    assert_eq!(config.secret, config::SecretSource::Random);
+    assert!(config.require_scorecards);
+    assert_eq!(
+        config.attachments_dir,
+        Some(std::path::PathBuf::from("documents"))
+    );
+
+    // Environment overrides the file, HR_SECRET overrides HR_SECRET_FILE
+    config
+        .apply_env(|var| match var {
+            "HR_PAGE_SIZE" => Some("2".to_string()),
+            "HR_SECRET_FILE" => Some("secret.key".to_string()),
+            "HR_SECRET" => Some("env secret".to_string()),
+            "HR_REQUIRE_SCORECARDS" => Some("false".to_string()),
+            "HR_REJECTION_REASONS" => Some("skills, other".to_string()),
+            "HR_MAINTENANCE_PASSWORD" => Some("admin".to_string()),
+            "HR_MAX_ATTACHMENT_SIZE" => Some("1024".to_string()),
+            "HR_ATTACHMENT_TYPES" => Some("application/pdf, text/plain".to_string()),
+            "HR_SKILL_DICTIONARY" => Some("Rust, Haskell".to_string()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(config.db_path, std::path::PathBuf::from("from_file.db"));
+    assert_eq!(config.page_size, 2);
+    assert!(!config.require_scorecards);
+    assert_eq!(config.rejection_reasons, vec!["skills", "other"]);
+    assert_eq!(config.max_attachment_size, 1024);
+    assert_eq!(
+        config.attachment_types,
+        vec!["application/pdf", "text/plain"]
+    );
+    assert_eq!(config.skill_dictionary, vec!["Rust", "Haskell"]);
+    assert_eq!(
+        (
+            config.maintenance_user.as_deref(),
+            config.maintenance_password.as_deref()
+        ),
+        (Some("admin"), Some("admin"))
+    );
+    assert_eq!(
+        config.secret,
+        config::SecretSource::Value(b"env secret".to_vec())
+    );
+
+    assert!(Config::from_toml("page_size = 0").is_err());
+    assert!(Config::from_toml("session_ttl = 9223372036854775807").is_err());
+    assert!(Config::from_toml("rejection_reasons = []").is_err());
+    assert!(Config::from_toml("max_attachment_size = 0").is_err());
+    assert!(Config::from_toml("unknown = 1").is_err());
+    assert!(config
+        .apply_env(|var| if var == "HR_BACKEND" {
+            Some("postgres".to_string())
+        } else {
+            None
+        })
+        .is_err());
+
+    let system = HRSystem::from_config(&config).unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    for name in ["Engineer", "Designer", "Manager"].iter() {
+        system
+            .create_job_posting(&recruiter, name.to_string())
+            .unwrap();
+    }
+    let names = |page| {
+        system
+            .list_jobs_page(&recruiter, page)
+            .unwrap()
+            .into_iter()
+            .map(|job| job.name)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(0), vec!["Engineer", "Designer"]);
+    assert_eq!(names(1), vec!["Manager"]);
+    assert!(names(2).is_empty());
+    // Pages whose offset overflows are empty too
+    assert!(names(usize::MAX).is_empty());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_migrations() {
+    use store::sqlite::SCHEMA_VERSION;
+    let path = std::env::temp_dir().join(format!("hr_migrations_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    // A DB created before migrations existed is adopted keeping its data
+    {
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "create table jobs (id integer primary key, name text not null unique, state integer not null);
+            create table candidates (id integer primary key, name text unique, password blob not null);
+            create table applications (state integer not null, job_id int, candidate_id int, PRIMARY KEY (job_id, candidate_id));
+            insert into jobs (name, state) values ('Engineer', 0);
+            insert into jobs (name, state) values ('Designer', 1);
+            insert into candidates (name, password) values ('test', x'');
+            insert into applications (state, job_id, candidate_id) values (1, 1, 1);
+            insert into applications (state, job_id, candidate_id) values (3, 2, 1);",
+        )
+        .unwrap();
     }
+    let store = SqliteStore::open(&path).unwrap();
+    assert_eq!(store.schema_version().unwrap(), SCHEMA_VERSION);
+    assert_eq!(store.list_jobs().unwrap()[0].name, "Engineer");
+    assert_eq!(store.list_jobs().unwrap()[0].headcount, 1);
+    assert_eq!(store.list_jobs().unwrap()[0].details, JobDetails::default());
+    assert_eq!(store.list_jobs().unwrap()[0].closes_at, None);
+    // Jobs posted before it was recorded count as posted at 0 and are indexed for search
+    assert_eq!(store.list_jobs().unwrap()[0].created_at, 0);
+    assert_eq!(
+        store.search_jobs(&JobSearch::new("engin")).unwrap()[0].name,
+        "Engineer"
+    );
+    // Old application states are the stages of the default pipeline
+    assert_eq!(
+        store.list_jobs().unwrap()[0].applicants["test"],
+        "Interviewed"
+    );
+    // Hires are kept as accepted offers
+    let designer = &store.list_jobs().unwrap()[1];
+    assert_eq!(designer.applicants["test"], "Approved");
+    assert_eq!((designer.hired, designer.open_slots()), (1, 0));
+    assert_eq!(
+        store.list_offers(&OfferFilter::default()).unwrap()[0].status,
+        OfferStatus::Accepted
+    );
+    // Which got its Withdrawn stage
+    assert_eq!(
+        store.get_pipeline(pipeline::DEFAULT_PIPELINE_ID).unwrap(),
+        Pipeline::default_pipeline()
+    );
+    // Unknown roles are a storage error rather than the least privileged role
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .execute("UPDATE candidates SET role = 9 WHERE name = 'test'", [])
+        .unwrap();
+    assert!(matches!(
+        store.get_candidate("test"),
+        Err(StoreError::Backend(_))
+    ));
+    // So are unknown outcomes rather than stages without one
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .execute(
+            "UPDATE pipeline_stages SET outcome = 9 WHERE outcome IS NOT NULL",
+            [],
+        )
+        .unwrap();
+    assert!(matches!(
+        store.get_pipeline(pipeline::DEFAULT_PIPELINE_ID),
+        Err(StoreError::Backend(_))
+    ));
+    drop(store);
+
+    // Reopening doesn't apply anything again
+    assert_eq!(
+        SqliteStore::open(&path).unwrap().schema_version().unwrap(),
+        SCHEMA_VERSION
+    );
+
+    // DBs from newer versions are refused
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .execute(
+            "INSERT INTO schema_version (version) values (?1)",
+            [SCHEMA_VERSION + 1],
+        )
+        .unwrap();
+    assert!(matches!(
+        SqliteStore::open(&path),
+        Err(StoreError::UnsupportedSchema { found, supported })
+            if found == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION
+    ));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn password_hashing() {
+    let first = PasswordHash::new("test", 1_000);
+    let second = PasswordHash::new("test", 1_000);
+    // Salted, the same password gives different hashes
+    assert_ne!(first, second);
+    assert!(first.verify("test") && second.verify("test"));
+    assert!(!first.verify("incorrect"));
+    assert!(!first.needs_rehash(1_000));
+    assert!(first.needs_rehash(2_000));
+
+    let legacy = PasswordHash::legacy("test");
+    assert!(legacy.verify("test"));
+    assert!(!legacy.verify("incorrect"));
+    assert!(legacy.needs_rehash(1));
+    assert!(!legacy.verify(""));
+    assert!(!PasswordHash::dummy(1_000).verify("test"));
+    assert!(!PasswordHash::dummy(1_000).verify(""));
+
+    let (algorithm, iterations, salt, hash) = first.to_parts();
+    assert_eq!(
+        PasswordHash::from_parts(algorithm, iterations, salt.into(), hash.into()),
+        Some(first.clone())
+    );
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn legacy_password_upgrade() {
+    let path = std::env::temp_dir().join(format!("hr_passwords_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let store = SqliteStore::open(&path).unwrap();
+    store
+        .add_candidate(&Candidate {
+            user: "test".into(),
+            password: PasswordHash::legacy("test"),
+            ..Candidate::default()
+        })
+        .unwrap();
+    let system = new_system(store);
+
+    let stored_algorithm = || -> String {
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .query_row(
+                "SELECT password_algorithm FROM candidates WHERE name = 'test'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap()
+    };
+    assert!(system.login("test", "incorrect").is_err());
+    assert_eq!(stored_algorithm(), PasswordHash::LEGACY_SHA256);
+    assert!(system.login("test", "test").is_ok());
+    assert_eq!(stored_algorithm(), PasswordHash::PBKDF2_SHA256);
+    // Still works after the upgrade
+    assert!(system.login("test", "test").is_ok());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn permissions() {
+    let system = new_system(MemoryStore::new());
+    system
+        .bootstrap_admin("admin".to_string(), "admin".to_string())
+        .unwrap();
+    // Only one admin can be bootstrapped
+    assert!(matches!(
+        system.bootstrap_admin("admin2".to_string(), "admin2".to_string()),
+        Err(ErrorVariant::PermissionDenied(Action::ManageUsers))
+    ));
+    let admin = system.login("admin", "admin").unwrap();
+    assert_eq!(admin.role, Role::Admin);
+
+    system
+        .register_candidate("test".to_string(), "test".to_string())
+        .unwrap();
+    let candidate = system.login("test", "test").unwrap();
+    assert_eq!(candidate.role, Role::Candidate);
+    assert!(matches!(
+        system.create_job_posting(&candidate, "Engineer".to_string()),
+        Err(ErrorVariant::PermissionDenied(Action::CreateJob))
+    ));
+    assert!(matches!(
+        system.set_role(&candidate, "test", Role::Admin),
+        Err(ErrorVariant::PermissionDenied(Action::ManageUsers))
+    ));
+
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    assert!(matches!(
+        system.apply(&recruiter, job_id),
+        Err(ErrorVariant::PermissionDenied(Action::Apply))
+    ));
+    system.apply(&candidate, job_id).unwrap();
+    system.interview(&recruiter, "test", job_id).unwrap();
+    assert!(matches!(
+        system.approve(&recruiter, "test", job_id),
+        Err(ErrorVariant::PermissionDenied(Action::Approve))
+    ));
+    assert!(matches!(
+        system.interview(&candidate, "test", job_id),
+        Err(ErrorVariant::PermissionDenied(Action::Interview))
+    ));
+
+    // The stored role is what counts, not the one of the logged user
+    system
+        .set_role(&admin, "recruiter", Role::HiringManager)
+        .unwrap();
+    assert_eq!(recruiter.role, Role::Recruiter);
+    system.approve(&recruiter, "test", job_id).unwrap();
+
+    // Forged tokens are refused
+    let forged = LoggedUser {
+        user: "admin".to_string(),
+        ..candidate
+    };
+    assert!(system.set_role(&forged, "test", Role::Admin).is_err());
+}
+
+#[test]
+fn sessions() {
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+    let now = Arc::new(AtomicI64::new(1_000));
+    let clock = now.clone();
+    let system = test_builder(MemoryStore::new())
+        .clock(move || clock.load(Ordering::SeqCst))
+        .session_ttl(100)
+        .build()
+        .unwrap();
+    system
+        .register_candidate("test".to_string(), "test".to_string())
+        .unwrap();
+
+    // Sessions expire after the TTL
+    let logged_user = system.login("test", "test").unwrap();
+    assert_eq!(logged_user.expires_at, 1_100);
+    system.check_token(&logged_user).unwrap();
+    now.store(1_100, Ordering::SeqCst);
+    assert!(matches!(
+        system.check_token(&logged_user),
+        Err(ErrorVariant::Unauthorized)
+    ));
+    assert!(system.list_jobs(&logged_user).is_err());
+
+    // Logging out revokes only that session
+    let first = system.login("test", "test").unwrap();
+    let second = system.login("test", "test").unwrap();
+    assert_ne!(first.session_id, second.session_id);
+    system.logout(&first).unwrap();
+    assert!(system.check_token(&first).is_err());
+    system.check_token(&second).unwrap();
+
+    // The token only signs its own session
+    let forged = LoggedUser {
+        session_id: first.session_id.clone(),
+        ..system.login("test", "test").unwrap()
+    };
+    assert!(system.check_token(&forged).is_err());
+    assert!(system.logout(&forged).is_err());
+    system.check_token(&second).unwrap();
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn unreadable_rows() {
+    let path = std::env::temp_dir().join(format!("hr_unreadable_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let system = HRSystem::builder()
+        .db_path(path.clone())
+        .clock(|| 0)
+        .password_iterations(1_000)
+        .build()
+        .unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let manager = logged_in_with_role(&system, "manager", Role::HiringManager);
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    let candidate = logged_in_with_role(&system, "test", Role::Candidate);
+    system.apply(&candidate, job_id).unwrap();
+    system.interview(&recruiter, "test", job_id).unwrap();
+    system.approve(&manager, "test", job_id).unwrap();
+    let offer_id = system
+        .create_offer(&manager, "test", job_id, 50_000, 1_000, 10_000)
+        .unwrap();
+    system.send_offer(&manager, offer_id).unwrap();
+    let attachment_id = system
+        .attach_document(
+            &candidate,
+            job_id,
+            DocumentKind::Resume,
+            "cv.txt",
+            "text/plain",
+            b"Rust developer",
+        )
+        .unwrap();
+    let conn = rusqlite::Connection::open(&path).unwrap();
+
+    // Text that isn't UTF-8 isn't parsed as a résumé
+    let binary = system
+        .attach_document(
+            &candidate,
+            job_id,
+            DocumentKind::Resume,
+            "cv.pdf",
+            "application/pdf",
+            b"%PDF-1.7\xff\xfe",
+        )
+        .unwrap();
+    conn.execute(
+        "UPDATE attachments SET mime_type = 'text/plain' WHERE id = (?1)",
+        [binary],
+    )
+    .unwrap();
+    assert!(matches!(
+        system.draft_profile_from_attachment(&candidate, binary),
+        Err(ErrorVariant::InvalidAttachment(_))
+    ));
+    conn.execute("DELETE FROM attachments WHERE id = (?1)", [binary])
+        .unwrap();
+
+    // Rows the store can't read are storage errors, not missing records
+    conn.execute("UPDATE offers SET status = 9", []).unwrap();
+    assert!(matches!(
+        system.accept_offer(&candidate, offer_id),
+        Err(ErrorVariant::Storage(_))
+    ));
+    conn.execute("UPDATE attachments SET kind = 9", []).unwrap();
+    assert!(matches!(
+        system.download_attachment(&candidate, attachment_id),
+        Err(ErrorVariant::Storage(_))
+    ));
+    assert!(matches!(
+        system.delete_attachment(&candidate, attachment_id),
+        Err(ErrorVariant::Storage(_))
+    ));
+    let attachments: i64 = conn
+        .query_row("SELECT count(*) FROM attachments", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(attachments, 1);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn atomic_applications() {
+    let path = std::env::temp_dir().join(format!("hr_atomic_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let system = HRSystem::builder()
+        .db_path(path.clone())
+        .password_iterations(1_000)
+        .build()
+        .unwrap();
+    let recruiter = logged_in_with_role(&system, "recruiter", Role::Recruiter);
+    let job_id = system
+        .create_job_posting(&recruiter, "Engineer".to_string())
+        .unwrap();
+    system
+        .set_questionnaire(
+            &recruiter,
+            job_id,
+            &[Question::yes_no("Can you work in the EU?").required()],
+        )
+        .unwrap();
+    let candidate = logged_in_with_role(&system, "test", Role::Candidate);
+
+    // When the answers can't be stored the application isn't either
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    conn.execute("ALTER TABLE answers RENAME TO answers_away", [])
+        .unwrap();
+    assert!(matches!(
+        system.apply_with_answers(&candidate, job_id, &[(0, Answer::YesNo(true))]),
+        Err(ErrorVariant::Storage(_))
+    ));
+    assert!(system.list_jobs(&recruiter).unwrap()[0]
+        .applicants
+        .is_empty());
+    conn.execute("ALTER TABLE answers_away RENAME TO answers", [])
+        .unwrap();
+    system
+        .apply_with_answers(&candidate, job_id, &[(0, Answer::YesNo(true))])
+        .unwrap();
+    assert_eq!(
+        system
+            .application_answers(&candidate, "test", job_id)
+            .unwrap()[0]
+            .1,
+        Answer::YesNo(true)
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn persistent_sessions() {
+    let path = std::env::temp_dir().join(format!("hr_sessions_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let open = || {
+        HRSystem::builder()
+            .db_path(path.clone())
+            .password_iterations(1_000)
+            .build()
+            .unwrap()
+    };
+
+    // The generated key is kept in the store, so sessions survive restarts
+    let system = open();
+    system
+        .register_candidate("test".to_string(), "test".to_string())
+        .unwrap();
+    let logged_user = system.login("test", "test").unwrap();
+    drop(system);
+    let system = open();
+    system.check_token(&logged_user).unwrap();
+    system.logout(&logged_user).unwrap();
+    drop(system);
+    assert!(open().check_token(&logged_user).is_err());
+    let _ = std::fs::remove_file(&path);
 }